@@ -6,7 +6,9 @@ CONTRACT_ADDRESS=$(cat ./contract_address.txt)
 
 MODULE_NAME=bounty_app
 
-ABI="export const ABI = $(curl https://fullnode.$NETWORK.aptoslabs.com/v1/accounts/$CONTRACT_ADDRESS/module/$MODULE_NAME | sed -n 's/.*"abi":\({.*}\).*}$/\1/p') as const" 
+ABI_JSON=$(curl https://fullnode.$NETWORK.aptoslabs.com/v1/accounts/$CONTRACT_ADDRESS/module/$MODULE_NAME | sed -n 's/.*"abi":\({.*}\).*}$/\1/p')
+
+ABI="export const ABI = $ABI_JSON as const"
 
 NEXT_APP_ABI_DIR="../../next-app/src/lib/abi"
 mkdir -p $NEXT_APP_ABI_DIR
@@ -15,3 +17,8 @@ echo $ABI > $NEXT_APP_ABI_DIR/${MODULE_NAME}_abi.ts
 NODE_SCRIPTS_ABI_DIR="../../node-scripts/src/lib/abi"
 mkdir -p $NODE_SCRIPTS_ABI_DIR
 echo $ABI > $NODE_SCRIPTS_ABI_DIR/${MODULE_NAME}_abi.ts
+
+# The indexer generates its on-chain structs from the raw JSON at build time
+INDEXER_ABI_DIR="../../indexer/abi"
+mkdir -p $INDEXER_ABI_DIR
+echo $ABI_JSON > $INDEXER_ABI_DIR/${MODULE_NAME}_abi.json
@@ -0,0 +1,195 @@
+//! Generates the Rust structs of the bounty_app events from the module ABI, see
+//! src/db_models/on_chain.rs. Refresh the ABI with contracts/bounty-app/sh_scripts/get_abis.sh
+//! after upgrading the contract, a changed event then fails to compile instead of failing to
+//! parse at runtime.
+
+use serde::Deserialize;
+use std::{collections::BTreeMap, env, fmt::Write, fs, path::Path};
+
+const ABI_PATH: &str = "abi/bounty_app_abi.json";
+const OUTPUT_FILE_NAME: &str = "bounty_app_on_chain.rs";
+
+#[derive(Deserialize)]
+struct MoveModuleAbi {
+    address: String,
+    name: String,
+    structs: Vec<MoveStructAbi>,
+}
+
+#[derive(Deserialize)]
+struct MoveStructAbi {
+    name: String,
+    fields: Vec<MoveFieldAbi>,
+}
+
+#[derive(Deserialize)]
+struct MoveFieldAbi {
+    name: String,
+    #[serde(rename = "type")]
+    move_type: String,
+}
+
+/// Split `0x1::object::Object<0x1::fungible_asset::Metadata>` into the base and its type params
+fn split_generic(move_type: &str) -> (&str, Vec<&str>) {
+    let Some(start) = move_type.find('<') else {
+        return (move_type, vec![]);
+    };
+    let inner = &move_type[start + 1..move_type.len() - 1];
+    let mut params = vec![];
+    let mut depth = 0;
+    let mut param_start = 0;
+    for (idx, c) in inner.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            ',' if depth == 0 => {
+                params.push(inner[param_start..idx].trim());
+                param_start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    params.push(inner[param_start..].trim());
+    (&move_type[..start], params)
+}
+
+struct Generator<'a> {
+    module_prefix: String,
+    structs: BTreeMap<&'a str, &'a MoveStructAbi>,
+    // Module structs reachable from the events
+    generated: Vec<&'a str>,
+}
+
+/// Rust field type and serde attribute for a Move type, integers above u32 are JSON strings
+struct RustType {
+    name: String,
+    serde_with: Option<&'static str>,
+}
+
+impl RustType {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            serde_with: None,
+        }
+    }
+}
+
+impl Generator<'_> {
+    fn map_type(&mut self, move_type: &str, location: &str) -> RustType {
+        let (base, params) = split_generic(move_type);
+        match (base, params.as_slice()) {
+            ("bool", []) => RustType::new("bool"),
+            ("u8" | "u16" | "u32", []) => RustType::new(base),
+            ("u64" | "u128", []) => RustType {
+                name: base.to_string(),
+                serde_with: Some("string_number"),
+            },
+            ("address", []) | ("0x1::string::String", []) => RustType::new("String"),
+            // The type param only matters on chain
+            ("0x1::object::Object", [_]) => RustType::new("MoveObject"),
+            ("0x1::option::Option", [inner]) => {
+                let inner = self.map_type(inner, location);
+                if inner.serde_with.is_some() {
+                    panic!(
+                        "Unsupported Move type {} in {}, add a mapping in build.rs",
+                        move_type, location
+                    );
+                }
+                RustType::new(&format!("MoveOption<{}>", inner.name))
+            }
+            (_, []) if base.starts_with(&self.module_prefix) => {
+                let struct_name = &base[self.module_prefix.len()..];
+                self.visit_struct(struct_name);
+                RustType::new(&format!("{}OnChain", struct_name))
+            }
+            _ => panic!(
+                "Unsupported Move type {} in {}, add a mapping in build.rs",
+                move_type, location
+            ),
+        }
+    }
+
+    fn visit_struct(&mut self, struct_name: &str) {
+        let (name, move_struct) = self
+            .structs
+            .get_key_value(struct_name)
+            .map(|(name, move_struct)| (*name, *move_struct))
+            .unwrap_or_else(|| panic!("Struct {} is missing from {}", struct_name, ABI_PATH));
+        if self.generated.contains(&name) {
+            return;
+        }
+        self.generated.push(name);
+        for field in &move_struct.fields {
+            self.map_type(&field.move_type, &format!("{}.{}", name, field.name));
+        }
+    }
+
+    fn generate_struct(&mut self, struct_name: &str) -> String {
+        let move_struct = self.structs[struct_name];
+        let mut code = String::new();
+        writeln!(
+            code,
+            "#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]"
+        )
+        .unwrap();
+        writeln!(
+            code,
+            "/// On-chain representation of bounty_app::{}",
+            struct_name
+        )
+        .unwrap();
+        writeln!(code, "pub struct {}OnChain {{", struct_name).unwrap();
+        for field in &move_struct.fields {
+            let rust_type =
+                self.map_type(&field.move_type, &format!("{}.{}", struct_name, field.name));
+            if let Some(serde_with) = rust_type.serde_with {
+                writeln!(code, "    #[serde(with = \"{}\")]", serde_with).unwrap();
+            }
+            writeln!(code, "    pub {}: {},", field.name, rust_type.name).unwrap();
+        }
+        writeln!(code, "}}").unwrap();
+        code
+    }
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", ABI_PATH);
+    println!("cargo:rerun-if-changed=build.rs");
+    // Migrations are embedded by embed_migrations!, pick up new ones without touching the code
+    println!("cargo:rerun-if-changed=src/db_migrations/migrations");
+
+    let abi_json = fs::read_to_string(ABI_PATH)
+        .unwrap_or_else(|e| panic!("Failed to read {}: {}", ABI_PATH, e));
+    let abi: MoveModuleAbi = serde_json::from_str(&abi_json)
+        .unwrap_or_else(|e| panic!("Failed to parse {}: {}", ABI_PATH, e));
+
+    let mut generator = Generator {
+        module_prefix: format!("{}::{}::", abi.address, abi.name),
+        structs: abi
+            .structs
+            .iter()
+            .map(|move_struct| (move_struct.name.as_str(), move_struct))
+            .collect(),
+        generated: vec![],
+    };
+    // Only the events and what they contain are read by the indexer, resources like Config may
+    // use types we don't map
+    for move_struct in &abi.structs {
+        if move_struct.name.ends_with("Event") {
+            generator.visit_struct(&move_struct.name);
+        }
+    }
+
+    let mut code = format!("// @generated by build.rs from {}, do not edit\n", ABI_PATH);
+    let mut struct_names = generator.generated.clone();
+    struct_names.sort();
+    for struct_name in struct_names {
+        code.push('\n');
+        code.push_str(&generator.generate_struct(struct_name));
+    }
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join(OUTPUT_FILE_NAME), code)
+        .unwrap_or_else(|e| panic!("Failed to write {}: {}", OUTPUT_FILE_NAME, e));
+}
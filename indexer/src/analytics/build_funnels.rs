@@ -0,0 +1,101 @@
+use diesel::{delete, sql_query, sql_types::Int8, QueryResult};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+use crate::{
+    processors::events::storers::{BUILD_STATUS_COMPLETED, BUILD_STATUS_READY_FOR_REVIEW},
+    schema::{bounty_build_funnels, payment_token_build_funnels},
+};
+
+/// Count builds at each step of create -> submit for review -> accept for every bounty,
+/// bounties without any build are included with zero counts. Builds accepted before
+/// submit_timestamp existed have none, they count as submitted from their status.
+const INSERT_BOUNTY_BUILD_FUNNELS_SQL: &str = "
+INSERT INTO bounty_build_funnels (
+    bounty_obj_addr,
+    payment_metadata_obj_addr,
+    build_created,
+    build_submitted_for_review,
+    build_completed,
+    submit_rate,
+    accept_rate
+)
+SELECT
+    bounties.bounty_obj_addr,
+    bounties.payment_metadata_obj_addr,
+    COUNT(builds.build_obj_addr),
+    COUNT(builds.build_obj_addr) FILTER (
+        WHERE builds.build_status IN ($1, $2) OR builds.submit_timestamp IS NOT NULL
+    ),
+    COUNT(builds.build_obj_addr) FILTER (WHERE builds.build_status = $1),
+    COALESCE(
+        COUNT(builds.build_obj_addr) FILTER (
+            WHERE builds.build_status IN ($1, $2) OR builds.submit_timestamp IS NOT NULL
+        )::FLOAT8 / NULLIF(COUNT(builds.build_obj_addr), 0),
+        0
+    ),
+    COALESCE(
+        COUNT(builds.build_obj_addr) FILTER (WHERE builds.build_status = $1)::FLOAT8
+            / NULLIF(COUNT(builds.build_obj_addr) FILTER (
+                WHERE builds.build_status IN ($1, $2) OR builds.submit_timestamp IS NOT NULL
+            ), 0),
+        0
+    )
+FROM bounties
+LEFT JOIN builds ON builds.bounty_obj_addr = bounties.bounty_obj_addr
+GROUP BY bounties.bounty_obj_addr, bounties.payment_metadata_obj_addr
+";
+
+/// Same as the bounty funnel, aggregated over all bounties paying in the same token.
+const INSERT_PAYMENT_TOKEN_BUILD_FUNNELS_SQL: &str = "
+INSERT INTO payment_token_build_funnels (
+    payment_metadata_obj_addr,
+    build_created,
+    build_submitted_for_review,
+    build_completed,
+    submit_rate,
+    accept_rate
+)
+SELECT
+    bounties.payment_metadata_obj_addr,
+    COUNT(builds.build_obj_addr),
+    COUNT(builds.build_obj_addr) FILTER (
+        WHERE builds.build_status IN ($1, $2) OR builds.submit_timestamp IS NOT NULL
+    ),
+    COUNT(builds.build_obj_addr) FILTER (WHERE builds.build_status = $1),
+    COALESCE(
+        COUNT(builds.build_obj_addr) FILTER (
+            WHERE builds.build_status IN ($1, $2) OR builds.submit_timestamp IS NOT NULL
+        )::FLOAT8 / NULLIF(COUNT(builds.build_obj_addr), 0),
+        0
+    ),
+    COALESCE(
+        COUNT(builds.build_obj_addr) FILTER (WHERE builds.build_status = $1)::FLOAT8
+            / NULLIF(COUNT(builds.build_obj_addr) FILTER (
+                WHERE builds.build_status IN ($1, $2) OR builds.submit_timestamp IS NOT NULL
+            ), 0),
+        0
+    )
+FROM bounties
+LEFT JOIN builds ON builds.bounty_obj_addr = bounties.bounty_obj_addr
+GROUP BY bounties.payment_metadata_obj_addr
+";
+
+/// Replace both funnel tables with recomputed ones, should run in a transaction.
+pub async fn refresh_build_funnels(conn: &mut AsyncPgConnection) -> QueryResult<()> {
+    delete(bounty_build_funnels::table).execute(conn).await?;
+    sql_query(INSERT_BOUNTY_BUILD_FUNNELS_SQL)
+        .bind::<Int8, _>(BUILD_STATUS_COMPLETED)
+        .bind::<Int8, _>(BUILD_STATUS_READY_FOR_REVIEW)
+        .execute(conn)
+        .await?;
+
+    delete(payment_token_build_funnels::table)
+        .execute(conn)
+        .await?;
+    sql_query(INSERT_PAYMENT_TOKEN_BUILD_FUNNELS_SQL)
+        .bind::<Int8, _>(BUILD_STATUS_COMPLETED)
+        .bind::<Int8, _>(BUILD_STATUS_READY_FOR_REVIEW)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use diesel::{delete, sql_query, QueryResult};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+use crate::schema::builder_cohort_retention;
+
+/// Group builders by the month (UTC) of their first build, then count for every later month how
+/// many builders of the cohort created at least one build in it.
+/// Months where no builder of the cohort came back have no row.
+const INSERT_BUILDER_COHORT_RETENTION_SQL: &str = "
+WITH builder_months AS (
+    SELECT DISTINCT
+        creator_addr,
+        EXTRACT(EPOCH FROM DATE_TRUNC('month', TO_TIMESTAMP(create_timestamp) AT TIME ZONE 'UTC'))::BIGINT AS month_timestamp,
+        (EXTRACT(YEAR FROM TO_TIMESTAMP(create_timestamp) AT TIME ZONE 'UTC') * 12
+            + EXTRACT(MONTH FROM TO_TIMESTAMP(create_timestamp) AT TIME ZONE 'UTC'))::BIGINT AS month_idx
+    FROM builds
+),
+cohorts AS (
+    SELECT
+        creator_addr,
+        MIN(month_timestamp) AS cohort_month_timestamp,
+        MIN(month_idx) AS cohort_month_idx
+    FROM builder_months
+    GROUP BY creator_addr
+),
+cohort_sizes AS (
+    SELECT cohort_month_timestamp, COUNT(*) AS cohort_size
+    FROM cohorts
+    GROUP BY cohort_month_timestamp
+)
+INSERT INTO builder_cohort_retention (
+    cohort_month_timestamp,
+    months_since_first_build,
+    cohort_size,
+    retained_builders,
+    retention_rate
+)
+SELECT
+    cohorts.cohort_month_timestamp,
+    builder_months.month_idx - cohorts.cohort_month_idx,
+    cohort_sizes.cohort_size,
+    COUNT(*),
+    COUNT(*)::FLOAT8 / cohort_sizes.cohort_size
+FROM builder_months
+JOIN cohorts ON cohorts.creator_addr = builder_months.creator_addr
+JOIN cohort_sizes ON cohort_sizes.cohort_month_timestamp = cohorts.cohort_month_timestamp
+GROUP BY
+    cohorts.cohort_month_timestamp,
+    builder_months.month_idx - cohorts.cohort_month_idx,
+    cohort_sizes.cohort_size
+";
+
+/// Replace the builder cohort retention table with a recomputed one, should run in a transaction.
+pub async fn refresh_builder_cohort_retention(conn: &mut AsyncPgConnection) -> QueryResult<()> {
+    delete(builder_cohort_retention::table)
+        .execute(conn)
+        .await?;
+    sql_query(INSERT_BUILDER_COHORT_RETENTION_SQL)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
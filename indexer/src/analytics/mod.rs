@@ -0,0 +1,56 @@
+//! Analytics that are too expensive to maintain per batch in the storers.
+//! They are recomputed from the indexed tables by the `refresh-analytics` command,
+//! which can run once (e.g. from a cron job) or on a fixed interval.
+
+pub mod build_funnels;
+pub mod builder_cohorts;
+
+use anyhow::{Context, Result};
+use diesel_async::AsyncConnection;
+use std::time::Duration;
+
+use self::{
+    build_funnels::refresh_build_funnels, builder_cohorts::refresh_builder_cohort_retention,
+};
+use crate::{
+    config::indexer_processor_config::IndexerProcessorConfig,
+    utils::{
+        database_connection::{get_db_connection, new_db_pool},
+        database_utils::ArcDbPool,
+    },
+};
+
+/// Recompute all analytics tables in a single transaction so readers never see a partial refresh.
+pub async fn refresh_analytics(pool: ArcDbPool) -> Result<()> {
+    let conn = &mut get_db_connection(&pool).await?;
+    conn.transaction(|conn| {
+        Box::pin(async move {
+            refresh_builder_cohort_retention(conn).await?;
+            refresh_build_funnels(conn).await?;
+            Ok::<(), diesel::result::Error>(())
+        })
+    })
+    .await
+    .context("Failed to refresh analytics")
+}
+
+/// Refresh analytics once, or forever every `interval` if set.
+pub async fn run_analytics_refresh(
+    config: IndexerProcessorConfig,
+    interval: Option<Duration>,
+) -> Result<()> {
+    let pool = new_db_pool(
+        &config.db_config.postgres_connection_string,
+        config.db_config.db_pool_size,
+    )
+    .await?;
+
+    loop {
+        refresh_analytics(pool.clone()).await?;
+        tracing::info!("Refreshed builder cohort retention and build funnels");
+        match interval {
+            Some(interval) => tokio::time::sleep(interval).await,
+            None => return Ok(()),
+        }
+    }
+}
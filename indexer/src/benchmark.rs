@@ -0,0 +1,239 @@
+//! Times the events storer on synthetic batches for every combination of chunk size and max
+//! concurrent DB tasks, to choose the `pipeline_config` values for a given database.
+//! The synthetic bounties, builds and user stats are written for real, so only run the
+//! `benchmark-storer` command against a throwaway database with the migrations applied.
+
+use anyhow::{bail, Result};
+use aptos_indexer_processor_sdk::{
+    traits::Processable, types::transaction_context::TransactionContext,
+};
+use bigdecimal::{BigDecimal, Zero};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::{
+    config::indexer_processor_config::{IndexerProcessorConfig, PipelineConfig},
+    db_models::{bounty::Bounty, build::Build, processor_status::ProcessorStatusQuery},
+    processors::events::{
+        events_extractor::ContractEvent,
+        events_storer::EventsStorer,
+        storers::{
+            APT_FA_ADDR, BUILD_STATUS_COMPLETED, BUILD_STATUS_IN_PROGRESS,
+            BUILD_STATUS_READY_FOR_REVIEW,
+        },
+    },
+    utils::{
+        database_connection::{get_db_connection, new_db_pool},
+        link_parser::{CONTACT_TYPE_OTHER, LINK_KIND_OTHER},
+    },
+};
+
+// Tables whose chunk size is set by the benchmark, the others use the configured value
+const BENCHMARKED_TABLES: [&str; 3] = ["bounties", "builds", "user_stats"];
+
+// Events of a batch are spread over this many users, so user stats get reduced like on chain
+const USERS_PER_BATCH: usize = 20;
+
+pub struct StorerBenchmarkArgs {
+    pub batches: usize,
+    pub bounties_per_batch: usize,
+    pub builds_per_bounty: usize,
+    pub chunk_sizes: Vec<usize>,
+    pub max_concurrent_db_tasks: Vec<usize>,
+}
+
+/// Store the same synthetic workload once per combination and log how long each took.
+pub async fn run_storer_benchmark(
+    config: IndexerProcessorConfig,
+    args: StorerBenchmarkArgs,
+) -> Result<()> {
+    let pool = new_db_pool(
+        &config.db_config.postgres_connection_string,
+        config.db_config.db_pool_size,
+    )
+    .await?;
+
+    let processor_name = config.processor_config.name();
+    if ProcessorStatusQuery::get_by_processor(processor_name, &mut get_db_connection(&pool).await?)
+        .await?
+        .is_some()
+    {
+        bail!(
+            "{} already indexed this database, run the benchmark against a throwaway one",
+            processor_name
+        );
+    }
+
+    // Object addresses are unique per invocation so runs don't update each other's rows
+    let nonce = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let mut run_idx = 0;
+    for &chunk_size in &args.chunk_sizes {
+        for &max_concurrent_db_tasks in &args.max_concurrent_db_tasks {
+            let pipeline_config = PipelineConfig {
+                per_table_chunk_sizes: BENCHMARKED_TABLES
+                    .iter()
+                    .map(|table_name| (table_name.to_string(), chunk_size))
+                    .collect(),
+                max_concurrent_db_tasks: Some(max_concurrent_db_tasks),
+                ..config.pipeline_config.clone()
+            };
+            pipeline_config.validate()?;
+            let mut events_storer = EventsStorer::new(
+                pool.clone(),
+                config.contract_config.contract_address.clone(),
+                processor_name.to_string(),
+                false,
+                pipeline_config,
+                config.analytics_config.clone(),
+            );
+
+            let mut event_count = 0;
+            let start = Instant::now();
+            for batch_idx in 0..args.batches {
+                let events = new_synthetic_batch(nonce, run_idx, batch_idx, &args);
+                event_count += events.len();
+                events_storer
+                    .process(TransactionContext {
+                        data: events,
+                        start_version: batch_idx as u64,
+                        end_version: batch_idx as u64,
+                        start_transaction_timestamp: None,
+                        end_transaction_timestamp: None,
+                        total_size_in_bytes: 0,
+                    })
+                    .await?;
+            }
+            let elapsed = start.elapsed();
+            tracing::info!(
+                chunk_size,
+                max_concurrent_db_tasks,
+                event_count,
+                elapsed_ms = elapsed.as_millis() as u64,
+                events_per_second = (event_count as f64 / elapsed.as_secs_f64()) as u64,
+                "Finished storer benchmark run"
+            );
+            run_idx += 1;
+        }
+    }
+    Ok(())
+}
+
+fn new_obj_addr(nonce: u64, run_idx: usize, batch_idx: usize, obj_idx: usize) -> String {
+    format!(
+        "0x{:016x}{:016x}{:016x}{:016x}",
+        nonce, run_idx, batch_idx, obj_idx
+    )
+}
+
+/// Every bounty gets created, then its builds are created and submitted, and the first build is
+/// accepted, the same order a batch from the chain would have.
+fn new_synthetic_batch(
+    nonce: u64,
+    run_idx: usize,
+    batch_idx: usize,
+    args: &StorerBenchmarkArgs,
+) -> Vec<ContractEvent> {
+    let timestamp = 1_729_000_000 + batch_idx as i64;
+    let txn_version = batch_idx as i64;
+    let user_addrs = (0..USERS_PER_BATCH)
+        .map(|user_idx| new_obj_addr(nonce, usize::MAX, 0, user_idx))
+        .collect::<Vec<_>>();
+
+    let mut bounties = vec![];
+    let mut builds = vec![];
+    let mut accepted_builds = vec![];
+    for bounty_idx in 0..args.bounties_per_batch {
+        let bounty_obj_addr = new_obj_addr(nonce, run_idx, batch_idx, bounty_idx);
+        bounties.push(Bounty {
+            bounty_obj_addr: bounty_obj_addr.clone(),
+            creator_addr: user_addrs[bounty_idx % USERS_PER_BATCH].clone(),
+            create_timestamp: timestamp,
+            end_timestamp: timestamp + 7 * 24 * 60 * 60,
+            last_update_timestamp: timestamp,
+            title: format!("Benchmark bounty {}", bounty_idx),
+            description_link: String::new(),
+            payment_metadata_obj_addr: APT_FA_ADDR.to_string(),
+            payment_per_winner: BigDecimal::from(100_000_000),
+            stake_required: BigDecimal::zero(),
+            stake_lockup_in_seconds: 0,
+            winner_count: 0,
+            winner_limit: 1,
+            total_payment: BigDecimal::from(100_000_000),
+            contact_info: String::new(),
+            last_update_event_idx: 0,
+            refunded_amount: BigDecimal::zero(),
+            contact_handle_type: CONTACT_TYPE_OTHER.to_string(),
+            contact_handle: None,
+            last_update_txn_version: txn_version,
+        });
+        for build_idx in 0..args.builds_per_bounty {
+            let obj_idx = args.bounties_per_batch * (build_idx + 1) + bounty_idx;
+            let creator_addr = user_addrs[(bounty_idx + build_idx + 1) % USERS_PER_BATCH].clone();
+            builds.push(Build {
+                build_obj_addr: new_obj_addr(nonce, run_idx, batch_idx, obj_idx),
+                bounty_obj_addr: bounty_obj_addr.clone(),
+                creator_addr: creator_addr.clone(),
+                payment_recipient_addr: creator_addr,
+                payment_amount: BigDecimal::zero(),
+                create_timestamp: timestamp,
+                last_update_timestamp: timestamp,
+                proof_link: String::new(),
+                build_status: BUILD_STATUS_IN_PROGRESS,
+                last_update_event_idx: 0,
+                submit_timestamp: None,
+                proof_link_kind: LINK_KIND_OTHER.to_string(),
+                proof_link_host: None,
+                proof_link_owner: None,
+                proof_link_repo: None,
+                proof_link_pr_number: None,
+                proof_link_normalized: None,
+                last_update_txn_version: txn_version,
+            });
+            if build_idx == 0 {
+                accepted_builds.push(builds[builds.len() - 1].clone());
+            }
+        }
+    }
+
+    let mut events = bounties
+        .iter()
+        .map(|bounty| ContractEvent::CreateBountyEvent(bounty.clone(), txn_version))
+        .collect::<Vec<_>>();
+    events.extend(
+        builds
+            .iter()
+            .map(|build| ContractEvent::CreateBuildEvent(build.clone(), txn_version)),
+    );
+    events.extend(builds.iter().map(|build| {
+        ContractEvent::SubmitBuildForReviewEvent(
+            Build {
+                build_status: BUILD_STATUS_READY_FOR_REVIEW,
+                submit_timestamp: Some(timestamp),
+                last_update_event_idx: 1,
+                ..build.clone()
+            },
+            txn_version,
+        )
+    }));
+    events.extend(
+        bounties
+            .into_iter()
+            .zip(accepted_builds)
+            .map(|(bounty, build)| {
+                ContractEvent::AcceptBuildEvent(
+                    Bounty {
+                        winner_count: 1,
+                        last_update_event_idx: 2,
+                        ..bounty.clone()
+                    },
+                    Build {
+                        build_status: BUILD_STATUS_COMPLETED,
+                        payment_amount: bounty.payment_per_winner,
+                        last_update_event_idx: 2,
+                        ..build
+                    },
+                    txn_version,
+                )
+            }),
+    );
+    events
+}
@@ -0,0 +1,334 @@
+use crate::utils::object_address::parse_address;
+use anyhow::{bail, Result};
+use serde::Serialize;
+
+// Mirror the constants in bounty_app.move, the contract counts bytes not chars
+pub const MAX_TITLE_LENGTH: usize = 100;
+pub const MAX_LINK_LENGTH: usize = 300;
+pub const MAX_CONTACT_INFO_LENGTH: usize = 100;
+
+pub const BOUNTY_APP_MODULE_NAME: &str = "bounty_app";
+// Variant index of TransactionPayload::EntryFunction in aptos-types
+const ENTRY_FUNCTION_PAYLOAD_VARIANT: u8 = 2;
+
+/// Move address, BCS encodes as 32 bytes without a length prefix
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct AccountAddress([u8; 32]);
+
+impl AccountAddress {
+    pub fn from_hex(addr: &str) -> Result<Self> {
+        Ok(Self(parse_address(addr)?))
+    }
+}
+
+/// Same layout as ModuleId in aptos-types
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ModuleId {
+    pub address: AccountAddress,
+    pub name: String,
+}
+
+/// Same layout as EntryFunction in aptos-types, each arg is already BCS encoded.
+/// None of the bounty_app entry functions take type args so they are always empty.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct EntryFunctionPayload {
+    pub module: ModuleId,
+    pub function: String,
+    pub ty_args: Vec<String>,
+    pub args: Vec<Vec<u8>>,
+}
+
+impl EntryFunctionPayload {
+    /// BCS bytes of the payload wrapped in TransactionPayload::EntryFunction, ready to be put in
+    /// a RawTransaction.
+    pub fn to_bcs_bytes(&self) -> Result<Vec<u8>> {
+        let mut bytes = vec![ENTRY_FUNCTION_PAYLOAD_VARIANT];
+        bytes.extend(bcs::to_bytes(self)?);
+        Ok(bytes)
+    }
+}
+
+/// Arguments of entry_create_bounty
+#[derive(Clone, Debug)]
+pub struct CreateBountyArgs {
+    pub title: String,
+    pub description_link: String,
+    // None means the bounty never ends
+    pub end_timestamp: Option<u64>,
+    // Object<Metadata> of the fungible asset paid to winners, must be in the payment allowlist
+    pub payment_metadata_obj_addr: String,
+    pub payment_per_winner: u64,
+    pub stake_required: u64,
+    pub stake_lockup_in_seconds: u64,
+    pub winner_limit: u64,
+    pub contact_info: String,
+}
+
+fn check_length(field: &str, value: &str, max_length: usize) -> Result<()> {
+    if value.is_empty() || value.len() > max_length {
+        bail!(
+            "{} must be between 1 and {} bytes, got {} bytes",
+            field,
+            max_length,
+            value.len()
+        );
+    }
+    Ok(())
+}
+
+fn encode_address(addr: &str) -> Result<Vec<u8>> {
+    Ok(bcs::to_bytes(&AccountAddress::from_hex(addr)?)?)
+}
+
+/// Builds payloads for the bounty_app entry functions deployed at `contract_addr`.
+/// Arguments are checked against the contract's asserts so invalid transactions fail before
+/// being simulated or paying gas.
+pub struct BountyAppPayloadBuilder {
+    contract_addr: AccountAddress,
+}
+
+impl BountyAppPayloadBuilder {
+    pub fn new(contract_addr: &str) -> Result<Self> {
+        Ok(Self {
+            contract_addr: AccountAddress::from_hex(contract_addr)?,
+        })
+    }
+
+    fn build(&self, function: &str, args: Vec<Vec<u8>>) -> EntryFunctionPayload {
+        EntryFunctionPayload {
+            module: ModuleId {
+                address: self.contract_addr,
+                name: BOUNTY_APP_MODULE_NAME.to_string(),
+            },
+            function: function.to_string(),
+            ty_args: vec![],
+            args,
+        }
+    }
+
+    pub fn entry_create_bounty(&self, args: &CreateBountyArgs) -> Result<EntryFunctionPayload> {
+        check_length("title", &args.title, MAX_TITLE_LENGTH)?;
+        check_length("description_link", &args.description_link, MAX_LINK_LENGTH)?;
+        check_length("contact_info", &args.contact_info, MAX_CONTACT_INFO_LENGTH)?;
+        match args.payment_per_winner.checked_mul(args.winner_limit) {
+            Some(0) => bail!("payment_per_winner * winner_limit must be greater than 0"),
+            // The contract would abort with an arithmetic error
+            None => bail!("payment_per_winner * winner_limit overflows u64"),
+            Some(_) => {}
+        }
+        Ok(self.build(
+            "entry_create_bounty",
+            vec![
+                bcs::to_bytes(&args.title)?,
+                bcs::to_bytes(&args.description_link)?,
+                // Move Option is a vector of 0 or 1 element, same BCS as a Rust Option
+                bcs::to_bytes(&args.end_timestamp)?,
+                encode_address(&args.payment_metadata_obj_addr)?,
+                bcs::to_bytes(&args.payment_per_winner)?,
+                bcs::to_bytes(&args.stake_required)?,
+                bcs::to_bytes(&args.stake_lockup_in_seconds)?,
+                bcs::to_bytes(&args.winner_limit)?,
+                bcs::to_bytes(&args.contact_info)?,
+            ],
+        ))
+    }
+
+    /// `payment_recipient_addr` defaults to the sender on chain when None
+    pub fn entry_create_build(
+        &self,
+        bounty_obj_addr: &str,
+        payment_recipient_addr: Option<&str>,
+    ) -> Result<EntryFunctionPayload> {
+        let payment_recipient = payment_recipient_addr
+            .map(AccountAddress::from_hex)
+            .transpose()?;
+        Ok(self.build(
+            "entry_create_build",
+            vec![
+                bcs::to_bytes(&payment_recipient)?,
+                encode_address(bounty_obj_addr)?,
+            ],
+        ))
+    }
+
+    /// The contract doesn't limit the proof link, we still apply MAX_LINK_LENGTH because the
+    /// indexer stores it in a VARCHAR(300) column.
+    pub fn submit_build_for_review(
+        &self,
+        build_obj_addr: &str,
+        proof_link: &str,
+    ) -> Result<EntryFunctionPayload> {
+        check_length("proof_link", proof_link, MAX_LINK_LENGTH)?;
+        Ok(self.build(
+            "submit_build_for_review",
+            vec![encode_address(build_obj_addr)?, bcs::to_bytes(proof_link)?],
+        ))
+    }
+
+    pub fn accept_build(&self, build_obj_addr: &str) -> Result<EntryFunctionPayload> {
+        Ok(self.build("accept_build", vec![encode_address(build_obj_addr)?]))
+    }
+
+    pub fn cancel_build(&self, build_obj_addr: &str) -> Result<EntryFunctionPayload> {
+        Ok(self.build("cancel_build", vec![encode_address(build_obj_addr)?]))
+    }
+
+    pub fn end_bounty(&self, bounty_obj_addr: &str) -> Result<EntryFunctionPayload> {
+        Ok(self.build("end_bounty", vec![encode_address(bounty_obj_addr)?]))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn new_builder() -> BountyAppPayloadBuilder {
+        BountyAppPayloadBuilder::new("0x1").unwrap()
+    }
+
+    fn new_create_bounty_args() -> CreateBountyArgs {
+        CreateBountyArgs {
+            title: "t".to_string(),
+            description_link: "l".to_string(),
+            end_timestamp: Some(1),
+            payment_metadata_obj_addr: "0xa".to_string(),
+            payment_per_winner: 2,
+            stake_required: 0,
+            stake_lockup_in_seconds: 0,
+            winner_limit: 3,
+            contact_info: "c".to_string(),
+        }
+    }
+
+    fn address_bytes(last_byte: u8) -> Vec<u8> {
+        let mut bytes = vec![0; 32];
+        bytes[31] = last_byte;
+        bytes
+    }
+
+    #[test]
+    fn test_entry_create_bounty_args() {
+        let payload = new_builder()
+            .entry_create_bounty(&new_create_bounty_args())
+            .unwrap();
+        assert_eq!(payload.function, "entry_create_bounty");
+        assert_eq!(
+            payload.args,
+            vec![
+                vec![1, b't'],
+                vec![1, b'l'],
+                vec![1, 1, 0, 0, 0, 0, 0, 0, 0],
+                address_bytes(0xa),
+                2u64.to_le_bytes().to_vec(),
+                vec![0; 8],
+                vec![0; 8],
+                3u64.to_le_bytes().to_vec(),
+                vec![1, b'c'],
+            ]
+        );
+
+        let no_end = new_builder()
+            .entry_create_bounty(&CreateBountyArgs {
+                end_timestamp: None,
+                ..new_create_bounty_args()
+            })
+            .unwrap();
+        assert_eq!(no_end.args[2], vec![0]);
+    }
+
+    #[test]
+    fn test_entry_create_bounty_limits() {
+        let builder = new_builder();
+        let cases = [
+            CreateBountyArgs {
+                title: "".to_string(),
+                ..new_create_bounty_args()
+            },
+            CreateBountyArgs {
+                title: "a".repeat(MAX_TITLE_LENGTH + 1),
+                ..new_create_bounty_args()
+            },
+            CreateBountyArgs {
+                // 2 bytes per char, so too long even though it's only 151 chars
+                description_link: "é".repeat(MAX_LINK_LENGTH / 2 + 1),
+                ..new_create_bounty_args()
+            },
+            CreateBountyArgs {
+                contact_info: "a".repeat(MAX_CONTACT_INFO_LENGTH + 1),
+                ..new_create_bounty_args()
+            },
+            CreateBountyArgs {
+                winner_limit: 0,
+                ..new_create_bounty_args()
+            },
+            CreateBountyArgs {
+                payment_per_winner: u64::MAX,
+                ..new_create_bounty_args()
+            },
+            CreateBountyArgs {
+                payment_metadata_obj_addr: "not_an_address".to_string(),
+                ..new_create_bounty_args()
+            },
+        ];
+        for args in cases {
+            assert!(builder.entry_create_bounty(&args).is_err(), "{:?}", args);
+        }
+        assert!(builder
+            .entry_create_bounty(&CreateBountyArgs {
+                title: "a".repeat(MAX_TITLE_LENGTH),
+                description_link: "a".repeat(MAX_LINK_LENGTH),
+                contact_info: "a".repeat(MAX_CONTACT_INFO_LENGTH),
+                ..new_create_bounty_args()
+            })
+            .is_ok());
+    }
+
+    #[test]
+    fn test_build_payloads() {
+        let builder = new_builder();
+        let create = builder.entry_create_build("0xb", Some("0xc")).unwrap();
+        assert_eq!(
+            create.args,
+            vec![[vec![1], address_bytes(0xc)].concat(), address_bytes(0xb),]
+        );
+        let create = builder.entry_create_build("0xb", None).unwrap();
+        assert_eq!(create.args[0], vec![0]);
+
+        let submit = builder.submit_build_for_review("0xd", "p").unwrap();
+        assert_eq!(submit.args, vec![address_bytes(0xd), vec![1, b'p']]);
+        assert!(builder.submit_build_for_review("0xd", "").is_err());
+
+        for (payload, function) in [
+            (builder.accept_build("0xd").unwrap(), "accept_build"),
+            (builder.cancel_build("0xd").unwrap(), "cancel_build"),
+            (builder.end_bounty("0xd").unwrap(), "end_bounty"),
+        ] {
+            assert_eq!(payload.function, function);
+            assert_eq!(payload.args, vec![address_bytes(0xd)]);
+        }
+    }
+
+    #[test]
+    fn test_to_bcs_bytes() {
+        let bytes = new_builder()
+            .end_bounty("0xd")
+            .unwrap()
+            .to_bcs_bytes()
+            .unwrap();
+        let expected = [
+            vec![ENTRY_FUNCTION_PAYLOAD_VARIANT],
+            address_bytes(1),
+            vec![10],
+            b"bounty_app".to_vec(),
+            vec![10],
+            b"end_bounty".to_vec(),
+            // no type args
+            vec![0],
+            // 1 arg of 32 bytes
+            vec![1, 32],
+            address_bytes(0xd),
+        ]
+        .concat();
+        assert_eq!(bytes, expected);
+    }
+}
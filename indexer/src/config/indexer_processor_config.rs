@@ -1,9 +1,14 @@
 use super::processor_config::ProcessorConfig;
-use crate::processors::events::events_processor::EventsProcessor;
-use anyhow::Result;
+use crate::{
+    processors::events::events_processor::EventsProcessor,
+    utils::database_utils::get_max_table_chunk_size,
+};
+use ahash::AHashMap;
+use anyhow::{anyhow, ensure, Result};
 use aptos_indexer_processor_sdk::aptos_indexer_transaction_stream::TransactionStreamConfig;
 use aptos_indexer_processor_sdk_server_framework::RunnableConfig;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 pub const QUERY_DEFAULT_RETRIES: u32 = 5;
 pub const QUERY_DEFAULT_RETRY_DELAY_MS: u64 = 500;
@@ -15,6 +20,16 @@ pub struct IndexerProcessorConfig {
     pub transaction_stream_config: TransactionStreamConfig,
     pub db_config: DbConfig,
     pub contract_config: ContractConfig,
+    #[serde(default)]
+    pub analytics_config: AnalyticsConfig,
+    #[serde(default)]
+    pub leaderboard_config: LeaderboardConfig,
+    #[serde(default)]
+    pub enrichment_config: EnrichmentConfig,
+    #[serde(default)]
+    pub pipeline_config: PipelineConfig,
+    #[serde(default)]
+    pub transaction_files_config: TransactionFilesConfig,
 }
 
 #[async_trait::async_trait]
@@ -47,6 +62,12 @@ pub struct DbConfig {
     // Size of the pool for writes/reads to the DB. Limits maximum number of queries in flight
     #[serde(default = "DbConfig::default_db_pool_size")]
     pub db_pool_size: u32,
+    // Write each batch, all event types plus the processor status, in a single transaction
+    #[serde(default)]
+    pub commit_batch_atomically: bool,
+    // Apply pending migrations before indexing, otherwise the indexer refuses to start on them
+    #[serde(default)]
+    pub run_migrations_on_startup: bool,
 }
 
 impl DbConfig {
@@ -55,8 +76,295 @@ impl DbConfig {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineConfig {
+    // Rows per insert by table name, tables not listed get as many rows as fit in one query
+    #[serde(default)]
+    pub per_table_chunk_sizes: AHashMap<String, usize>,
+    // Number of batches buffered between two steps of the pipeline
+    #[serde(default = "PipelineConfig::default_channel_size")]
+    pub channel_size: usize,
+    // Max chunks a storer writes at the same time, each on its own connection.
+    // Only bounded by db_pool_size if unset
+    #[serde(default)]
+    pub max_concurrent_db_tasks: Option<usize>,
+}
+
+impl PipelineConfig {
+    pub const fn default_channel_size() -> usize {
+        10
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        for (table_name, chunk_size) in &self.per_table_chunk_sizes {
+            ensure!(
+                *chunk_size > 0,
+                "Chunk size of table {} must be greater than 0",
+                table_name
+            );
+            let max_chunk_size = get_max_table_chunk_size(table_name)
+                .ok_or_else(|| anyhow!("Unknown table {} in per_table_chunk_sizes", table_name))?;
+            // Larger inserts go over the bind parameter limit of Postgres and fail at runtime
+            ensure!(
+                *chunk_size <= max_chunk_size,
+                "Chunk size of table {} must be at most {}",
+                table_name,
+                max_chunk_size
+            );
+        }
+        ensure!(self.channel_size > 0, "channel_size must be greater than 0");
+        ensure!(
+            self.max_concurrent_db_tasks != Some(0),
+            "max_concurrent_db_tasks must be greater than 0"
+        );
+        Ok(())
+    }
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            per_table_chunk_sizes: AHashMap::new(),
+            channel_size: Self::default_channel_size(),
+            max_concurrent_db_tasks: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TransactionFilesConfig {
+    // Write every batch received from the transaction stream to this directory
+    #[serde(default)]
+    pub record_dir: Option<PathBuf>,
+    // Read the batches recorded in this directory instead of connecting to the transaction stream
+    #[serde(default)]
+    pub replay_dir: Option<PathBuf>,
+}
+
+impl TransactionFilesConfig {
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            self.record_dir.is_none() || self.replay_dir.is_none(),
+            "record_dir and replay_dir can't be set together"
+        );
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct ContractConfig {
     pub contract_address: String,
 }
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AnalyticsConfig {
+    // Rebuild daily metrics from already indexed data before processing new transactions
+    #[serde(default)]
+    pub backfill_daily_metrics_on_startup: bool,
+    // A build waiting for review longer than this counts as overdue in creator stats
+    #[serde(default = "AnalyticsConfig::default_review_overdue_threshold_in_seconds")]
+    pub review_overdue_threshold_in_seconds: i64,
+}
+
+impl AnalyticsConfig {
+    pub const fn default_review_overdue_threshold_in_seconds() -> i64 {
+        // Weekly
+        7 * 24 * 60 * 60
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            self.review_overdue_threshold_in_seconds >= 0,
+            "review_overdue_threshold_in_seconds can't be negative"
+        );
+        Ok(())
+    }
+}
+
+impl Default for AnalyticsConfig {
+    fn default() -> Self {
+        Self {
+            backfill_daily_metrics_on_startup: false,
+            review_overdue_threshold_in_seconds: Self::default_review_overdue_threshold_in_seconds(
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct LeaderboardConfig {
+    // Take a leaderboard snapshot when this much chain time passed since the previous one
+    #[serde(default = "LeaderboardConfig::default_snapshot_interval_in_seconds")]
+    pub snapshot_interval_in_seconds: Option<u64>,
+    // Take a leaderboard snapshot when a batch crosses a multiple of this version
+    #[serde(default)]
+    pub snapshot_version_interval: Option<u64>,
+}
+
+impl LeaderboardConfig {
+    pub const fn default_snapshot_interval_in_seconds() -> Option<u64> {
+        // Daily
+        Some(24 * 60 * 60)
+    }
+}
+
+impl Default for LeaderboardConfig {
+    fn default() -> Self {
+        Self {
+            snapshot_interval_in_seconds: Self::default_snapshot_interval_in_seconds(),
+            snapshot_version_interval: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnrichmentConfig {
+    // Fetch the content behind bounty description links in the background
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "EnrichmentConfig::default_poll_interval_in_seconds")]
+    pub poll_interval_in_seconds: u64,
+    // Max number of links fetched per poll
+    #[serde(default = "EnrichmentConfig::default_batch_size")]
+    pub batch_size: u64,
+    // Give up on a link after this many consecutive failures
+    #[serde(default = "EnrichmentConfig::default_max_attempts")]
+    pub max_attempts: u64,
+    // Delay before the first retry, doubled on every failure
+    #[serde(default = "EnrichmentConfig::default_retry_base_delay_in_seconds")]
+    pub retry_base_delay_in_seconds: u64,
+    // Re-fetch successfully fetched links to pick up edits, never if unset
+    #[serde(default = "EnrichmentConfig::default_refresh_interval_in_seconds")]
+    pub refresh_interval_in_seconds: Option<u64>,
+    #[serde(default = "EnrichmentConfig::default_request_timeout_in_seconds")]
+    pub request_timeout_in_seconds: u64,
+    // Pages are cut off after this many bytes, larger GitHub API responses fail
+    #[serde(default = "EnrichmentConfig::default_max_response_size_in_bytes")]
+    pub max_response_size_in_bytes: usize,
+    // Also fetch links to loopback, private and link-local addresses, only for local testing
+    #[serde(default)]
+    pub allow_private_addresses: bool,
+    #[serde(default = "EnrichmentConfig::default_github_api_base_url")]
+    pub github_api_base_url: String,
+    // Unauthenticated requests to the GitHub API are limited to 60 per hour
+    #[serde(default)]
+    pub github_token: Option<String>,
+}
+
+impl EnrichmentConfig {
+    pub const fn default_poll_interval_in_seconds() -> u64 {
+        60
+    }
+
+    pub const fn default_batch_size() -> u64 {
+        20
+    }
+
+    pub const fn default_max_attempts() -> u64 {
+        8
+    }
+
+    pub const fn default_retry_base_delay_in_seconds() -> u64 {
+        60
+    }
+
+    pub const fn default_refresh_interval_in_seconds() -> Option<u64> {
+        // Weekly
+        Some(7 * 24 * 60 * 60)
+    }
+
+    pub const fn default_request_timeout_in_seconds() -> u64 {
+        10
+    }
+
+    pub const fn default_max_response_size_in_bytes() -> usize {
+        1024 * 1024
+    }
+
+    pub fn default_github_api_base_url() -> String {
+        "https://api.github.com".to_string()
+    }
+}
+
+impl Default for EnrichmentConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            poll_interval_in_seconds: Self::default_poll_interval_in_seconds(),
+            batch_size: Self::default_batch_size(),
+            max_attempts: Self::default_max_attempts(),
+            retry_base_delay_in_seconds: Self::default_retry_base_delay_in_seconds(),
+            refresh_interval_in_seconds: Self::default_refresh_interval_in_seconds(),
+            request_timeout_in_seconds: Self::default_request_timeout_in_seconds(),
+            max_response_size_in_bytes: Self::default_max_response_size_in_bytes(),
+            allow_private_addresses: false,
+            github_api_base_url: Self::default_github_api_base_url(),
+            github_token: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_validate_pipeline_config() {
+        assert!(PipelineConfig::default().validate().is_ok());
+
+        let mut config = PipelineConfig::default();
+        config.per_table_chunk_sizes.insert("builds".to_string(), 0);
+        assert!(config.validate().is_err());
+
+        let max_chunk_size = get_max_table_chunk_size("builds").unwrap();
+        let mut config = PipelineConfig::default();
+        config
+            .per_table_chunk_sizes
+            .insert("builds".to_string(), max_chunk_size);
+        assert!(config.validate().is_ok());
+        config
+            .per_table_chunk_sizes
+            .insert("builds".to_string(), max_chunk_size + 1);
+        assert!(config.validate().is_err());
+
+        let mut config = PipelineConfig::default();
+        config
+            .per_table_chunk_sizes
+            .insert("build".to_string(), 100);
+        assert!(config.validate().is_err());
+
+        let config = PipelineConfig {
+            max_concurrent_db_tasks: Some(0),
+            ..PipelineConfig::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_analytics_config() {
+        assert!(AnalyticsConfig::default().validate().is_ok());
+
+        let config = AnalyticsConfig {
+            review_overdue_threshold_in_seconds: -1,
+            ..AnalyticsConfig::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_transaction_files_config() {
+        assert!(TransactionFilesConfig::default().validate().is_ok());
+
+        let config = TransactionFilesConfig {
+            record_dir: Some(PathBuf::from("fixtures")),
+            replay_dir: Some(PathBuf::from("fixtures")),
+        };
+        assert!(config.validate().is_err());
+    }
+}
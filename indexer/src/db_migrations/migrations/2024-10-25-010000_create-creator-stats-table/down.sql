@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS creator_stats;
+ALTER TABLE builds DROP COLUMN IF EXISTS submit_timestamp;
@@ -0,0 +1,24 @@
+-- Your SQL goes here
+-- we store when the build was last submitted for review so we can measure how long creators take to review
+ALTER TABLE builds ADD COLUMN submit_timestamp BIGINT;
+-- builds in review were last updated when submitted, the submit time of builds already accepted is lost
+UPDATE builds SET submit_timestamp = last_update_timestamp WHERE build_status = 2;
+
+CREATE TABLE
+    creator_stats (
+        creator_addr VARCHAR(300) NOT NULL UNIQUE PRIMARY KEY,
+        last_update_timestamp BIGINT NOT NULL,
+        bounty_created BIGINT NOT NULL,
+        bounty_ended BIGINT NOT NULL,
+        apt_refunded BIGINT NOT NULL,
+        stable_refunded BIGINT NOT NULL,
+        build_submitted_for_review BIGINT NOT NULL,
+        build_accepted BIGINT NOT NULL,
+        build_in_review BIGINT NOT NULL,
+        -- builds still waiting for review after analytics_config.review_overdue_threshold_in_seconds
+        build_overdue_in_review BIGINT NOT NULL,
+        acceptance_ratio DOUBLE PRECISION NOT NULL,
+        -- null when the creator has not accepted any build yet
+        median_review_time_in_seconds BIGINT,
+        p90_review_time_in_seconds BIGINT
+    );
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS daily_metrics;
+ALTER TABLE bounties DROP COLUMN IF EXISTS refunded_amount;
@@ -0,0 +1,23 @@
+-- Your SQL goes here
+-- we store how much was sent back to the creator when the bounty is ended so refunds can be aggregated
+ALTER TABLE bounties ADD COLUMN refunded_amount BIGINT NOT NULL DEFAULT 0;
+
+CREATE TABLE
+    daily_metrics (
+        -- unix timestamp of 00:00:00 UTC of the day
+        day_timestamp BIGINT NOT NULL,
+        payment_metadata_obj_addr VARCHAR(300) NOT NULL,
+        -- address of the bounty app contract deployment that emitted the events
+        contract_addr VARCHAR(300) NOT NULL,
+        last_update_timestamp BIGINT NOT NULL,
+        bounty_created BIGINT NOT NULL,
+        build_created BIGINT NOT NULL,
+        build_submitted_for_review BIGINT NOT NULL,
+        build_canceled BIGINT NOT NULL,
+        build_completed BIGINT NOT NULL,
+        payout_volume BIGINT NOT NULL,
+        refunded_volume BIGINT NOT NULL,
+        new_users BIGINT NOT NULL,
+        returning_users BIGINT NOT NULL,
+        PRIMARY KEY (day_timestamp, payment_metadata_obj_addr, contract_addr)
+    );
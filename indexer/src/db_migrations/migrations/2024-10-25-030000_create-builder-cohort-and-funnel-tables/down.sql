@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS payment_token_build_funnels;
+DROP TABLE IF EXISTS bounty_build_funnels;
+DROP TABLE IF EXISTS builder_cohort_retention;
@@ -0,0 +1,36 @@
+-- Your SQL goes here
+CREATE TABLE
+    builder_cohort_retention (
+        -- unix timestamp of the first day of the month (UTC) of the builders' first build
+        cohort_month_timestamp BIGINT NOT NULL,
+        -- 0 is the cohort month itself
+        months_since_first_build BIGINT NOT NULL,
+        cohort_size BIGINT NOT NULL,
+        -- builders of the cohort that created at least one build in that month
+        retained_builders BIGINT NOT NULL,
+        retention_rate DOUBLE PRECISION NOT NULL,
+        PRIMARY KEY (cohort_month_timestamp, months_since_first_build)
+    );
+
+CREATE TABLE
+    bounty_build_funnels (
+        bounty_obj_addr VARCHAR(300) NOT NULL UNIQUE PRIMARY KEY,
+        payment_metadata_obj_addr VARCHAR(300) NOT NULL,
+        build_created BIGINT NOT NULL,
+        build_submitted_for_review BIGINT NOT NULL,
+        build_completed BIGINT NOT NULL,
+        -- share of created builds that were submitted for review
+        submit_rate DOUBLE PRECISION NOT NULL,
+        -- share of submitted builds that were accepted
+        accept_rate DOUBLE PRECISION NOT NULL
+    );
+
+CREATE TABLE
+    payment_token_build_funnels (
+        payment_metadata_obj_addr VARCHAR(300) NOT NULL UNIQUE PRIMARY KEY,
+        build_created BIGINT NOT NULL,
+        build_submitted_for_review BIGINT NOT NULL,
+        build_completed BIGINT NOT NULL,
+        submit_rate DOUBLE PRECISION NOT NULL,
+        accept_rate DOUBLE PRECISION NOT NULL
+    );
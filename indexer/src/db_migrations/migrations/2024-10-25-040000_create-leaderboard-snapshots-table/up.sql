@@ -0,0 +1,16 @@
+-- Your SQL goes here
+CREATE TABLE
+    leaderboard_snapshots (
+        -- which points column of user_stats is ranked, e.g. season_1 or all_time
+        season VARCHAR(50) NOT NULL,
+        -- last transaction version included in the snapshot
+        snapshot_version BIGINT NOT NULL,
+        snapshot_timestamp BIGINT NOT NULL,
+        user_addr VARCHAR(300) NOT NULL,
+        -- 1 is the top, ties on points are broken by earliest create_timestamp then user_addr
+        rank BIGINT NOT NULL,
+        points BIGINT NOT NULL,
+        PRIMARY KEY (season, snapshot_version, user_addr)
+    );
+
+CREATE INDEX leaderboard_snapshots_user_addr_idx ON leaderboard_snapshots (season, user_addr, snapshot_version);
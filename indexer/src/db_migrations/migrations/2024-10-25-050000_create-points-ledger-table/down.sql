@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP VIEW IF EXISTS points_ledger_mismatches;
+
+DROP TABLE IF EXISTS points_ledger;
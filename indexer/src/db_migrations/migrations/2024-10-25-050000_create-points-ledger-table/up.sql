@@ -0,0 +1,34 @@
+-- Your SQL goes here
+CREATE TABLE
+    points_ledger (
+        txn_version BIGINT NOT NULL,
+        -- on-chain event that awarded or deducted the points, e.g. CreateBuildEvent
+        event_type VARCHAR(50) NOT NULL,
+        -- bounty or build object the event is about
+        object_addr VARCHAR(300) NOT NULL,
+        user_addr VARCHAR(300) NOT NULL,
+        -- season the points count towards, every entry also counts towards total_points
+        season VARCHAR(50) NOT NULL,
+        -- negative for penalties
+        points_delta BIGINT NOT NULL,
+        timestamp BIGINT NOT NULL,
+        PRIMARY KEY (txn_version, event_type, object_addr, user_addr)
+    );
+
+CREATE INDEX points_ledger_user_addr_idx ON points_ledger (user_addr, txn_version);
+
+-- Users whose points in user_stats don't add up to their ledger entries, should always be empty
+CREATE VIEW
+    points_ledger_mismatches AS
+SELECT
+    user_stats.user_addr,
+    user_stats.season_1_points,
+    COALESCE(SUM(points_ledger.points_delta) FILTER (WHERE points_ledger.season = 'season_1'), 0) AS ledger_season_1_points,
+    user_stats.total_points,
+    COALESCE(SUM(points_ledger.points_delta), 0) AS ledger_total_points
+FROM user_stats
+LEFT JOIN points_ledger ON points_ledger.user_addr = user_stats.user_addr
+GROUP BY user_stats.user_addr
+HAVING
+    user_stats.season_1_points != COALESCE(SUM(points_ledger.points_delta) FILTER (WHERE points_ledger.season = 'season_1'), 0)
+    OR user_stats.total_points != COALESCE(SUM(points_ledger.points_delta), 0);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS bounty_search_documents;
+
+DROP FUNCTION IF EXISTS bounty_search_vector;
@@ -0,0 +1,26 @@
+-- Your SQL goes here
+-- Shared by the index and the search query, they must use the exact same expression for the index to be used
+CREATE FUNCTION
+    bounty_search_vector (title TEXT, description_text TEXT) RETURNS TSVECTOR LANGUAGE SQL IMMUTABLE AS $$
+    SELECT setweight(to_tsvector('english', title), 'A') || setweight(to_tsvector('english', description_text), 'B')
+$$;
+
+CREATE TABLE
+    bounty_search_documents (
+        bounty_obj_addr VARCHAR(300) NOT NULL PRIMARY KEY,
+        title VARCHAR(300) NOT NULL,
+        -- text fetched from the description link, empty until it's fetched
+        description_text TEXT NOT NULL DEFAULT '',
+        last_update_timestamp BIGINT NOT NULL
+    );
+
+CREATE INDEX bounty_search_documents_search_vector_idx ON bounty_search_documents USING GIN (bounty_search_vector (title, description_text));
+
+INSERT INTO
+    bounty_search_documents (bounty_obj_addr, title, last_update_timestamp)
+SELECT
+    bounty_obj_addr,
+    title,
+    create_timestamp
+FROM
+    bounties;
@@ -0,0 +1,24 @@
+-- Your SQL goes here
+CREATE TABLE
+    bounty_enrichment (
+        bounty_obj_addr VARCHAR(300) NOT NULL PRIMARY KEY,
+        description_link VARCHAR(300) NOT NULL,
+        -- pending, fetched, retrying, failed (gave up) or unsupported (link we can't fetch)
+        fetch_status VARCHAR(50) NOT NULL,
+        -- consecutive failed attempts, reset on success
+        attempt_count BIGINT NOT NULL,
+        -- unix timestamp of the next fetch, fetched rows are re-fetched to pick up edits
+        next_attempt_timestamp BIGINT NOT NULL,
+        last_attempt_timestamp BIGINT,
+        last_success_timestamp BIGINT,
+        last_error TEXT,
+        -- hex sha256 of the fetched content, used to skip unchanged content
+        content_hash VARCHAR(64),
+        title TEXT,
+        body_excerpt TEXT,
+        labels TEXT[] NOT NULL DEFAULT '{}',
+        repo_owner VARCHAR(300),
+        repo_name VARCHAR(300)
+    );
+
+CREATE INDEX bounty_enrichment_next_attempt_idx ON bounty_enrichment (fetch_status, next_attempt_timestamp);
@@ -0,0 +1,12 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE bounties DROP COLUMN IF EXISTS contact_handle;
+ALTER TABLE bounties DROP COLUMN IF EXISTS contact_handle_type;
+
+DROP INDEX IF EXISTS builds_proof_link_normalized_idx;
+
+ALTER TABLE builds DROP COLUMN IF EXISTS proof_link_normalized;
+ALTER TABLE builds DROP COLUMN IF EXISTS proof_link_pr_number;
+ALTER TABLE builds DROP COLUMN IF EXISTS proof_link_repo;
+ALTER TABLE builds DROP COLUMN IF EXISTS proof_link_owner;
+ALTER TABLE builds DROP COLUMN IF EXISTS proof_link_host;
+ALTER TABLE builds DROP COLUMN IF EXISTS proof_link_kind;
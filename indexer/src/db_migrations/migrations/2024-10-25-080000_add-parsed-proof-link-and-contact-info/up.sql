@@ -0,0 +1,18 @@
+-- Your SQL goes here
+-- parsed from proof_link by the indexer, existing rows keep the defaults until re-indexed
+-- github_pr, github_commit, github_repo, github_gist, tweet or other
+ALTER TABLE builds ADD COLUMN proof_link_kind VARCHAR(50) NOT NULL DEFAULT 'other';
+ALTER TABLE builds ADD COLUMN proof_link_host VARCHAR(300);
+-- GitHub owner, or X user for tweets
+ALTER TABLE builds ADD COLUMN proof_link_owner VARCHAR(300);
+ALTER TABLE builds ADD COLUMN proof_link_repo VARCHAR(300);
+ALTER TABLE builds ADD COLUMN proof_link_pr_number BIGINT;
+-- same proof submitted with a different url shape has the same value, used to find reused proofs
+ALTER TABLE builds ADD COLUMN proof_link_normalized VARCHAR(300);
+
+CREATE INDEX builds_proof_link_normalized_idx ON builds (proof_link_normalized);
+
+-- parsed from contact_info by the indexer
+-- discord, x, telegram, email or other
+ALTER TABLE bounties ADD COLUMN contact_handle_type VARCHAR(50) NOT NULL DEFAULT 'other';
+ALTER TABLE bounties ADD COLUMN contact_handle VARCHAR(100);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS unhandled_events;
+
+DROP TABLE IF EXISTS module_upgrades;
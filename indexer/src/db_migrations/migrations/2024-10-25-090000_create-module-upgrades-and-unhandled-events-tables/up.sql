@@ -0,0 +1,28 @@
+-- Your SQL goes here
+CREATE TABLE
+    module_upgrades (
+        -- publish or upgrade transaction
+        txn_version BIGINT NOT NULL UNIQUE PRIMARY KEY,
+        module_addr VARCHAR(300) NOT NULL,
+        package_name VARCHAR(100) NOT NULL,
+        -- 0 for the first publish, incremented by every upgrade
+        upgrade_number BIGINT NOT NULL,
+        -- modules in the package after this upgrade
+        module_names TEXT[] NOT NULL DEFAULT '{}',
+        timestamp BIGINT NOT NULL
+    );
+
+CREATE TABLE
+    unhandled_events (
+        txn_version BIGINT NOT NULL,
+        event_idx BIGINT NOT NULL,
+        type_str TEXT NOT NULL,
+        -- raw event JSON, so the event can be processed once the indexer knows how to decode it
+        data TEXT NOT NULL,
+        -- package version that emitted the event
+        upgrade_number BIGINT NOT NULL,
+        timestamp BIGINT NOT NULL,
+        PRIMARY KEY (txn_version, event_idx)
+    );
+
+CREATE INDEX unhandled_events_type_str_idx ON unhandled_events (type_str);
@@ -0,0 +1,24 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS payment_tokens;
+
+ALTER TABLE daily_metrics
+    ALTER COLUMN payout_volume TYPE BIGINT,
+    ALTER COLUMN refunded_volume TYPE BIGINT;
+
+ALTER TABLE creator_stats
+    ALTER COLUMN apt_refunded TYPE BIGINT,
+    ALTER COLUMN stable_refunded TYPE BIGINT;
+
+ALTER TABLE user_stats
+    ALTER COLUMN apt_spent TYPE BIGINT,
+    ALTER COLUMN stable_spent TYPE BIGINT,
+    ALTER COLUMN apt_received TYPE BIGINT,
+    ALTER COLUMN stable_received TYPE BIGINT;
+
+ALTER TABLE builds ALTER COLUMN payment_amount TYPE BIGINT;
+
+ALTER TABLE bounties
+    ALTER COLUMN payment_per_winner TYPE BIGINT,
+    ALTER COLUMN stake_required TYPE BIGINT,
+    ALTER COLUMN total_payment TYPE BIGINT,
+    ALTER COLUMN refunded_amount TYPE BIGINT;
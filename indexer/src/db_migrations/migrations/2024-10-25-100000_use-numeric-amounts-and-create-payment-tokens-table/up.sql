@@ -0,0 +1,42 @@
+-- Your SQL goes here
+-- amounts are in the smallest unit of the payment token, a u64 doesn't always fit in a BIGINT
+-- and neither do sums of them
+ALTER TABLE bounties
+    ALTER COLUMN payment_per_winner TYPE NUMERIC,
+    ALTER COLUMN stake_required TYPE NUMERIC,
+    ALTER COLUMN total_payment TYPE NUMERIC,
+    ALTER COLUMN refunded_amount TYPE NUMERIC;
+
+ALTER TABLE builds ALTER COLUMN payment_amount TYPE NUMERIC;
+
+ALTER TABLE user_stats
+    ALTER COLUMN apt_spent TYPE NUMERIC,
+    ALTER COLUMN stable_spent TYPE NUMERIC,
+    ALTER COLUMN apt_received TYPE NUMERIC,
+    ALTER COLUMN stable_received TYPE NUMERIC;
+
+ALTER TABLE creator_stats
+    ALTER COLUMN apt_refunded TYPE NUMERIC,
+    ALTER COLUMN stable_refunded TYPE NUMERIC;
+
+ALTER TABLE daily_metrics
+    ALTER COLUMN payout_volume TYPE NUMERIC,
+    ALTER COLUMN refunded_volume TYPE NUMERIC;
+
+-- display amount = amount / 10 ^ decimals, join on payment_metadata_obj_addr
+CREATE TABLE
+    payment_tokens (
+        payment_metadata_obj_addr VARCHAR(300) NOT NULL UNIQUE PRIMARY KEY,
+        symbol VARCHAR(50) NOT NULL,
+        decimals BIGINT NOT NULL
+    );
+
+-- add USDC and USDT once they are deployed, see APT_FA_ADDR in the indexer
+INSERT INTO
+    payment_tokens (payment_metadata_obj_addr, symbol, decimals)
+VALUES
+    (
+        '0x000000000000000000000000000000000000000000000000000000000000000a',
+        'APT',
+        8
+    );
@@ -0,0 +1,12 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS builds_last_update_timestamp_idx;
+
+DROP INDEX IF EXISTS builds_submit_timestamp_idx;
+
+DROP INDEX IF EXISTS builds_create_timestamp_idx;
+
+DROP INDEX IF EXISTS bounties_end_timestamp_idx;
+
+DROP INDEX IF EXISTS bounties_create_timestamp_idx;
+
+DROP TABLE IF EXISTS user_first_activities;
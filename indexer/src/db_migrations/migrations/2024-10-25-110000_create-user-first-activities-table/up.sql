@@ -0,0 +1,35 @@
+-- Your SQL goes here
+-- first day a user created a bounty, created a build or submitted a build, maintained by the
+-- daily metrics refresh so it doesn't scan every bounty and build to tell new users from returning
+CREATE TABLE
+    user_first_activities (
+        user_addr VARCHAR(300) NOT NULL UNIQUE PRIMARY KEY,
+        first_activity_timestamp BIGINT NOT NULL
+    );
+
+INSERT INTO
+    user_first_activities (user_addr, first_activity_timestamp)
+SELECT
+    user_addr,
+    MIN(ts)
+FROM
+    (
+        SELECT creator_addr AS user_addr, create_timestamp AS ts FROM bounties
+        UNION ALL
+        SELECT creator_addr, create_timestamp FROM builds
+        UNION ALL
+        SELECT creator_addr, submit_timestamp FROM builds WHERE submit_timestamp IS NOT NULL
+    ) AS user_activities
+GROUP BY
+    user_addr;
+
+-- the daily metrics refresh only reads the rows of the days a batch touched
+CREATE INDEX bounties_create_timestamp_idx ON bounties (create_timestamp);
+
+CREATE INDEX bounties_end_timestamp_idx ON bounties (end_timestamp);
+
+CREATE INDEX builds_create_timestamp_idx ON builds (create_timestamp);
+
+CREATE INDEX builds_submit_timestamp_idx ON builds (submit_timestamp);
+
+CREATE INDEX builds_last_update_timestamp_idx ON builds (last_update_timestamp);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE builds DROP COLUMN IF EXISTS last_update_txn_version;
+ALTER TABLE bounties DROP COLUMN IF EXISTS last_update_txn_version;
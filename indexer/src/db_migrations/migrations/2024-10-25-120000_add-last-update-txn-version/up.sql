@@ -0,0 +1,5 @@
+-- Your SQL goes here
+-- (last_update_timestamp, last_update_txn_version, last_update_event_idx) orders updates of a row,
+-- timestamps alone tie for events of the same second. Existing rows keep 0 until updated again
+ALTER TABLE bounties ADD COLUMN last_update_txn_version BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE builds ADD COLUMN last_update_txn_version BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,11 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS builds_proof_link_normalized_idx;
+CREATE INDEX builds_proof_link_normalized_idx ON builds (proof_link_normalized);
+
+-- longer values are cut off
+ALTER TABLE bounties ALTER COLUMN contact_handle TYPE VARCHAR(100) USING LEFT(contact_handle, 100);
+ALTER TABLE builds ALTER COLUMN proof_link_normalized TYPE VARCHAR(300) USING LEFT(proof_link_normalized, 300);
+ALTER TABLE builds ALTER COLUMN proof_link_repo TYPE VARCHAR(300) USING LEFT(proof_link_repo, 300);
+ALTER TABLE builds ALTER COLUMN proof_link_owner TYPE VARCHAR(300) USING LEFT(proof_link_owner, 300);
+ALTER TABLE builds ALTER COLUMN proof_link_host TYPE VARCHAR(300) USING LEFT(proof_link_host, 300);
+ALTER TABLE builds ALTER COLUMN proof_link TYPE VARCHAR(300) USING LEFT(proof_link, 300);
@@ -0,0 +1,14 @@
+-- Your SQL goes here
+-- the contract doesn't limit proof links, and parsed values are percent-encoded, so several times
+-- longer than the input for non-ASCII links
+ALTER TABLE builds ALTER COLUMN proof_link TYPE TEXT;
+ALTER TABLE builds ALTER COLUMN proof_link_host TYPE TEXT;
+ALTER TABLE builds ALTER COLUMN proof_link_owner TYPE TEXT;
+ALTER TABLE builds ALTER COLUMN proof_link_repo TYPE TEXT;
+ALTER TABLE builds ALTER COLUMN proof_link_normalized TYPE TEXT;
+-- contact_info is limited to 100 chars but a handle parsed from a URL is percent-encoded
+ALTER TABLE bounties ALTER COLUMN contact_handle TYPE TEXT;
+
+-- btree index entries are limited to about 2.7kB, hash indexes store a hash of the value
+DROP INDEX IF EXISTS builds_proof_link_normalized_idx;
+CREATE INDEX builds_proof_link_normalized_idx ON builds USING HASH (proof_link_normalized);
@@ -15,15 +15,80 @@ diesel::table! {
         description_link -> Varchar,
         #[max_length = 300]
         payment_metadata_obj_addr -> Varchar,
-        payment_per_winner -> Int8,
-        stake_required -> Int8,
+        payment_per_winner -> Numeric,
+        stake_required -> Numeric,
         stake_lockup_in_seconds -> Int8,
         winner_count -> Int8,
         winner_limit -> Int8,
-        total_payment -> Int8,
+        total_payment -> Numeric,
         #[max_length = 100]
         contact_info -> Varchar,
         last_update_event_idx -> Int8,
+        refunded_amount -> Numeric,
+        #[max_length = 50]
+        contact_handle_type -> Varchar,
+        contact_handle -> Nullable<Text>,
+        last_update_txn_version -> Int8,
+    }
+}
+
+diesel::table! {
+    bounty_build_funnels (bounty_obj_addr) {
+        #[max_length = 300]
+        bounty_obj_addr -> Varchar,
+        #[max_length = 300]
+        payment_metadata_obj_addr -> Varchar,
+        build_created -> Int8,
+        build_submitted_for_review -> Int8,
+        build_completed -> Int8,
+        submit_rate -> Float8,
+        accept_rate -> Float8,
+    }
+}
+
+diesel::table! {
+    bounty_enrichment (bounty_obj_addr) {
+        #[max_length = 300]
+        bounty_obj_addr -> Varchar,
+        #[max_length = 300]
+        description_link -> Varchar,
+        #[max_length = 50]
+        fetch_status -> Varchar,
+        attempt_count -> Int8,
+        next_attempt_timestamp -> Int8,
+        last_attempt_timestamp -> Nullable<Int8>,
+        last_success_timestamp -> Nullable<Int8>,
+        last_error -> Nullable<Text>,
+        #[max_length = 64]
+        content_hash -> Nullable<Varchar>,
+        title -> Nullable<Text>,
+        body_excerpt -> Nullable<Text>,
+        labels -> Array<Nullable<Text>>,
+        #[max_length = 300]
+        repo_owner -> Nullable<Varchar>,
+        #[max_length = 300]
+        repo_name -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    bounty_search_documents (bounty_obj_addr) {
+        #[max_length = 300]
+        bounty_obj_addr -> Varchar,
+        #[max_length = 300]
+        title -> Varchar,
+        description_text -> Text,
+        last_update_timestamp -> Int8,
+    }
+}
+
+diesel::table! {
+    builder_cohort_retention (cohort_month_timestamp, months_since_first_build) {
+        cohort_month_timestamp -> Int8,
+        months_since_first_build -> Int8,
+        cohort_size -> Int8,
+        retained_builders -> Int8,
+        retention_rate -> Float8,
     }
 }
 
@@ -37,13 +102,73 @@ diesel::table! {
         creator_addr -> Varchar,
         #[max_length = 300]
         payment_recipient_addr -> Varchar,
-        payment_amount -> Int8,
+        payment_amount -> Numeric,
         create_timestamp -> Int8,
         last_update_timestamp -> Int8,
-        #[max_length = 300]
-        proof_link -> Varchar,
+        proof_link -> Text,
         build_status -> Int8,
         last_update_event_idx -> Int8,
+        submit_timestamp -> Nullable<Int8>,
+        #[max_length = 50]
+        proof_link_kind -> Varchar,
+        proof_link_host -> Nullable<Text>,
+        proof_link_owner -> Nullable<Text>,
+        proof_link_repo -> Nullable<Text>,
+        proof_link_pr_number -> Nullable<Int8>,
+        proof_link_normalized -> Nullable<Text>,
+        last_update_txn_version -> Int8,
+    }
+}
+
+diesel::table! {
+    creator_stats (creator_addr) {
+        #[max_length = 300]
+        creator_addr -> Varchar,
+        last_update_timestamp -> Int8,
+        bounty_created -> Int8,
+        bounty_ended -> Int8,
+        apt_refunded -> Numeric,
+        stable_refunded -> Numeric,
+        build_submitted_for_review -> Int8,
+        build_accepted -> Int8,
+        build_in_review -> Int8,
+        build_overdue_in_review -> Int8,
+        acceptance_ratio -> Float8,
+        median_review_time_in_seconds -> Nullable<Int8>,
+        p90_review_time_in_seconds -> Nullable<Int8>,
+    }
+}
+
+diesel::table! {
+    daily_metrics (day_timestamp, payment_metadata_obj_addr, contract_addr) {
+        day_timestamp -> Int8,
+        #[max_length = 300]
+        payment_metadata_obj_addr -> Varchar,
+        #[max_length = 300]
+        contract_addr -> Varchar,
+        last_update_timestamp -> Int8,
+        bounty_created -> Int8,
+        build_created -> Int8,
+        build_submitted_for_review -> Int8,
+        build_canceled -> Int8,
+        build_completed -> Int8,
+        payout_volume -> Numeric,
+        refunded_volume -> Numeric,
+        new_users -> Int8,
+        returning_users -> Int8,
+    }
+}
+
+diesel::table! {
+    leaderboard_snapshots (season, snapshot_version, user_addr) {
+        #[max_length = 50]
+        season -> Varchar,
+        snapshot_version -> Int8,
+        snapshot_timestamp -> Int8,
+        #[max_length = 300]
+        user_addr -> Varchar,
+        rank -> Int8,
+        points -> Int8,
     }
 }
 
@@ -53,6 +178,57 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    module_upgrades (txn_version) {
+        txn_version -> Int8,
+        #[max_length = 300]
+        module_addr -> Varchar,
+        #[max_length = 100]
+        package_name -> Varchar,
+        upgrade_number -> Int8,
+        module_names -> Array<Nullable<Text>>,
+        timestamp -> Int8,
+    }
+}
+
+diesel::table! {
+    payment_token_build_funnels (payment_metadata_obj_addr) {
+        #[max_length = 300]
+        payment_metadata_obj_addr -> Varchar,
+        build_created -> Int8,
+        build_submitted_for_review -> Int8,
+        build_completed -> Int8,
+        submit_rate -> Float8,
+        accept_rate -> Float8,
+    }
+}
+
+diesel::table! {
+    payment_tokens (payment_metadata_obj_addr) {
+        #[max_length = 300]
+        payment_metadata_obj_addr -> Varchar,
+        #[max_length = 50]
+        symbol -> Varchar,
+        decimals -> Int8,
+    }
+}
+
+diesel::table! {
+    points_ledger (txn_version, event_type, object_addr, user_addr) {
+        txn_version -> Int8,
+        #[max_length = 50]
+        event_type -> Varchar,
+        #[max_length = 300]
+        object_addr -> Varchar,
+        #[max_length = 300]
+        user_addr -> Varchar,
+        #[max_length = 50]
+        season -> Varchar,
+        points_delta -> Int8,
+        timestamp -> Int8,
+    }
+}
+
 diesel::table! {
     processor_status (processor) {
         #[max_length = 50]
@@ -63,6 +239,25 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    unhandled_events (txn_version, event_idx) {
+        txn_version -> Int8,
+        event_idx -> Int8,
+        type_str -> Text,
+        data -> Text,
+        upgrade_number -> Int8,
+        timestamp -> Int8,
+    }
+}
+
+diesel::table! {
+    user_first_activities (user_addr) {
+        #[max_length = 300]
+        user_addr -> Varchar,
+        first_activity_timestamp -> Int8,
+    }
+}
+
 diesel::table! {
     user_stats (user_addr) {
         #[max_length = 300]
@@ -70,14 +265,14 @@ diesel::table! {
         create_timestamp -> Int8,
         last_update_timestamp -> Int8,
         bounty_created -> Int8,
-        apt_spent -> Int8,
-        stable_spent -> Int8,
+        apt_spent -> Numeric,
+        stable_spent -> Numeric,
         build_created -> Int8,
         build_submitted_for_review -> Int8,
         build_canceled -> Int8,
         build_completed -> Int8,
-        apt_received -> Int8,
-        stable_received -> Int8,
+        apt_received -> Numeric,
+        stable_received -> Numeric,
         season_1_points -> Int8,
         total_points -> Int8,
     }
@@ -85,8 +280,21 @@ diesel::table! {
 
 diesel::allow_tables_to_appear_in_same_query!(
     bounties,
+    bounty_build_funnels,
+    bounty_enrichment,
+    bounty_search_documents,
+    builder_cohort_retention,
     builds,
+    creator_stats,
+    daily_metrics,
+    leaderboard_snapshots,
     ledger_infos,
+    module_upgrades,
+    payment_token_build_funnels,
+    payment_tokens,
+    points_ledger,
     processor_status,
+    unhandled_events,
+    user_first_activities,
     user_stats,
 );
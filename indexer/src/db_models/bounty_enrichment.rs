@@ -0,0 +1,31 @@
+use diesel::{AsChangeset, Insertable, Queryable};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::bounty_enrichment;
+
+pub const FETCH_STATUS_PENDING: &str = "pending";
+pub const FETCH_STATUS_FETCHED: &str = "fetched";
+pub const FETCH_STATUS_RETRYING: &str = "retrying";
+pub const FETCH_STATUS_FAILED: &str = "failed";
+pub const FETCH_STATUS_UNSUPPORTED: &str = "unsupported";
+
+#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Serialize, Queryable)]
+#[diesel(table_name = bounty_enrichment, treat_none_as_null = true)]
+/// Database representation of the content fetched from a bounty's description link
+pub struct BountyEnrichment {
+    pub bounty_obj_addr: String,
+    pub description_link: String,
+    pub fetch_status: String,
+    pub attempt_count: i64,
+    pub next_attempt_timestamp: i64,
+    pub last_attempt_timestamp: Option<i64>,
+    pub last_success_timestamp: Option<i64>,
+    pub last_error: Option<String>,
+    pub content_hash: Option<String>,
+    pub title: Option<String>,
+    pub body_excerpt: Option<String>,
+    pub labels: Vec<Option<String>>,
+    pub repo_owner: Option<String>,
+    pub repo_name: Option<String>,
+}
@@ -0,0 +1,15 @@
+use diesel::{AsChangeset, Insertable, Queryable};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::bounty_search_documents;
+
+#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Serialize, Queryable)]
+#[diesel(table_name = bounty_search_documents)]
+/// Database representation of the searchable text of a bounty, indexed by bounty_search_vector
+pub struct BountySearchDocument {
+    pub bounty_obj_addr: String,
+    pub title: String,
+    pub description_text: String,
+    pub last_update_timestamp: i64,
+}
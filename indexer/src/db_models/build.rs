@@ -1,11 +1,21 @@
 use aptos_indexer_processor_sdk::utils::convert::standardize_address;
+use bigdecimal::{BigDecimal, Zero};
 use diesel::{AsChangeset, Insertable};
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
 
-use crate::schema::builds;
+use crate::{
+    schema::builds,
+    utils::link_parser::{parse_contact_info, parse_proof_link},
+};
 
-use super::bounty::{Bounty, BountyOnChain};
+use super::{
+    bounty::Bounty,
+    on_chain::{
+        to_amount, to_i64, to_total_payment, AcceptBuildEventOnChain, CancelBuildEventOnChain,
+        CreateBuildEventOnChain, SubmitBuildForReviewEventOnChain,
+    },
+};
 
 #[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Serialize)]
 #[diesel(table_name = builds)]
@@ -15,150 +25,157 @@ pub struct Build {
     pub bounty_obj_addr: String,
     pub creator_addr: String,
     pub payment_recipient_addr: String,
-    pub payment_amount: i64,
+    pub payment_amount: BigDecimal,
     pub create_timestamp: i64,
     pub last_update_timestamp: i64,
     pub proof_link: String,
     pub build_status: i64,
     pub last_update_event_idx: i64,
-}
-
-#[derive(Clone, Debug, Deserialize, Serialize)]
-pub struct OnChainObject {
-    pub inner: String,
-}
-
-#[derive(Clone, Debug, Deserialize, Serialize)]
-/// On-chain representation of a bounty
-pub struct BuildOnChain {
-    pub creator: String,
-    pub payment_recipient: String,
-    pub payment_amount: String,
-    pub create_timestamp: String,
-    pub last_update_timestamp: String,
-    pub proof_link: String,
-    pub bounty_object: OnChainObject,
-    pub status: String,
-}
-
-#[derive(Clone, Debug, Deserialize, Serialize)]
-/// On-chain representation of a create bounty event
-pub struct CreateBuildEventOnChain {
-    pub build_obj_addr: String,
-    pub build: BuildOnChain,
+    pub submit_timestamp: Option<i64>,
+    pub proof_link_kind: String,
+    pub proof_link_host: Option<String>,
+    pub proof_link_owner: Option<String>,
+    pub proof_link_repo: Option<String>,
+    pub proof_link_pr_number: Option<i64>,
+    pub proof_link_normalized: Option<String>,
+    pub last_update_txn_version: i64,
 }
 
 impl CreateBuildEventOnChain {
-    pub fn to_db_build(&self) -> Build {
-        let create_timestamp = self.build.create_timestamp.parse().unwrap();
+    pub fn to_db_build(&self, txn_version: i64) -> Build {
+        let parsed_proof_link = parse_proof_link(&self.build.proof_link);
+        let create_timestamp = to_i64(self.build.create_timestamp);
         Build {
             build_obj_addr: standardize_address(&self.build_obj_addr),
             bounty_obj_addr: standardize_address(&self.build.bounty_object.inner),
             creator_addr: standardize_address(self.build.creator.as_str()),
             payment_recipient_addr: standardize_address(self.build.payment_recipient.as_str()),
-            payment_amount: self.build.payment_amount.parse().unwrap(),
+            payment_amount: to_amount(self.build.payment_amount),
             create_timestamp,
             last_update_timestamp: create_timestamp,
             proof_link: self.build.proof_link.clone(),
-            build_status: self.build.status.parse().unwrap(),
+            build_status: to_i64(self.build.status),
             last_update_event_idx: 0,
+            submit_timestamp: None,
+            proof_link_kind: parsed_proof_link.kind.to_string(),
+            proof_link_host: parsed_proof_link.host,
+            proof_link_owner: parsed_proof_link.owner,
+            proof_link_repo: parsed_proof_link.repo,
+            proof_link_pr_number: parsed_proof_link.pr_number,
+            proof_link_normalized: parsed_proof_link.normalized,
+            last_update_txn_version: txn_version,
         }
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
-/// On-chain representation of a cancel bounty event
-pub struct CancelBuildEventOnChain {
-    pub build_obj_addr: String,
-    pub build: BuildOnChain,
-}
-
 impl CancelBuildEventOnChain {
-    pub fn to_db_build(&self, last_update_event_idx: i64) -> Build {
+    pub fn to_db_build(&self, last_update_txn_version: i64, last_update_event_idx: i64) -> Build {
+        let parsed_proof_link = parse_proof_link(&self.build.proof_link);
         Build {
             build_obj_addr: standardize_address(&self.build_obj_addr),
             bounty_obj_addr: standardize_address(&self.build.bounty_object.inner),
             creator_addr: standardize_address(self.build.creator.as_str()),
             payment_recipient_addr: standardize_address(self.build.payment_recipient.as_str()),
-            payment_amount: self.build.payment_amount.parse().unwrap(),
-            create_timestamp: self.build.create_timestamp.parse().unwrap(),
-            last_update_timestamp: self.build.last_update_timestamp.parse().unwrap(),
+            payment_amount: to_amount(self.build.payment_amount),
+            create_timestamp: to_i64(self.build.create_timestamp),
+            last_update_timestamp: to_i64(self.build.last_update_timestamp),
             proof_link: self.build.proof_link.clone(),
-            build_status: self.build.status.parse().unwrap(),
+            build_status: to_i64(self.build.status),
             last_update_event_idx,
+            // This value doesn't matter because we always use the original DB value
+            submit_timestamp: None,
+            proof_link_kind: parsed_proof_link.kind.to_string(),
+            proof_link_host: parsed_proof_link.host,
+            proof_link_owner: parsed_proof_link.owner,
+            proof_link_repo: parsed_proof_link.repo,
+            proof_link_pr_number: parsed_proof_link.pr_number,
+            proof_link_normalized: parsed_proof_link.normalized,
+            last_update_txn_version,
         }
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
-/// On-chain representation of a submit bounty for review event
-pub struct SubmitBuildForReviewEventOnChain {
-    pub build_obj_addr: String,
-    pub build: BuildOnChain,
-}
-
 impl SubmitBuildForReviewEventOnChain {
-    pub fn to_db_build(&self, last_update_event_idx: i64) -> Build {
+    pub fn to_db_build(&self, last_update_txn_version: i64, last_update_event_idx: i64) -> Build {
+        let parsed_proof_link = parse_proof_link(&self.build.proof_link);
+        let last_update_timestamp = to_i64(self.build.last_update_timestamp);
         Build {
             build_obj_addr: standardize_address(&self.build_obj_addr),
             bounty_obj_addr: standardize_address(&self.build.bounty_object.inner),
             creator_addr: standardize_address(self.build.creator.as_str()),
             payment_recipient_addr: standardize_address(self.build.payment_recipient.as_str()),
-            payment_amount: self.build.payment_amount.parse().unwrap(),
-            create_timestamp: self.build.create_timestamp.parse().unwrap(),
-            last_update_timestamp: self.build.last_update_timestamp.parse().unwrap(),
+            payment_amount: to_amount(self.build.payment_amount),
+            create_timestamp: to_i64(self.build.create_timestamp),
+            last_update_timestamp,
             proof_link: self.build.proof_link.clone(),
-            build_status: self.build.status.parse().unwrap(),
+            build_status: to_i64(self.build.status),
             last_update_event_idx,
+            submit_timestamp: Some(last_update_timestamp),
+            proof_link_kind: parsed_proof_link.kind.to_string(),
+            proof_link_host: parsed_proof_link.host,
+            proof_link_owner: parsed_proof_link.owner,
+            proof_link_repo: parsed_proof_link.repo,
+            proof_link_pr_number: parsed_proof_link.pr_number,
+            proof_link_normalized: parsed_proof_link.normalized,
+            last_update_txn_version,
         }
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
-/// On-chain representation of a submit bounty for review event
-pub struct AcceptBuildEventOnChain {
-    pub build_obj_addr: String,
-    pub build: BuildOnChain,
-    pub bounty: BountyOnChain,
-}
-
 impl AcceptBuildEventOnChain {
-    pub fn to_db_build(&self, last_update_event_idx: i64) -> Build {
+    pub fn to_db_build(&self, last_update_txn_version: i64, last_update_event_idx: i64) -> Build {
+        let parsed_proof_link = parse_proof_link(&self.build.proof_link);
         Build {
             build_obj_addr: standardize_address(&self.build_obj_addr),
             bounty_obj_addr: standardize_address(&self.build.bounty_object.inner),
             creator_addr: standardize_address(self.build.creator.as_str()),
             payment_recipient_addr: standardize_address(self.build.payment_recipient.as_str()),
-            payment_amount: self.build.payment_amount.parse().unwrap(),
-            create_timestamp: self.build.create_timestamp.parse().unwrap(),
-            last_update_timestamp: self.build.last_update_timestamp.parse().unwrap(),
+            payment_amount: to_amount(self.build.payment_amount),
+            create_timestamp: to_i64(self.build.create_timestamp),
+            last_update_timestamp: to_i64(self.build.last_update_timestamp),
             proof_link: self.build.proof_link.clone(),
-            build_status: self.build.status.parse().unwrap(),
+            build_status: to_i64(self.build.status),
             last_update_event_idx,
+            // This value doesn't matter because we always use the original DB value
+            submit_timestamp: None,
+            proof_link_kind: parsed_proof_link.kind.to_string(),
+            proof_link_host: parsed_proof_link.host,
+            proof_link_owner: parsed_proof_link.owner,
+            proof_link_repo: parsed_proof_link.repo,
+            proof_link_pr_number: parsed_proof_link.pr_number,
+            proof_link_normalized: parsed_proof_link.normalized,
+            last_update_txn_version,
         }
     }
-    pub fn to_db_bounty(&self, last_update_event_idx: i64) -> Bounty {
-        let payment_per_winner = self.bounty.payment_per_winner.parse().unwrap();
-        let winner_limit = self.bounty.winner_limit.parse().unwrap();
+    pub fn to_db_bounty(&self, last_update_txn_version: i64, last_update_event_idx: i64) -> Bounty {
+        let parsed_contact_info = parse_contact_info(&self.bounty.contact_info);
         Bounty {
             bounty_obj_addr: standardize_address(&self.build.bounty_object.inner),
             creator_addr: standardize_address(self.bounty.creator.as_str()),
-            create_timestamp: self.bounty.create_timestamp.parse().unwrap(),
-            end_timestamp: self.bounty.end_timestamp.parse().unwrap(),
-            last_update_timestamp: self.bounty.last_update_timestamp.parse().unwrap(),
+            create_timestamp: to_i64(self.bounty.create_timestamp),
+            end_timestamp: to_i64(self.bounty.end_timestamp),
+            last_update_timestamp: to_i64(self.bounty.last_update_timestamp),
             title: self.bounty.title.clone(),
             description_link: self.bounty.description_link.clone(),
             payment_metadata_obj_addr: standardize_address(
                 &self.bounty.payment_metadata_object.inner,
             ),
-            payment_per_winner,
-            stake_required: self.bounty.stake_required.parse().unwrap(),
-            stake_lockup_in_seconds: self.bounty.stake_lockup_in_seconds.parse().unwrap(),
-            winner_count: self.bounty.winner_count.parse().unwrap(),
-            winner_limit,
-            total_payment: payment_per_winner * winner_limit,
+            payment_per_winner: to_amount(self.bounty.payment_per_winner),
+            stake_required: to_amount(self.bounty.stake_required),
+            stake_lockup_in_seconds: to_i64(self.bounty.stake_lockup_in_seconds),
+            winner_count: to_i64(self.bounty.winner_count),
+            winner_limit: to_i64(self.bounty.winner_limit),
+            total_payment: to_total_payment(
+                self.bounty.payment_per_winner,
+                self.bounty.winner_limit,
+            ),
             contact_info: self.bounty.contact_info.clone(),
             last_update_event_idx,
+            // This value doesn't matter because we always use the original DB value
+            refunded_amount: BigDecimal::zero(),
+            contact_handle_type: parsed_contact_info.handle_type.to_string(),
+            contact_handle: parsed_contact_info.handle,
+            last_update_txn_version,
         }
     }
 }
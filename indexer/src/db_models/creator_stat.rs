@@ -0,0 +1,25 @@
+use bigdecimal::BigDecimal;
+use diesel::{AsChangeset, Insertable, Queryable};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::creator_stats;
+
+#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Serialize, Queryable)]
+#[diesel(table_name = creator_stats)]
+/// Database representation of a bounty creator's review responsiveness
+pub struct CreatorStat {
+    pub creator_addr: String,
+    pub last_update_timestamp: i64,
+    pub bounty_created: i64,
+    pub bounty_ended: i64,
+    pub apt_refunded: BigDecimal,
+    pub stable_refunded: BigDecimal,
+    pub build_submitted_for_review: i64,
+    pub build_accepted: i64,
+    pub build_in_review: i64,
+    pub build_overdue_in_review: i64,
+    pub acceptance_ratio: f64,
+    pub median_review_time_in_seconds: Option<i64>,
+    pub p90_review_time_in_seconds: Option<i64>,
+}
@@ -0,0 +1,25 @@
+use bigdecimal::BigDecimal;
+use diesel::{AsChangeset, Insertable, Queryable};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::daily_metrics;
+
+#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Serialize, Queryable)]
+#[diesel(table_name = daily_metrics)]
+/// Database representation of the activity of one UTC day for one payment token
+pub struct DailyMetric {
+    pub day_timestamp: i64,
+    pub payment_metadata_obj_addr: String,
+    pub contract_addr: String,
+    pub last_update_timestamp: i64,
+    pub bounty_created: i64,
+    pub build_created: i64,
+    pub build_submitted_for_review: i64,
+    pub build_canceled: i64,
+    pub build_completed: i64,
+    pub payout_volume: BigDecimal,
+    pub refunded_volume: BigDecimal,
+    pub new_users: i64,
+    pub returning_users: i64,
+}
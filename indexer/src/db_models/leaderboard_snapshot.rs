@@ -0,0 +1,41 @@
+use diesel::{ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable};
+use diesel_async::RunQueryDsl;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+use crate::{schema::leaderboard_snapshots, utils::database_utils::DbPoolConnection};
+
+// Season name and the user_stats column holding its points
+pub const LEADERBOARD_SEASONS: [(&str, &str); 2] = [
+    ("season_1", "season_1_points"),
+    ("all_time", "total_points"),
+];
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Insertable, Serialize, Queryable)]
+#[diesel(table_name = leaderboard_snapshots)]
+/// Database representation of a user's rank in one leaderboard snapshot
+pub struct LeaderboardSnapshot {
+    pub season: String,
+    pub snapshot_version: i64,
+    pub snapshot_timestamp: i64,
+    pub user_addr: String,
+    pub rank: i64,
+    pub points: i64,
+}
+
+impl LeaderboardSnapshot {
+    /// Returns the version and timestamp of the latest snapshot of any season.
+    pub async fn get_latest_version_and_timestamp(
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<Option<(i64, i64)>> {
+        leaderboard_snapshots::table
+            .select((
+                leaderboard_snapshots::snapshot_version,
+                leaderboard_snapshots::snapshot_timestamp,
+            ))
+            .order(leaderboard_snapshots::snapshot_version.desc())
+            .first::<(i64, i64)>(conn)
+            .await
+            .optional()
+    }
+}
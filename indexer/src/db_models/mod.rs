@@ -1,5 +1,14 @@
 pub mod bounty;
+pub mod bounty_enrichment;
+pub mod bounty_search_document;
 pub mod build;
+pub mod creator_stat;
+pub mod daily_metric;
+pub mod leaderboard_snapshot;
 pub mod ledger_info;
+pub mod module_upgrade;
+pub mod on_chain;
+pub mod points_ledger_entry;
 pub mod processor_status;
+pub mod unhandled_event;
 pub mod user_stat;
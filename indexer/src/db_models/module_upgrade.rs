@@ -0,0 +1,28 @@
+use diesel::{ExpressionMethods, Insertable, QueryDsl, Queryable};
+use diesel_async::RunQueryDsl;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+use crate::{schema::module_upgrades, utils::database_utils::DbPoolConnection};
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Insertable, Serialize, Queryable)]
+#[diesel(table_name = module_upgrades)]
+/// Database representation of a publish or upgrade of the contract package
+pub struct ModuleUpgrade {
+    pub txn_version: i64,
+    pub module_addr: String,
+    pub package_name: String,
+    pub upgrade_number: i64,
+    pub module_names: Vec<Option<String>>,
+    pub timestamp: i64,
+}
+
+impl ModuleUpgrade {
+    pub async fn get_all(conn: &mut DbPoolConnection<'_>) -> diesel::QueryResult<Vec<Self>> {
+        module_upgrades::table
+            .select(module_upgrades::all_columns)
+            .order(module_upgrades::txn_version.asc())
+            .load::<Self>(conn)
+            .await
+    }
+}
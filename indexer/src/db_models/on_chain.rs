@@ -0,0 +1,157 @@
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+
+/// On-chain representation of 0x1::object::Object<T>, the same for any T
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct MoveObject {
+    pub inner: String,
+}
+
+/// On-chain representation of 0x1::option::Option<T>, a vector of 0 or 1 element
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct MoveOption<T> {
+    pub vec: Vec<T>,
+}
+
+impl<T> MoveOption<T> {
+    pub fn into_option(self) -> Option<T> {
+        self.vec.into_iter().next()
+    }
+}
+
+/// u64 and u128 are strings in event JSON so they don't lose precision in JS
+pub mod string_number {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use std::{fmt::Display, str::FromStr};
+
+    pub fn serialize<T: Display, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Timestamps and counters are BIGINT columns, so values above i64::MAX can't be stored
+pub fn to_i64(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or_else(|_| panic!("{} doesn't fit in a BIGINT column", value))
+}
+
+/// Amounts are NUMERIC columns in the token's smallest unit, any u64 fits
+pub fn to_amount(value: u64) -> BigDecimal {
+    BigDecimal::from(value)
+}
+
+/// The contract computes the total in u64 and aborts on overflow, a product that doesn't fit
+/// means the event doesn't come from the contract we decode
+pub fn to_total_payment(payment_per_winner: u64, winner_limit: u64) -> BigDecimal {
+    let total = payment_per_winner
+        .checked_mul(winner_limit)
+        .unwrap_or_else(|| {
+            panic!(
+                "Total payment of {} * {} overflows u64",
+                payment_per_winner, winner_limit
+            )
+        });
+    to_amount(total)
+}
+
+// Framework resources are not in our ABI, only the fields we read are declared
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+/// On-chain representation of 0x1::code::PackageRegistry, written on every publish and upgrade
+pub struct PackageRegistryOnChain {
+    pub packages: Vec<PackageMetadataOnChain>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+/// On-chain representation of 0x1::code::PackageMetadata
+pub struct PackageMetadataOnChain {
+    pub name: String,
+    #[serde(with = "string_number")]
+    pub upgrade_number: u64,
+    pub modules: Vec<ModuleMetadataOnChain>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+/// On-chain representation of 0x1::code::ModuleMetadata
+pub struct ModuleMetadataOnChain {
+    pub name: String,
+}
+
+include!(concat!(env!("OUT_DIR"), "/bounty_app_on_chain.rs"));
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const BUILD_JSON: &str = r#"{
+        "creator": "0x1",
+        "payment_recipient": "0x2",
+        "payment_amount": "100",
+        "create_timestamp": "1729000000",
+        "last_update_timestamp": "1729000001",
+        "proof_link": "https://github.com/o/r/pull/1",
+        "bounty_object": {"inner": "0x3"},
+        "status": "2"
+    }"#;
+
+    #[test]
+    fn test_deserialize_build() {
+        let build: BuildOnChain = serde_json::from_str(BUILD_JSON).unwrap();
+        assert_eq!(build.payment_amount, 100);
+        assert_eq!(build.last_update_timestamp, 1729000001);
+        assert_eq!(build.bounty_object.inner, "0x3");
+        assert_eq!(build.status, 2);
+
+        // Round trips to the same JSON shape the chain emits
+        let value = serde_json::to_value(&build).unwrap();
+        assert_eq!(value["payment_amount"], "100");
+        assert_eq!(
+            serde_json::from_value::<BuildOnChain>(value).unwrap(),
+            build
+        );
+    }
+
+    #[test]
+    fn test_deserialize_invalid_number() {
+        let json = BUILD_JSON.replace(r#""payment_amount": "100""#, r#""payment_amount": "-1""#);
+        assert!(serde_json::from_str::<BuildOnChain>(&json).is_err());
+        let json = BUILD_JSON.replace(r#""payment_amount": "100""#, r#""payment_amount": 100"#);
+        assert!(serde_json::from_str::<BuildOnChain>(&json).is_err());
+    }
+
+    #[test]
+    fn test_amounts_above_i64() {
+        assert_eq!(to_amount(u64::MAX).to_string(), "18446744073709551615");
+        assert_eq!(
+            to_total_payment(u64::MAX / 2, 2).to_string(),
+            "18446744073709551614"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "overflows u64")]
+    fn test_total_payment_overflow() {
+        to_total_payment(u64::MAX, 2);
+    }
+
+    #[test]
+    fn test_move_option() {
+        let none: MoveOption<String> = serde_json::from_str(r#"{"vec": []}"#).unwrap();
+        assert_eq!(none.into_option(), None);
+        let some: MoveOption<String> = serde_json::from_str(r#"{"vec": ["0x1"]}"#).unwrap();
+        assert_eq!(some.into_option(), Some("0x1".to_string()));
+    }
+}
@@ -0,0 +1,19 @@
+use diesel::{Insertable, Queryable};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::points_ledger;
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Insertable, Serialize, Queryable)]
+#[diesel(table_name = points_ledger)]
+/// Database representation of a single point award or penalty, summing a user's entries gives the
+/// points in user_stats
+pub struct PointsLedgerEntry {
+    pub txn_version: i64,
+    pub event_type: String,
+    pub object_addr: String,
+    pub user_addr: String,
+    pub season: String,
+    pub points_delta: i64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,18 @@
+use diesel::{Insertable, Queryable};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::unhandled_events;
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Insertable, Serialize, Queryable)]
+#[diesel(table_name = unhandled_events)]
+/// Database representation of a contract event the indexer has no decoder for, e.g. an event
+/// added by a contract upgrade
+pub struct UnhandledEvent {
+    pub txn_version: i64,
+    pub event_idx: i64,
+    pub type_str: String,
+    pub data: String,
+    pub upgrade_number: i64,
+    pub timestamp: i64,
+}
@@ -1,10 +1,13 @@
+use bigdecimal::{BigDecimal, Zero};
 use diesel::{AsChangeset, Insertable, Queryable};
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
 
 use crate::schema::user_stats;
 
-#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Serialize, Queryable)]
+#[derive(
+    AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, PartialEq, Serialize, Queryable,
+)]
 #[diesel(table_name = user_stats)]
 /// Database representation of a user's statistics
 pub struct UserStat {
@@ -12,14 +15,36 @@ pub struct UserStat {
     pub create_timestamp: i64,
     pub last_update_timestamp: i64,
     pub bounty_created: i64,
-    pub apt_spent: i64,
-    pub stable_spent: i64,
+    pub apt_spent: BigDecimal,
+    pub stable_spent: BigDecimal,
     pub build_created: i64,
     pub build_submitted_for_review: i64,
     pub build_canceled: i64,
     pub build_completed: i64,
-    pub apt_received: i64,
-    pub stable_received: i64,
+    pub apt_received: BigDecimal,
+    pub stable_received: BigDecimal,
     pub season_1_points: i64,
     pub total_points: i64,
 }
+
+impl UserStat {
+    /// Change a single event makes to a user's stats, counters left at zero are unchanged
+    pub fn new_change(user_addr: &str, timestamp: i64) -> Self {
+        Self {
+            user_addr: user_addr.to_string(),
+            create_timestamp: timestamp,
+            last_update_timestamp: timestamp,
+            bounty_created: 0,
+            apt_spent: BigDecimal::zero(),
+            stable_spent: BigDecimal::zero(),
+            build_created: 0,
+            build_submitted_for_review: 0,
+            build_canceled: 0,
+            build_completed: 0,
+            apt_received: BigDecimal::zero(),
+            stable_received: BigDecimal::zero(),
+            season_1_points: 0,
+            total_points: 0,
+        }
+    }
+}
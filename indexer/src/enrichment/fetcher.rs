@@ -0,0 +1,605 @@
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use reqwest::{
+    dns::{Addrs, Name, Resolve, Resolving},
+    redirect, Response,
+};
+use serde::Deserialize;
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+use url::{Host, Url};
+
+// Same as reqwest's default redirect policy
+const MAX_REDIRECTS: usize = 10;
+
+/// Content extracted from a description link
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FetchedDescription {
+    pub title: Option<String>,
+    pub body: String,
+    pub labels: Vec<String>,
+    // (owner, repo) when the link points into a GitHub repo
+    pub repo: Option<(String, String)>,
+}
+
+impl FetchedDescription {
+    /// Postgres TEXT can't store NUL, which binary pages and `\u0000` in JSON can contain
+    fn without_nul(self) -> Self {
+        let strip = |text: String| text.replace('\0', "");
+        Self {
+            title: self.title.map(strip),
+            body: strip(self.body),
+            labels: self.labels.into_iter().map(strip).collect(),
+            repo: self.repo,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FetchOutcome {
+    Fetched(FetchedDescription),
+    // The link is not something we know how to fetch, don't retry
+    Unsupported,
+}
+
+/// Fetches the content behind a bounty's description link.
+/// Returning an error means the fetch may succeed later and will be retried.
+#[async_trait]
+pub trait DescriptionFetcher: Send + Sync {
+    async fn fetch(&self, description_link: &str) -> Result<FetchOutcome>;
+}
+
+#[derive(Deserialize)]
+struct GithubLabel {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct GithubIssue {
+    title: String,
+    body: Option<String>,
+    #[serde(default)]
+    labels: Vec<GithubLabel>,
+}
+
+/// Whether an address is reachable from the internet. Description links are on-chain user input,
+/// fetching loopback, private, link-local or otherwise reserved addresses would let anyone probe
+/// the network the indexer runs in.
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => is_public_ipv6(ip),
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        // "This network"
+        || a == 0
+        // Shared address space of carrier-grade NATs
+        || (a == 100 && (64..128).contains(&b))
+        // IETF protocol assignments
+        || (a == 192 && b == 0 && ip.octets()[2] == 0)
+        // Benchmarking
+        || (a == 198 && (18..20).contains(&b))
+        // Reserved
+        || a >= 240)
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    // IPv4-mapped and NAT64 addresses reach the embedded IPv4 address
+    if let Some(ipv4) = ip.to_ipv4_mapped() {
+        return is_public_ipv4(ipv4);
+    }
+    let segments = ip.segments();
+    if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        let [a, b] = segments[6].to_be_bytes();
+        let [c, d] = segments[7].to_be_bytes();
+        return is_public_ipv4(Ipv4Addr::new(a, b, c, d));
+    }
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // Unique local
+        || (segments[0] & 0xfe00) == 0xfc00
+        // Link-local
+        || (segments[0] & 0xffc0) == 0xfe80
+        // Documentation
+        || (segments[0] == 0x2001 && segments[1] == 0x0db8))
+}
+
+/// Only http(s) links to public hosts are fetched. Hostnames are checked once resolved, by
+/// PublicAddressResolver.
+fn is_fetchable_url(url: &Url, allow_private_addresses: bool) -> bool {
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return false;
+    }
+    match url.host() {
+        Some(Host::Ipv4(ip)) => allow_private_addresses || is_public_ipv4(ip),
+        Some(Host::Ipv6(ip)) => allow_private_addresses || is_public_ipv6(ip),
+        Some(Host::Domain(_)) => true,
+        None => false,
+    }
+}
+
+/// Resolves hostnames like the system resolver, but fails when a name only resolves to addresses
+/// that aren't public. Every connection goes through it, redirects included, so a public name
+/// can't lead to an internal host.
+struct PublicAddressResolver;
+
+impl Resolve for PublicAddressResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} doesn't resolve to a public address", host).into());
+            }
+            let addrs: Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+/// Read at most max_size bytes of the body, returns whether the rest was cut off
+async fn read_body_limited(mut response: Response, max_size: usize) -> Result<(Vec<u8>, bool)> {
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > max_size {
+            body.extend_from_slice(&chunk[..max_size - body.len()]);
+            return Ok((body, true));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok((body, false))
+}
+
+/// Fetches GitHub issues and pull requests through the GitHub API, any other http(s) link as a
+/// plain page.
+pub struct HttpDescriptionFetcher {
+    client: reqwest::Client,
+    // Overridable so tests can point it at a local stub
+    github_api_base_url: String,
+    github_token: Option<String>,
+    max_response_size: usize,
+    allow_private_addresses: bool,
+}
+
+impl HttpDescriptionFetcher {
+    /// Links to private addresses are only fetched when allow_private_addresses is set, for local
+    /// testing.
+    pub fn new(
+        github_api_base_url: String,
+        github_token: Option<String>,
+        request_timeout: Duration,
+        max_response_size: usize,
+        allow_private_addresses: bool,
+    ) -> Result<Self> {
+        let redirect_policy = redirect::Policy::custom(move |attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                attempt.error("Too many redirects")
+            } else if !is_fetchable_url(attempt.url(), allow_private_addresses) {
+                attempt.error("Redirect to a link that can't be fetched")
+            } else {
+                attempt.follow()
+            }
+        });
+        let mut builder = reqwest::Client::builder()
+            .timeout(request_timeout)
+            .user_agent("bounty-app-indexer")
+            .redirect(redirect_policy)
+            // A proxy would resolve the hostnames instead of PublicAddressResolver
+            .no_proxy();
+        if !allow_private_addresses {
+            builder = builder.dns_resolver(Arc::new(PublicAddressResolver));
+        }
+        let client = builder.build().context("Failed to build HTTP client")?;
+        Ok(Self {
+            client,
+            github_api_base_url: github_api_base_url.trim_end_matches('/').to_string(),
+            github_token,
+            max_response_size,
+            allow_private_addresses,
+        })
+    }
+
+    async fn fetch_github_issue(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: &str,
+    ) -> Result<FetchedDescription> {
+        // Pull requests are issues in the GitHub API
+        let api_url = format!(
+            "{}/repos/{}/{}/issues/{}",
+            self.github_api_base_url, owner, repo, number
+        );
+        let mut request = self
+            .client
+            .get(&api_url)
+            .header("Accept", "application/vnd.github+json");
+        if let Some(token) = &self.github_token {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Failed to request {}", api_url))?
+            .error_for_status()?;
+        let (body, truncated) = read_body_limited(response, self.max_response_size).await?;
+        if truncated {
+            bail!(
+                "GitHub issue from {} is larger than {} bytes",
+                api_url,
+                self.max_response_size
+            );
+        }
+        let issue = serde_json::from_slice::<GithubIssue>(&body)
+            .with_context(|| format!("Failed to parse GitHub issue from {}", api_url))?;
+        Ok(FetchedDescription {
+            title: Some(issue.title),
+            body: issue.body.unwrap_or_default(),
+            labels: issue.labels.into_iter().map(|label| label.name).collect(),
+            repo: Some((owner.to_string(), repo.to_string())),
+        })
+    }
+
+    async fn fetch_page(&self, url: &Url) -> Result<FetchedDescription> {
+        let response = self
+            .client
+            .get(url.as_str())
+            .send()
+            .await
+            .with_context(|| format!("Failed to request {}", url))?
+            .error_for_status()?;
+        let is_html = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .is_some_and(|content_type| content_type.contains("html"));
+        // Long pages are cut off, the beginning is enough for search and previews
+        let (body, _) = read_body_limited(response, self.max_response_size).await?;
+        let content = String::from_utf8_lossy(&body).into_owned();
+        if !is_html {
+            return Ok(FetchedDescription {
+                body: content,
+                ..Default::default()
+            });
+        }
+        Ok(FetchedDescription {
+            title: extract_html_title(&content),
+            body: extract_html_text(&content),
+            ..Default::default()
+        })
+    }
+}
+
+#[async_trait]
+impl DescriptionFetcher for HttpDescriptionFetcher {
+    async fn fetch(&self, description_link: &str) -> Result<FetchOutcome> {
+        let url = match Url::parse(description_link.trim()) {
+            Ok(url) if is_fetchable_url(&url, self.allow_private_addresses) => url,
+            _ => return Ok(FetchOutcome::Unsupported),
+        };
+        let segments = url
+            .path_segments()
+            .map(|segments| segments.filter(|s| !s.is_empty()).collect::<Vec<_>>())
+            .unwrap_or_default();
+        let description = match (url.host_str(), segments.as_slice()) {
+            (
+                Some("github.com" | "www.github.com"),
+                [owner, repo, "issues" | "pull", number, ..],
+            ) if number.parse::<u64>().is_ok() => {
+                self.fetch_github_issue(owner, repo, number).await?
+            }
+            (Some("github.com" | "www.github.com"), [owner, repo, ..]) => {
+                let mut description = self.fetch_page(&url).await?;
+                description.repo = Some((owner.to_string(), repo.to_string()));
+                description
+            }
+            _ => self.fetch_page(&url).await?,
+        };
+        Ok(FetchOutcome::Fetched(description.without_nul()))
+    }
+}
+
+fn extract_html_title(html: &str) -> Option<String> {
+    // ASCII lowercase keeps byte offsets the same as the original
+    let lowercase = html.to_ascii_lowercase();
+    let start = lowercase.find("<title")?;
+    let start = start + lowercase[start..].find('>')? + 1;
+    let end = start + lowercase[start..].find("</title>")?;
+    let title = html[start..end].trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+fn starts_with_ignore_ascii_case(s: &str, prefix: &str) -> bool {
+    s.len() >= prefix.len() && s.as_bytes()[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
+}
+
+/// Naive tag stripping, good enough for search and previews. Script and style content is dropped.
+fn extract_html_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len() / 2);
+    let mut rest = html;
+    while let Some(tag_start) = rest.find('<') {
+        text.push_str(&rest[..tag_start]);
+        text.push(' ');
+        rest = &rest[tag_start..];
+        let skip_until = if starts_with_ignore_ascii_case(rest, "<script") {
+            Some("</script>")
+        } else if starts_with_ignore_ascii_case(rest, "<style") {
+            Some("</style>")
+        } else {
+            None
+        };
+        let tag_end = match skip_until {
+            Some(closing_tag) => rest
+                .to_ascii_lowercase()
+                .find(closing_tag)
+                .map(|idx| idx + closing_tag.len()),
+            None => rest.find('>').map(|idx| idx + 1),
+        };
+        match tag_end {
+            Some(tag_end) => rest = &rest[tag_end..],
+            None => {
+                rest = "";
+                break;
+            }
+        }
+    }
+    text.push_str(rest);
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    /// Serve canned responses keyed by request path on a random local port, returns the base url.
+    async fn spawn_stub_server(
+        routes: Vec<(&'static str, u16, &'static str, &'static str)>,
+    ) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let routes = routes.clone();
+                tokio::spawn(async move {
+                    let mut buffer = vec![0; 8192];
+                    let read = socket.read(&mut buffer).await.unwrap();
+                    let request = String::from_utf8_lossy(&buffer[..read]);
+                    let path = request.split_whitespace().nth(1).unwrap_or("/");
+                    let (status, content_type, body) = routes
+                        .iter()
+                        .find(|(route, ..)| *route == path)
+                        .map(|(_, status, content_type, body)| (*status, *content_type, *body))
+                        .unwrap_or((404, "text/plain", "not found"));
+                    let response = format!(
+                        "HTTP/1.1 {} STUB\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        content_type,
+                        body.len(),
+                        body
+                    );
+                    socket.write_all(response.as_bytes()).await.unwrap();
+                });
+            }
+        });
+        base_url
+    }
+
+    // The stub server listens on a private address
+    fn new_fetcher(github_api_base_url: String) -> HttpDescriptionFetcher {
+        HttpDescriptionFetcher::new(
+            github_api_base_url,
+            None,
+            Duration::from_secs(5),
+            1024,
+            true,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_fetch_github_issue() {
+        let base_url = spawn_stub_server(vec![(
+            "/repos/bulsico/bounty-app/issues/42",
+            200,
+            "application/json",
+            r#"{"title": "Add dark mode", "body": "Support **dark** mode", "labels": [{"name": "ui"}, {"name": "good first issue"}]}"#,
+        )])
+        .await;
+        let outcome = new_fetcher(base_url)
+            .fetch("https://github.com/bulsico/bounty-app/issues/42")
+            .await
+            .unwrap();
+        assert_eq!(
+            outcome,
+            FetchOutcome::Fetched(FetchedDescription {
+                title: Some("Add dark mode".to_string()),
+                body: "Support **dark** mode".to_string(),
+                labels: vec!["ui".to_string(), "good first issue".to_string()],
+                repo: Some(("bulsico".to_string(), "bounty-app".to_string())),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_html_page() {
+        let base_url = spawn_stub_server(vec![(
+            "/spec",
+            200,
+            "text/html; charset=utf-8",
+            "<html><head><title> Bounty spec </title><style>p { color: red; }</style></head><body><p>Build a <b>CLI</b></p><script>alert(1)</script></body></html>",
+        )])
+        .await;
+        let outcome = new_fetcher(base_url.clone())
+            .fetch(&format!("{}/spec", base_url))
+            .await
+            .unwrap();
+        assert_eq!(
+            outcome,
+            FetchOutcome::Fetched(FetchedDescription {
+                title: Some("Bounty spec".to_string()),
+                body: "Bounty spec Build a CLI".to_string(),
+                labels: vec![],
+                repo: None,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_strips_nul() {
+        let base_url = spawn_stub_server(vec![
+            ("/binary", 200, "application/pdf", "%PDF-1.4\0\0binary"),
+            (
+                "/repos/o/r/issues/1",
+                200,
+                "application/json",
+                r#"{"title": "Add\u0000 dark mode", "body": "Support\u0000 dark mode", "labels": [{"name": "u\u0000i"}]}"#,
+            ),
+        ])
+        .await;
+        let fetcher = new_fetcher(base_url.clone());
+        assert_eq!(
+            fetcher
+                .fetch(&format!("{}/binary", base_url))
+                .await
+                .unwrap(),
+            FetchOutcome::Fetched(FetchedDescription {
+                body: "%PDF-1.4binary".to_string(),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            fetcher
+                .fetch("https://github.com/o/r/issues/1")
+                .await
+                .unwrap(),
+            FetchOutcome::Fetched(FetchedDescription {
+                title: Some("Add dark mode".to_string()),
+                body: "Support dark mode".to_string(),
+                labels: vec!["ui".to_string()],
+                repo: Some(("o".to_string(), "r".to_string())),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_error_status_is_retryable() {
+        let base_url =
+            spawn_stub_server(vec![("/down", 503, "text/plain", "try again later")]).await;
+        let result = new_fetcher(base_url.clone())
+            .fetch(&format!("{}/down", base_url))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_unsupported_link() {
+        let fetcher = new_fetcher("http://127.0.0.1:1".to_string());
+        assert_eq!(
+            fetcher.fetch("not a link").await.unwrap(),
+            FetchOutcome::Unsupported
+        );
+        assert_eq!(
+            fetcher.fetch("ipfs://bafybeigdyr").await.unwrap(),
+            FetchOutcome::Unsupported
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_page_larger_than_max_response_size() {
+        let base_url = spawn_stub_server(vec![(
+            "/long",
+            200,
+            "text/plain",
+            "0123456789abcdefghijklmnopqrstuvwxyz",
+        )])
+        .await;
+        let fetcher =
+            HttpDescriptionFetcher::new(base_url.clone(), None, Duration::from_secs(5), 10, true)
+                .unwrap();
+        let outcome = fetcher.fetch(&format!("{}/long", base_url)).await.unwrap();
+        assert_eq!(
+            outcome,
+            FetchOutcome::Fetched(FetchedDescription {
+                body: "0123456789".to_string(),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_private_address() {
+        let fetcher = HttpDescriptionFetcher::new(
+            "http://127.0.0.1:1".to_string(),
+            None,
+            Duration::from_secs(5),
+            1024,
+            false,
+        )
+        .unwrap();
+        for link in [
+            "http://127.0.0.1:8080/admin",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://10.0.0.1/",
+            "http://[::1]/",
+            "http://[::ffff:192.168.1.1]/",
+            "file:///etc/passwd",
+        ] {
+            assert_eq!(
+                fetcher.fetch(link).await.unwrap(),
+                FetchOutcome::Unsupported,
+                "{}",
+                link
+            );
+        }
+        // Checked once resolved
+        assert!(fetcher.fetch("http://localhost:1/").await.is_err());
+    }
+
+    #[test]
+    fn test_is_public_ip() {
+        for ip in ["1.1.1.1", "140.82.112.3", "2606:4700:4700::1111"] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in [
+            "0.0.0.0",
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.0.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "224.0.0.1",
+            "255.255.255.255",
+            "::",
+            "::1",
+            "fc00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "64:ff9b::a9fe:a9fe",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+}
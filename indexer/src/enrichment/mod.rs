@@ -0,0 +1,323 @@
+//! Background worker fetching the content behind bounty description links.
+//! Fetched text feeds the bounty search documents so search and previews have more than a title.
+//! It runs next to the events processor when `enrichment_config.enabled` is set.
+
+pub mod fetcher;
+
+use anyhow::{Context, Result};
+use diesel::{
+    sql_query,
+    sql_types::{Int8, Text},
+    update, ExpressionMethods, QueryDsl,
+};
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use sha2::{Digest, Sha256};
+use std::{sync::Arc, time::Duration};
+
+use self::fetcher::{DescriptionFetcher, FetchOutcome, FetchedDescription, HttpDescriptionFetcher};
+use crate::{
+    config::indexer_processor_config::EnrichmentConfig,
+    db_models::bounty_enrichment::{
+        BountyEnrichment, FETCH_STATUS_FAILED, FETCH_STATUS_FETCHED, FETCH_STATUS_PENDING,
+        FETCH_STATUS_RETRYING, FETCH_STATUS_UNSUPPORTED,
+    },
+    schema::{bounty_enrichment, bounty_search_documents},
+    utils::{database_connection::get_db_connection, database_utils::ArcDbPool},
+};
+
+const BODY_EXCERPT_MAX_CHARS: usize = 500;
+// Upper bound of fetched text copied into the search document
+const SEARCH_TEXT_MAX_CHARS: usize = 20_000;
+const MAX_RETRY_DELAY_IN_SECONDS: i64 = 24 * 60 * 60;
+
+/// Add a pending row for every bounty that has never been enriched.
+/// Description links can't change on chain so a bounty is only queued once.
+const QUEUE_NEW_BOUNTIES_SQL: &str = "
+INSERT INTO bounty_enrichment (bounty_obj_addr, description_link, fetch_status, attempt_count, next_attempt_timestamp)
+SELECT bounties.bounty_obj_addr, bounties.description_link, $1, 0, $2
+FROM bounties
+LEFT JOIN bounty_enrichment ON bounty_enrichment.bounty_obj_addr = bounties.bounty_obj_addr
+WHERE bounty_enrichment.bounty_obj_addr IS NULL
+ON CONFLICT (bounty_obj_addr) DO NOTHING
+";
+
+/// Exponential backoff starting at `base_delay_in_seconds`, capped at a day.
+pub fn get_retry_delay_in_seconds(attempt_count: i64, base_delay_in_seconds: u64) -> i64 {
+    let exponent = (attempt_count - 1).clamp(0, 20) as u32;
+    (base_delay_in_seconds as i64)
+        .saturating_mul(2_i64.pow(exponent))
+        .min(MAX_RETRY_DELAY_IN_SECONDS)
+}
+
+/// Hex sha256 over everything we store, so an unchanged hash means nothing to update.
+pub fn get_content_hash(description: &FetchedDescription) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(description.title.as_deref().unwrap_or_default());
+    hasher.update([0]);
+    hasher.update(&description.body);
+    for label in &description.labels {
+        hasher.update([0]);
+        hasher.update(label);
+    }
+    hex::encode(hasher.finalize())
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    text.chars().take(max_chars).collect()
+}
+
+async fn queue_new_bounties(pool: &ArcDbPool, now: i64) -> Result<()> {
+    let conn = &mut get_db_connection(pool).await?;
+    sql_query(QUEUE_NEW_BOUNTIES_SQL)
+        .bind::<Text, _>(FETCH_STATUS_PENDING)
+        .bind::<Int8, _>(now)
+        .execute(conn)
+        .await
+        .context("Failed to queue new bounties for enrichment")?;
+    Ok(())
+}
+
+async fn get_due_enrichments(
+    pool: &ArcDbPool,
+    now: i64,
+    batch_size: i64,
+) -> Result<Vec<BountyEnrichment>> {
+    let conn = &mut get_db_connection(pool).await?;
+    bounty_enrichment::table
+        .filter(bounty_enrichment::fetch_status.eq_any([
+            FETCH_STATUS_PENDING,
+            FETCH_STATUS_RETRYING,
+            FETCH_STATUS_FETCHED,
+        ]))
+        .filter(bounty_enrichment::next_attempt_timestamp.le(now))
+        .order(bounty_enrichment::next_attempt_timestamp.asc())
+        .limit(batch_size)
+        .load::<BountyEnrichment>(conn)
+        .await
+        .context("Failed to load bounties due for enrichment")
+}
+
+/// Returns the updated row and the search text to store, if the content changed.
+fn apply_fetch_result(
+    mut enrichment: BountyEnrichment,
+    result: Result<FetchOutcome>,
+    config: &EnrichmentConfig,
+    now: i64,
+) -> (BountyEnrichment, Option<String>) {
+    enrichment.last_attempt_timestamp = Some(now);
+    let description = match result {
+        Ok(FetchOutcome::Fetched(description)) => description,
+        Ok(FetchOutcome::Unsupported) => {
+            enrichment.fetch_status = FETCH_STATUS_UNSUPPORTED.to_string();
+            enrichment.last_error = None;
+            return (enrichment, None);
+        }
+        Err(e) => {
+            enrichment.attempt_count += 1;
+            enrichment.last_error = Some(format!("{:#}", e));
+            if enrichment.attempt_count >= config.max_attempts as i64 {
+                enrichment.fetch_status = FETCH_STATUS_FAILED.to_string();
+            } else {
+                // Keep the last good content of fetched rows, only the refresh failed
+                if enrichment.fetch_status != FETCH_STATUS_FETCHED {
+                    enrichment.fetch_status = FETCH_STATUS_RETRYING.to_string();
+                }
+                enrichment.next_attempt_timestamp = now
+                    + get_retry_delay_in_seconds(
+                        enrichment.attempt_count,
+                        config.retry_base_delay_in_seconds,
+                    );
+            }
+            return (enrichment, None);
+        }
+    };
+
+    enrichment.fetch_status = FETCH_STATUS_FETCHED.to_string();
+    enrichment.attempt_count = 0;
+    enrichment.last_error = None;
+    enrichment.last_success_timestamp = Some(now);
+    enrichment.next_attempt_timestamp = match config.refresh_interval_in_seconds {
+        Some(interval) => now + interval as i64,
+        None => i64::MAX,
+    };
+
+    let content_hash = get_content_hash(&description);
+    if enrichment.content_hash.as_deref() == Some(content_hash.as_str()) {
+        return (enrichment, None);
+    }
+    let search_text = truncate_chars(
+        &format!(
+            "{}\n{}",
+            description.title.as_deref().unwrap_or_default(),
+            description.body
+        ),
+        SEARCH_TEXT_MAX_CHARS,
+    );
+    enrichment.content_hash = Some(content_hash);
+    enrichment.title = description.title;
+    enrichment.body_excerpt = Some(truncate_chars(&description.body, BODY_EXCERPT_MAX_CHARS));
+    enrichment.labels = description.labels.into_iter().map(Some).collect();
+    (enrichment.repo_owner, enrichment.repo_name) = match description.repo {
+        Some((owner, repo)) => (Some(owner), Some(repo)),
+        None => (None, None),
+    };
+    (enrichment, Some(search_text))
+}
+
+async fn save_enrichment(
+    pool: &ArcDbPool,
+    enrichment: BountyEnrichment,
+    search_text: Option<String>,
+) -> Result<()> {
+    let conn = &mut get_db_connection(pool).await?;
+    conn.transaction(|conn| {
+        Box::pin(async move {
+            if let Some(search_text) = search_text {
+                update(bounty_search_documents::table.find(&enrichment.bounty_obj_addr))
+                    .set(bounty_search_documents::description_text.eq(search_text))
+                    .execute(conn)
+                    .await?;
+            }
+            update(bounty_enrichment::table.find(&enrichment.bounty_obj_addr))
+                .set(&enrichment)
+                .execute(conn)
+                .await?;
+            Ok::<(), diesel::result::Error>(())
+        })
+    })
+    .await
+    .context("Failed to save bounty enrichment")
+}
+
+/// Fetch every due bounty once, returns how many were attempted.
+pub async fn run_enrichment_pass(
+    pool: &ArcDbPool,
+    config: &EnrichmentConfig,
+    fetcher: &dyn DescriptionFetcher,
+) -> Result<usize> {
+    let now = chrono::Utc::now().timestamp();
+    queue_new_bounties(pool, now).await?;
+    let due_enrichments = get_due_enrichments(pool, now, config.batch_size as i64).await?;
+    let attempted = due_enrichments.len();
+    for enrichment in due_enrichments {
+        let result = fetcher.fetch(&enrichment.description_link).await;
+        let now = chrono::Utc::now().timestamp();
+        let (updated_enrichment, search_text) =
+            apply_fetch_result(enrichment.clone(), result, config, now);
+        tracing::debug!(
+            bounty_obj_addr = updated_enrichment.bounty_obj_addr,
+            fetch_status = updated_enrichment.fetch_status,
+            "Enriched bounty description"
+        );
+        if let Err(e) = save_enrichment(pool, updated_enrichment, search_text).await {
+            // Retry the row later like a failed fetch, otherwise it comes first in every pass
+            tracing::warn!(
+                bounty_obj_addr = enrichment.bounty_obj_addr,
+                "Error saving bounty enrichment: {:?}",
+                e
+            );
+            let (failed_enrichment, _) = apply_fetch_result(enrichment, Err(e), config, now);
+            save_enrichment(pool, failed_enrichment, None).await?;
+        }
+    }
+    Ok(attempted)
+}
+
+/// Run forever, errors are logged and retried on the next poll so they never stop the indexer.
+pub async fn run_enrichment_worker(
+    pool: ArcDbPool,
+    config: EnrichmentConfig,
+    fetcher: Arc<dyn DescriptionFetcher>,
+) {
+    let poll_interval = Duration::from_secs(config.poll_interval_in_seconds);
+    loop {
+        match run_enrichment_pass(&pool, &config, fetcher.as_ref()).await {
+            // A full batch means more rows may be due, don't wait
+            Ok(attempted) if attempted as u64 >= config.batch_size => continue,
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Error enriching bounty descriptions: {:?}", e),
+        }
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+/// Start the worker with the HTTP fetcher in the background.
+pub fn spawn_enrichment_worker(pool: ArcDbPool, config: EnrichmentConfig) -> Result<()> {
+    let fetcher = HttpDescriptionFetcher::new(
+        config.github_api_base_url.clone(),
+        config.github_token.clone(),
+        Duration::from_secs(config.request_timeout_in_seconds),
+        config.max_response_size_in_bytes,
+        config.allow_private_addresses,
+    )?;
+    tokio::spawn(run_enrichment_worker(pool, config, Arc::new(fetcher)));
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn new_enrichment() -> BountyEnrichment {
+        BountyEnrichment {
+            bounty_obj_addr: "0x1".to_string(),
+            description_link: "https://github.com/o/r/issues/1".to_string(),
+            fetch_status: FETCH_STATUS_PENDING.to_string(),
+            attempt_count: 0,
+            next_attempt_timestamp: 0,
+            last_attempt_timestamp: None,
+            last_success_timestamp: None,
+            last_error: None,
+            content_hash: None,
+            title: None,
+            body_excerpt: None,
+            labels: vec![],
+            repo_owner: None,
+            repo_name: None,
+        }
+    }
+
+    #[test]
+    fn test_retry_schedule() {
+        let config = EnrichmentConfig {
+            max_attempts: 3,
+            retry_base_delay_in_seconds: 60,
+            ..Default::default()
+        };
+        let (enrichment, _) =
+            apply_fetch_result(new_enrichment(), Err(anyhow::anyhow!("503")), &config, 1000);
+        assert_eq!(enrichment.fetch_status, FETCH_STATUS_RETRYING);
+        assert_eq!(enrichment.next_attempt_timestamp, 1060);
+        let (enrichment, _) =
+            apply_fetch_result(enrichment, Err(anyhow::anyhow!("503")), &config, 2000);
+        assert_eq!(enrichment.next_attempt_timestamp, 2120);
+        let (enrichment, _) =
+            apply_fetch_result(enrichment, Err(anyhow::anyhow!("503")), &config, 3000);
+        assert_eq!(enrichment.fetch_status, FETCH_STATUS_FAILED);
+        assert_eq!(
+            get_retry_delay_in_seconds(30, 60),
+            MAX_RETRY_DELAY_IN_SECONDS
+        );
+    }
+
+    #[test]
+    fn test_unchanged_content_is_not_stored_again() {
+        let config = EnrichmentConfig::default();
+        let fetched = FetchOutcome::Fetched(FetchedDescription {
+            title: Some("Add dark mode".to_string()),
+            body: "Support dark mode".to_string(),
+            labels: vec!["ui".to_string()],
+            repo: Some(("o".to_string(), "r".to_string())),
+        });
+        let (enrichment, search_text) =
+            apply_fetch_result(new_enrichment(), Ok(fetched.clone()), &config, 1000);
+        assert_eq!(enrichment.fetch_status, FETCH_STATUS_FETCHED);
+        assert_eq!(
+            search_text.as_deref(),
+            Some("Add dark mode\nSupport dark mode")
+        );
+        assert_eq!(enrichment.repo_name.as_deref(), Some("r"));
+        let (_, search_text) = apply_fetch_result(enrichment, Ok(fetched), &config, 2000);
+        assert_eq!(search_text, None);
+    }
+}
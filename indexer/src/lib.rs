@@ -1,6 +1,12 @@
+pub mod analytics;
+pub mod benchmark;
+pub mod client;
 pub mod config;
 pub mod db_models;
+pub mod enrichment;
 pub mod health_check_server;
+pub mod mock_stream;
+pub mod operator;
 pub mod processors;
 pub mod utils;
 
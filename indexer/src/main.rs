@@ -1,26 +1,200 @@
 use anyhow::Result;
-use aptos_indexer_processor_sdk_server_framework::ServerArgs;
-use clap::Parser;
+use aptos_indexer_processor_sdk_server_framework::{load, GenericConfig, ServerArgs};
+use clap::{Parser, Subcommand};
 use indexer::{
+    analytics::run_analytics_refresh,
+    benchmark::{run_storer_benchmark, StorerBenchmarkArgs},
     config::indexer_processor_config::IndexerProcessorConfig,
     health_check_server::{self, HealthServerConfig},
+    mock_stream::{run_mock_stream, MockStreamArgs},
+    operator::{clear_chain_id, set_checkpoint, show_status, truncate_indexed_data, verify_config},
+    utils::database_migrations::{
+        print_migration_status, revert_migrations, run_pending_migrations,
+    },
 };
+use std::{path::PathBuf, time::Duration};
 
 #[cfg(unix)]
 #[global_allocator]
 static ALLOC: jemallocator::Jemalloc = jemallocator::Jemalloc;
 
+#[derive(Parser)]
+struct IndexerArgs {
+    #[clap(flatten)]
+    server_args: ServerArgs,
+    /// Run a one-off command instead of the indexer
+    #[clap(subcommand)]
+    command: Option<IndexerCommand>,
+}
+
+#[derive(Subcommand)]
+enum IndexerCommand {
+    /// Recompute builder cohort retention and build funnels
+    RefreshAnalytics {
+        /// Keep running and refresh every given number of seconds instead of once
+        #[clap(long)]
+        interval_secs: Option<u64>,
+    },
+    /// Time the events storer on synthetic events for each chunk size and max concurrent DB
+    /// tasks, to tune pipeline_config. Writes to the database, only use a throwaway one
+    BenchmarkStorer {
+        #[clap(long, default_value_t = 5)]
+        batches: usize,
+        #[clap(long, default_value_t = 200)]
+        bounties_per_batch: usize,
+        #[clap(long, default_value_t = 5)]
+        builds_per_bounty: usize,
+        /// Rows per insert for the bounties, builds and user_stats tables
+        #[clap(long, value_delimiter = ',', default_value = "100,500,2000")]
+        chunk_sizes: Vec<usize>,
+        #[clap(long, value_delimiter = ',', default_value = "1,4,16")]
+        max_concurrent_db_tasks: Vec<usize>,
+    },
+    /// Print the last processed version, the chain id and the lag behind the chain
+    Status,
+    /// Set the last processed version, the indexer resumes from the next one
+    SetCheckpoint {
+        /// Last successfully processed version
+        version: u64,
+    },
+    /// Forget the recorded chain id, e.g. after a testnet reset
+    ClearChainId,
+    /// Check the config, database and transaction stream, and print the starting version
+    VerifyConfig,
+    /// Delete all indexed data and the processor status to re-index from scratch
+    Truncate {
+        /// Don't ask for confirmation
+        #[clap(long)]
+        yes: bool,
+    },
+    /// Serve generated or recorded transactions of the configured contract as a local gRPC
+    /// transaction stream, to run the indexer end-to-end without network access
+    MockStream {
+        #[clap(long, default_value_t = 50051)]
+        port: u16,
+        /// Defaults to the chain id of the recorded batches, or 4 for a local testnet
+        #[clap(long)]
+        chain_id: Option<u64>,
+        /// Serve as fast as the indexer reads if unset
+        #[clap(long)]
+        transactions_per_second: Option<u64>,
+        #[clap(long, default_value_t = 100)]
+        batch_size: u64,
+        /// Serve the batches recorded in this directory instead of generated transactions
+        #[clap(long)]
+        replay_dir: Option<PathBuf>,
+        #[clap(long, default_value_t = 3)]
+        builds_per_bounty: u64,
+        /// Stop generating after this many transactions, never stops if unset
+        #[clap(long)]
+        transaction_count: Option<u64>,
+    },
+    /// Apply, revert or list the DB migrations embedded in the binary
+    Migrate {
+        #[clap(subcommand)]
+        command: MigrateCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum MigrateCommand {
+    /// Apply all pending migrations
+    Up,
+    /// Revert the last applied migration
+    Down {
+        /// Revert every migration, which drops all indexed data
+        #[clap(long)]
+        all: bool,
+    },
+    /// List the migrations and whether they are applied
+    Status,
+}
+
 async fn run_health_server() -> Result<()> {
     health_check_server::run(HealthServerConfig::default()).await
 }
 
-async fn run_indexer() -> Result<()> {
-    ServerArgs::parse()
+async fn run_indexer(server_args: ServerArgs) -> Result<()> {
+    server_args
         .run::<IndexerProcessorConfig>(tokio::runtime::Handle::current())
         .await
 }
 
+async fn run_command(server_args: ServerArgs, command: IndexerCommand) -> Result<()> {
+    aptos_indexer_processor_sdk_server_framework::setup_logging();
+    let config =
+        load::<GenericConfig<IndexerProcessorConfig>>(&server_args.config_path)?.server_config;
+    match command {
+        IndexerCommand::RefreshAnalytics { interval_secs } => {
+            run_analytics_refresh(config, interval_secs.map(Duration::from_secs)).await
+        }
+        IndexerCommand::BenchmarkStorer {
+            batches,
+            bounties_per_batch,
+            builds_per_bounty,
+            chunk_sizes,
+            max_concurrent_db_tasks,
+        } => {
+            run_storer_benchmark(
+                config,
+                StorerBenchmarkArgs {
+                    batches,
+                    bounties_per_batch,
+                    builds_per_bounty,
+                    chunk_sizes,
+                    max_concurrent_db_tasks,
+                },
+            )
+            .await
+        }
+        IndexerCommand::Status => show_status(config).await,
+        IndexerCommand::SetCheckpoint { version } => set_checkpoint(config, version).await,
+        IndexerCommand::ClearChainId => clear_chain_id(config).await,
+        IndexerCommand::VerifyConfig => verify_config(config).await,
+        IndexerCommand::Truncate { yes } => truncate_indexed_data(config, yes).await,
+        IndexerCommand::MockStream {
+            port,
+            chain_id,
+            transactions_per_second,
+            batch_size,
+            replay_dir,
+            builds_per_bounty,
+            transaction_count,
+        } => {
+            run_mock_stream(
+                config.contract_config.contract_address,
+                MockStreamArgs {
+                    port,
+                    chain_id,
+                    transactions_per_second,
+                    batch_size,
+                    replay_dir,
+                    builds_per_bounty,
+                    transaction_count,
+                },
+            )
+            .await
+        }
+        IndexerCommand::Migrate { command } => {
+            let database_url = &config.db_config.postgres_connection_string;
+            match command {
+                MigrateCommand::Up => {
+                    let versions = run_pending_migrations(database_url).await?;
+                    tracing::info!("Applied {} migrations: {:?}", versions.len(), versions);
+                }
+                MigrateCommand::Down { all } => {
+                    let versions = revert_migrations(database_url, all).await?;
+                    tracing::info!("Reverted {} migrations: {:?}", versions.len(), versions);
+                }
+                MigrateCommand::Status => print_migration_status(database_url).await?,
+            }
+            Ok(())
+        }
+    }
+}
+
 fn main() -> Result<()> {
+    let args = IndexerArgs::parse();
     let num_cpus = num_cpus::get();
     let worker_threads = (num_cpus).max(16);
 
@@ -32,7 +206,12 @@ fn main() -> Result<()> {
         .build()
         .unwrap()
         .block_on(async {
-            tokio::try_join!(run_health_server(), run_indexer())?;
-            Ok(())
+            match args.command {
+                Some(command) => run_command(args.server_args, command).await,
+                None => {
+                    tokio::try_join!(run_health_server(), run_indexer(args.server_args))?;
+                    Ok(())
+                }
+            }
         })
 }
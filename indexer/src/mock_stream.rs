@@ -0,0 +1,501 @@
+//! Local stand-in for the indexer gRPC data service, so the whole indexer, chain id check and
+//! transaction stream included, can run end-to-end and be load-tested without network access.
+//! It serves either generated bounty lifecycles or batches recorded with
+//! `transaction_files_config.record_dir`, at a configurable rate and chain id. Point
+//! `transaction_stream_config.indexer_grpc_data_service_address` at it.
+
+use anyhow::{bail, ensure, Context, Result};
+use aptos_indexer_processor_sdk::aptos_protos::{
+    indexer::v1::{
+        raw_data_server::{RawData, RawDataServer},
+        GetTransactionsRequest, TransactionsResponse,
+    },
+    transaction::v1::{transaction::TxnData, Transaction, UserTransaction},
+    util::timestamp::Timestamp,
+};
+use async_trait::async_trait;
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{codec::CompressionEncoding, transport::Server, Request, Response, Status};
+
+use crate::{
+    db_models::on_chain::{
+        AcceptBuildEventOnChain, BountyOnChain, BuildOnChain, CreateBountyEventOnChain,
+        CreateBuildEventOnChain, EndBountyEventOnChain, MoveObject,
+        SubmitBuildForReviewEventOnChain,
+    },
+    processors::events::{
+        events_extractor::new_bounty_app_event,
+        storers::{
+            APT_FA_ADDR, BUILD_STATUS_COMPLETED, BUILD_STATUS_IN_PROGRESS,
+            BUILD_STATUS_READY_FOR_REVIEW,
+        },
+    },
+    utils::transaction_files::{decode_batch, get_recorded_chain_id, list_batch_files},
+};
+
+// Chain id of a local testnet
+const DEFAULT_CHAIN_ID: u64 = 4;
+// Generated bounties and builds are spread over this many users
+const USER_COUNT: u64 = 20;
+// Each generated bounty pays 1 APT to each of its 2 winners, only the first build is accepted
+const PAYMENT_PER_WINNER: u64 = 100_000_000;
+const WINNER_LIMIT: u64 = 2;
+// Batches sent ahead of the client per stream
+const CHANNEL_SIZE: usize = 10;
+// Same limit as the SDK client, recorded batches can be large
+const MAX_MESSAGE_SIZE: usize = 256 * 1024 * 1024;
+
+pub struct MockStreamArgs {
+    pub port: u16,
+    /// Chain id of the recorded batches when replaying, a local testnet's otherwise
+    pub chain_id: Option<u64>,
+    /// Serve as fast as the client reads if unset
+    pub transactions_per_second: Option<u64>,
+    pub batch_size: u64,
+    /// Serve the batches recorded in this directory instead of generated transactions
+    pub replay_dir: Option<PathBuf>,
+    pub builds_per_bounty: u64,
+    /// Generate transactions without end if unset
+    pub transaction_count: Option<u64>,
+}
+
+/// Run the mock data service until the process is stopped.
+pub async fn run_mock_stream(contract_address: String, args: MockStreamArgs) -> Result<()> {
+    ensure!(args.batch_size > 0, "batch_size must be greater than 0");
+    ensure!(
+        args.transactions_per_second != Some(0),
+        "transactions_per_second must be greater than 0"
+    );
+    let (source, chain_id) = match &args.replay_dir {
+        Some(dir) => {
+            let chain_id = match args.chain_id {
+                Some(chain_id) => chain_id,
+                None => get_recorded_chain_id(dir)?,
+            };
+            (
+                TransactionSource::Recorded(load_recorded_transactions(dir)?),
+                chain_id,
+            )
+        }
+        None => {
+            let generator = TransactionGenerator::new(
+                contract_address,
+                args.builds_per_bounty,
+                args.transaction_count,
+            )?;
+            (
+                TransactionSource::Generated(generator),
+                args.chain_id.unwrap_or(DEFAULT_CHAIN_ID),
+            )
+        }
+    };
+
+    let service = MockRawDataService {
+        source: Arc::new(source),
+        chain_id,
+        batch_size: args.batch_size,
+        transactions_per_second: args.transactions_per_second,
+    };
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, args.port));
+    tracing::info!(
+        chain_id,
+        "Mock transaction stream listening on http://{}",
+        addr
+    );
+    // The SDK client compresses its requests and accepts compressed responses
+    let server = RawDataServer::new(service)
+        .accept_compressed(CompressionEncoding::Zstd)
+        .accept_compressed(CompressionEncoding::Gzip)
+        .send_compressed(CompressionEncoding::Zstd)
+        .max_decoding_message_size(MAX_MESSAGE_SIZE)
+        .max_encoding_message_size(MAX_MESSAGE_SIZE);
+    Server::builder()
+        .add_service(server)
+        .serve(addr)
+        .await
+        .context("Mock transaction stream failed")
+}
+
+fn load_recorded_transactions(dir: &Path) -> Result<Vec<Transaction>> {
+    let mut transactions = vec![];
+    for path in list_batch_files(dir, 0)? {
+        let (batch, _) = decode_batch(&std::fs::read(&path)?)
+            .with_context(|| format!("Could not decode {}", path.display()))?;
+        transactions.extend(batch);
+    }
+    if transactions.is_empty() {
+        bail!("No recorded transactions in {}", dir.display());
+    }
+    // Batches recorded by runs with overlapping ranges repeat versions
+    transactions.sort_by_key(|txn| txn.version);
+    transactions.dedup_by_key(|txn| txn.version);
+    Ok(transactions)
+}
+
+enum TransactionSource {
+    Generated(TransactionGenerator),
+    // Sorted by version
+    Recorded(Vec<Transaction>),
+}
+
+impl TransactionSource {
+    /// Up to `count` transactions from `version` on, empty once the source is exhausted
+    fn next_batch(&self, version: u64, count: u64) -> Vec<Transaction> {
+        match self {
+            Self::Generated(generator) => {
+                let end_version = match generator.transaction_count {
+                    Some(transaction_count) => transaction_count.min(version + count),
+                    None => version + count,
+                };
+                (version..end_version)
+                    .map(|version| generator.transaction(version))
+                    .collect()
+            }
+            Self::Recorded(transactions) => {
+                let start = transactions.partition_point(|txn| txn.version < version);
+                transactions[start..]
+                    .iter()
+                    .take(count as usize)
+                    .cloned()
+                    .collect()
+            }
+        }
+    }
+}
+
+struct MockRawDataService {
+    source: Arc<TransactionSource>,
+    chain_id: u64,
+    batch_size: u64,
+    transactions_per_second: Option<u64>,
+}
+
+#[async_trait]
+impl RawData for MockRawDataService {
+    type GetTransactionsStream = ReceiverStream<Result<TransactionsResponse, Status>>;
+
+    async fn get_transactions(
+        &self,
+        request: Request<GetTransactionsRequest>,
+    ) -> Result<Response<Self::GetTransactionsStream>, Status> {
+        let request = request.into_inner();
+        let mut version = request.starting_version.unwrap_or_default();
+        let end_version = request
+            .transactions_count
+            .map(|transactions_count| version + transactions_count);
+        tracing::info!(
+            starting_version = version,
+            end_version,
+            "Serving a transaction stream"
+        );
+
+        let (sender, receiver) = mpsc::channel(CHANNEL_SIZE);
+        let source = self.source.clone();
+        let chain_id = self.chain_id;
+        let batch_size = self.batch_size;
+        let transactions_per_second = self.transactions_per_second;
+        tokio::spawn(async move {
+            loop {
+                let count = match end_version {
+                    Some(end_version) => batch_size.min(end_version.saturating_sub(version)),
+                    None => batch_size,
+                };
+                if count == 0 {
+                    // Served every requested transaction, ending the stream
+                    return;
+                }
+                let transactions = source.next_batch(version, count);
+                let Some(last) = transactions.last() else {
+                    // Like the real service at the tip of the chain, keep the stream open
+                    sender.closed().await;
+                    return;
+                };
+                version = last.version + 1;
+                let interval = transactions_per_second.map(|transactions_per_second| {
+                    Duration::from_secs_f64(
+                        transactions.len() as f64 / transactions_per_second as f64,
+                    )
+                });
+                let response = TransactionsResponse {
+                    transactions,
+                    chain_id: Some(chain_id),
+                    ..Default::default()
+                };
+                if sender.send(Ok(response)).await.is_err() {
+                    // The client disconnected
+                    return;
+                }
+                if let Some(interval) = interval {
+                    tokio::time::sleep(interval).await;
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+}
+
+/// Generates bounty lifecycles, one event per transaction: a bounty is created, its builds are
+/// created then submitted for review, the first build is accepted and the bounty ends. A
+/// transaction only depends on its version, so any starting version can be served again.
+struct TransactionGenerator {
+    contract_address: String,
+    builds_per_bounty: u64,
+    transaction_count: Option<u64>,
+    start_timestamp: i64,
+}
+
+impl TransactionGenerator {
+    fn new(
+        contract_address: String,
+        builds_per_bounty: u64,
+        transaction_count: Option<u64>,
+    ) -> Result<Self> {
+        // Every build of a bounty needs its own builder
+        ensure!(
+            (1..USER_COUNT).contains(&builds_per_bounty),
+            "builds_per_bounty must be between 1 and {}",
+            USER_COUNT - 1
+        );
+        Ok(Self {
+            contract_address,
+            builds_per_bounty,
+            transaction_count,
+            start_timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64,
+        })
+    }
+
+    fn transactions_per_bounty(&self) -> u64 {
+        2 * self.builds_per_bounty + 3
+    }
+
+    fn transaction(&self, version: u64) -> Transaction {
+        let builds_per_bounty = self.builds_per_bounty;
+        let bounty_idx = version / self.transactions_per_bounty();
+        let bounty_version = bounty_idx * self.transactions_per_bounty();
+        let step = version - bounty_version;
+        let timestamp = |version: u64| self.start_timestamp as u64 + version;
+
+        let bounty_obj_addr = new_addr(2, bounty_idx);
+        let mut bounty = BountyOnChain {
+            creator: new_addr(1, bounty_idx % USER_COUNT),
+            create_timestamp: timestamp(bounty_version),
+            last_update_timestamp: timestamp(bounty_version),
+            end_timestamp: timestamp(bounty_version) + 7 * 24 * 60 * 60,
+            title: format!("Bounty {}", bounty_idx),
+            description_link: format!("https://github.com/o/r/issues/{}", bounty_idx),
+            payment_metadata_object: MoveObject {
+                inner: APT_FA_ADDR.to_string(),
+            },
+            payment_per_winner: PAYMENT_PER_WINNER,
+            stake_required: 0,
+            stake_lockup_in_seconds: 0,
+            winner_count: 0,
+            winner_limit: WINNER_LIMIT,
+            contact_info: "contact @apt_to_the_moon on twitter".to_string(),
+        };
+        let new_build = |build_idx: u64| {
+            let creator = new_addr(1, (bounty_idx + 1 + build_idx) % USER_COUNT);
+            let build = BuildOnChain {
+                creator: creator.clone(),
+                payment_recipient: creator,
+                payment_amount: 0,
+                create_timestamp: timestamp(bounty_version + 1 + build_idx),
+                last_update_timestamp: timestamp(bounty_version + 1 + build_idx),
+                proof_link: String::new(),
+                bounty_object: MoveObject {
+                    inner: bounty_obj_addr.clone(),
+                },
+                status: BUILD_STATUS_IN_PROGRESS as u64,
+            };
+            (
+                new_addr(3, bounty_idx * builds_per_bounty + build_idx),
+                build,
+            )
+        };
+        let submit = |mut build: BuildOnChain, build_idx: u64| {
+            build.last_update_timestamp =
+                timestamp(bounty_version + 1 + builds_per_bounty + build_idx);
+            build.proof_link = format!("https://github.com/o/r/pull/{}", build_idx);
+            build.status = BUILD_STATUS_READY_FOR_REVIEW as u64;
+            build
+        };
+
+        let event = if step == 0 {
+            new_bounty_app_event(
+                &self.contract_address,
+                "CreateBountyEvent",
+                &CreateBountyEventOnChain {
+                    bounty_obj_addr,
+                    bounty,
+                },
+            )
+        } else if step <= builds_per_bounty {
+            let (build_obj_addr, build) = new_build(step - 1);
+            new_bounty_app_event(
+                &self.contract_address,
+                "CreateBuildEvent",
+                &CreateBuildEventOnChain {
+                    build_obj_addr,
+                    build,
+                },
+            )
+        } else if step <= 2 * builds_per_bounty {
+            let build_idx = step - 1 - builds_per_bounty;
+            let (build_obj_addr, build) = new_build(build_idx);
+            new_bounty_app_event(
+                &self.contract_address,
+                "SubmitBuildForReviewEvent",
+                &SubmitBuildForReviewEventOnChain {
+                    build_obj_addr,
+                    build: submit(build, build_idx),
+                },
+            )
+        } else {
+            // The first build is accepted, then the bounty ends with one winner
+            let (build_obj_addr, build) = new_build(0);
+            let mut build = submit(build, 0);
+            let accept_timestamp = timestamp(bounty_version + 2 * builds_per_bounty + 1);
+            build.last_update_timestamp = accept_timestamp;
+            build.payment_amount = PAYMENT_PER_WINNER;
+            build.status = BUILD_STATUS_COMPLETED as u64;
+            bounty.last_update_timestamp = accept_timestamp;
+            bounty.winner_count = 1;
+            if step == 2 * builds_per_bounty + 1 {
+                new_bounty_app_event(
+                    &self.contract_address,
+                    "AcceptBuildEvent",
+                    &AcceptBuildEventOnChain {
+                        build_obj_addr,
+                        build,
+                        bounty,
+                    },
+                )
+            } else {
+                bounty.end_timestamp = timestamp(version);
+                new_bounty_app_event(
+                    &self.contract_address,
+                    "EndBountyEvent",
+                    &EndBountyEventOnChain {
+                        bounty_obj_addr,
+                        bounty,
+                        payment_sent_back_to_creator: PAYMENT_PER_WINNER * (WINNER_LIMIT - 1),
+                    },
+                )
+            }
+        };
+
+        Transaction {
+            version,
+            timestamp: Some(Timestamp {
+                seconds: timestamp(version) as i64,
+                nanos: 0,
+            }),
+            txn_data: Some(TxnData::User(UserTransaction {
+                events: vec![event],
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+    }
+}
+
+/// Addresses of generated users, bounties and builds don't collide
+fn new_addr(kind: u64, idx: u64) -> String {
+    format!("0x{:016x}{:048x}", kind, idx)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::processors::events::events_extractor::ContractEvent;
+
+    const CONTRACT_ADDRESS: &str =
+        "0x81453e030e20e46297aea5bc6c22343825f2f7d9cbc9131979d779491586f187";
+
+    fn new_generator(transaction_count: Option<u64>) -> TransactionGenerator {
+        TransactionGenerator::new(CONTRACT_ADDRESS.to_string(), 2, transaction_count).unwrap()
+    }
+
+    fn decode(transaction: &Transaction) -> Vec<ContractEvent> {
+        let Some(TxnData::User(txn)) = &transaction.txn_data else {
+            panic!("Not a user transaction");
+        };
+        ContractEvent::from_events(
+            CONTRACT_ADDRESS,
+            &txn.events,
+            transaction.version as i64,
+            0,
+            transaction.timestamp.as_ref().unwrap().seconds,
+        )
+    }
+
+    #[test]
+    fn test_generated_lifecycle_decodes() {
+        let source = TransactionSource::Generated(new_generator(None));
+        // 2 builds per bounty, the second bounty starts at version 7
+        let transactions = source.next_batch(0, 8);
+        let events = transactions.iter().flat_map(decode).collect::<Vec<_>>();
+        let names = events
+            .iter()
+            .map(|event| match event {
+                ContractEvent::CreateBountyEvent(..) => "create_bounty",
+                ContractEvent::CreateBuildEvent(..) => "create_build",
+                ContractEvent::SubmitBuildForReviewEvent(..) => "submit_build",
+                ContractEvent::AcceptBuildEvent(..) => "accept_build",
+                ContractEvent::EndBountyEvent(..) => "end_bounty",
+                _ => "other",
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                "create_bounty",
+                "create_build",
+                "create_build",
+                "submit_build",
+                "submit_build",
+                "accept_build",
+                "end_bounty",
+                "create_bounty",
+            ]
+        );
+        let ContractEvent::AcceptBuildEvent(bounty, build, _) = &events[5] else {
+            unreachable!();
+        };
+        assert_eq!(bounty.winner_count, 1);
+        assert_eq!(build.build_status, BUILD_STATUS_COMPLETED);
+        assert_eq!(build.bounty_obj_addr, bounty.bounty_obj_addr);
+
+        // Any version can be generated again on a reconnect
+        assert_eq!(source.next_batch(3, 1), vec![transactions[3].clone()]);
+    }
+
+    #[test]
+    fn test_next_batch_stops_at_the_end() {
+        let generated = TransactionSource::Generated(new_generator(Some(5)));
+        assert_eq!(generated.next_batch(3, 10).len(), 2);
+        assert!(generated.next_batch(5, 10).is_empty());
+
+        let recorded = TransactionSource::Recorded(
+            [10, 11, 12]
+                .map(|version| Transaction {
+                    version,
+                    ..Default::default()
+                })
+                .to_vec(),
+        );
+        let versions =
+            |batch: Vec<Transaction>| batch.iter().map(|txn| txn.version).collect::<Vec<_>>();
+        // Recordings can start after the requested version
+        assert_eq!(versions(recorded.next_batch(0, 2)), vec![10, 11]);
+        assert_eq!(versions(recorded.next_batch(12, 2)), vec![12]);
+        assert!(recorded.next_batch(13, 2).is_empty());
+    }
+}
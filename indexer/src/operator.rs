@@ -0,0 +1,202 @@
+//! Operator commands to inspect and reset the indexer progress, instead of hand-written SQL on
+//! `processor_status` and `ledger_infos`. Stop the indexer before changing its progress, a
+//! running indexer keeps saving the version it reached.
+
+use anyhow::{bail, Context, Result};
+use aptos_indexer_processor_sdk::aptos_indexer_transaction_stream::TransactionStream;
+use diesel::{upsert::excluded, ExpressionMethods};
+use diesel_async::RunQueryDsl;
+use std::io::{self, BufRead, Write};
+
+use crate::{
+    config::indexer_processor_config::IndexerProcessorConfig,
+    db_models::{
+        ledger_info::LedgerInfo,
+        on_chain::to_i64,
+        processor_status::{ProcessorStatus, ProcessorStatusQuery},
+    },
+    schema::{ledger_infos, processor_status},
+    utils::{
+        chain_id::check_or_update_chain_id,
+        database_connection::{get_db_connection, new_db_pool},
+        database_execution::execute_with_better_error,
+        database_migrations::get_pending_migrations,
+        database_utils::ArcDbPool,
+        starting_version::get_starting_version,
+    },
+};
+
+/// Every table written from indexed events, plus the processor status so the next run starts
+/// over. payment_tokens is seeded by migrations and ledger_infos has its own command, they are
+/// kept. Add new indexed tables here.
+const INDEXED_TABLES: [&str; 16] = [
+    "bounties",
+    "bounty_build_funnels",
+    "bounty_enrichment",
+    "bounty_search_documents",
+    "builder_cohort_retention",
+    "builds",
+    "creator_stats",
+    "daily_metrics",
+    "leaderboard_snapshots",
+    "module_upgrades",
+    "payment_token_build_funnels",
+    "points_ledger",
+    "processor_status",
+    "unhandled_events",
+    "user_first_activities",
+    "user_stats",
+];
+
+// Typed by the operator to confirm a truncate
+const TRUNCATE_CONFIRMATION: &str = "truncate";
+
+async fn new_operator_db_pool(config: &IndexerProcessorConfig) -> Result<ArcDbPool> {
+    // Commands run a few queries one after the other, one connection is enough
+    new_db_pool(&config.db_config.postgres_connection_string, 1).await
+}
+
+/// Print the last processed version, the chain id and how far behind the chain the indexer is
+pub async fn show_status(config: IndexerProcessorConfig) -> Result<()> {
+    let pool = new_operator_db_pool(&config).await?;
+    let conn = &mut get_db_connection(&pool).await?;
+    let processor_name = config.processor_config.name();
+
+    match LedgerInfo::get(conn).await? {
+        Some(ledger_info) => println!("Chain id: {}", ledger_info.chain_id),
+        None => println!("Chain id: not recorded yet"),
+    }
+    let Some(status) = ProcessorStatusQuery::get_by_processor(processor_name, conn).await? else {
+        println!("{} has not processed any version yet", processor_name);
+        return Ok(());
+    };
+
+    let now = chrono::Utc::now().naive_utc();
+    println!("Processor: {}", status.processor);
+    println!("Last success version: {}", status.last_success_version);
+    println!(
+        "Last updated: {} ({}s ago)",
+        status.last_updated,
+        (now - status.last_updated).num_seconds()
+    );
+    match status.last_transaction_timestamp {
+        Some(timestamp) => println!(
+            "Last transaction timestamp: {} (lag {}s)",
+            timestamp,
+            (now - timestamp).num_seconds()
+        ),
+        None => println!("Last transaction timestamp: unknown"),
+    }
+    Ok(())
+}
+
+/// Set the last processed version, the indexer resumes from the next one. Unlike the version
+/// tracker this can move the checkpoint backwards.
+pub async fn set_checkpoint(
+    config: IndexerProcessorConfig,
+    last_success_version: u64,
+) -> Result<()> {
+    let pool = new_operator_db_pool(&config).await?;
+    let status = ProcessorStatus {
+        processor: config.processor_config.name().to_string(),
+        last_success_version: to_i64(last_success_version),
+        last_transaction_timestamp: None,
+    };
+    let query = diesel::insert_into(processor_status::table)
+        .values(&status)
+        .on_conflict(processor_status::processor)
+        .do_update()
+        .set((
+            processor_status::last_success_version
+                .eq(excluded(processor_status::last_success_version)),
+            processor_status::last_updated.eq(excluded(processor_status::last_updated)),
+            processor_status::last_transaction_timestamp
+                .eq(excluded(processor_status::last_transaction_timestamp)),
+        ));
+    execute_with_better_error(&mut get_db_connection(&pool).await?, vec![query])
+        .await
+        .context("Failed to set the checkpoint")?;
+
+    // The starting version from the config wins if it is higher
+    let starting_version = get_starting_version(&config, pool).await?;
+    println!(
+        "Set the last success version of {} to {}, the indexer will start from version {}",
+        status.processor, last_success_version, starting_version
+    );
+    Ok(())
+}
+
+/// Forget the recorded chain id, so the indexer records the chain of the configured stream on
+/// its next start
+pub async fn clear_chain_id(config: IndexerProcessorConfig) -> Result<()> {
+    let pool = new_operator_db_pool(&config).await?;
+    let deleted = diesel::delete(ledger_infos::table)
+        .execute(&mut get_db_connection(&pool).await?)
+        .await
+        .context("Failed to clear the chain id")?;
+    println!("Cleared the chain id, {} row deleted", deleted);
+    Ok(())
+}
+
+/// Run the startup checks of the indexer without indexing: the config, the database schema and
+/// the chain of the transaction stream. Records the chain id if none is recorded yet, like the
+/// indexer does on startup.
+pub async fn verify_config(config: IndexerProcessorConfig) -> Result<()> {
+    config.pipeline_config.validate()?;
+    config.analytics_config.validate()?;
+    let pool = new_operator_db_pool(&config).await?;
+
+    let pending = get_pending_migrations(&config.db_config.postgres_connection_string).await?;
+    if !pending.is_empty() {
+        if !config.db_config.run_migrations_on_startup {
+            bail!(
+                "Database schema is behind the indexer, pending migrations: {}",
+                pending.join(", ")
+            );
+        }
+        println!(
+            "Pending migrations, applied on startup: {}",
+            pending.join(", ")
+        );
+    }
+
+    let grpc_chain_id = TransactionStream::new(config.transaction_stream_config.clone())
+        .await?
+        .get_chain_id()
+        .await?;
+    let chain_id = check_or_update_chain_id(grpc_chain_id as i64, pool.clone()).await?;
+    println!("Chain id: {}", chain_id);
+
+    let starting_version = get_starting_version(&config, pool).await?;
+    println!(
+        "Config is valid, the indexer will start from version {}",
+        starting_version
+    );
+    Ok(())
+}
+
+/// Delete all indexed data and the processor status, the next run re-indexes from the
+/// configured starting version. Asks to type a confirmation unless `yes` is set.
+pub async fn truncate_indexed_data(config: IndexerProcessorConfig, yes: bool) -> Result<()> {
+    if !yes {
+        print!(
+            "This deletes all data in {}. Type {} to confirm: ",
+            INDEXED_TABLES.join(", "),
+            TRUNCATE_CONFIRMATION
+        );
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().lock().read_line(&mut answer)?;
+        if answer.trim() != TRUNCATE_CONFIRMATION {
+            bail!("Not confirmed, nothing was deleted");
+        }
+    }
+
+    let pool = new_operator_db_pool(&config).await?;
+    diesel::sql_query(format!("TRUNCATE {}", INDEXED_TABLES.join(", ")))
+        .execute(&mut get_db_connection(&pool).await?)
+        .await
+        .context("Failed to truncate indexed data")?;
+    println!("Truncated {}", INDEXED_TABLES.join(", "));
+    Ok(())
+}
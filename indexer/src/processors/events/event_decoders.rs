@@ -0,0 +1,200 @@
+use serde::de::DeserializeOwned;
+
+use super::events_extractor::ContractEvent;
+use crate::db_models::on_chain::{
+    AcceptBuildEventOnChain, CancelBuildEventOnChain, CreateBountyEventOnChain,
+    CreateBuildEventOnChain, EndBountyEventOnChain, SubmitBuildForReviewEventOnChain,
+};
+
+/// Decodes an event's JSON data, given the event index and the transaction version
+type DecodeFn = fn(&str, i64, i64) -> ContractEvent;
+
+pub struct EventDecoder {
+    // Event type without the contract address, e.g. bounty_app::CreateBountyEvent
+    pub event_name: &'static str,
+    // Upgrade of the contract package that introduced this shape of the event
+    pub since_upgrade_number: i64,
+    pub decode: DecodeFn,
+}
+
+/// Every event the indexer understands. Compatible upgrades can't change an existing struct, so a
+/// new shape usually comes as a new event (e.g. CreateBountyEventV2) with its own decoder. A
+/// decoder with a higher since_upgrade_number replaces the older one of the same event from that
+/// upgrade on.
+pub const EVENT_DECODERS: &[EventDecoder] = &[
+    EventDecoder {
+        event_name: "bounty_app::CreateBountyEvent",
+        since_upgrade_number: 0,
+        decode: decode_create_bounty_event,
+    },
+    EventDecoder {
+        event_name: "bounty_app::EndBountyEvent",
+        since_upgrade_number: 0,
+        decode: decode_end_bounty_event,
+    },
+    EventDecoder {
+        event_name: "bounty_app::CreateBuildEvent",
+        since_upgrade_number: 0,
+        decode: decode_create_build_event,
+    },
+    EventDecoder {
+        event_name: "bounty_app::CancelBuildEvent",
+        since_upgrade_number: 0,
+        decode: decode_cancel_build_event,
+    },
+    EventDecoder {
+        event_name: "bounty_app::SubmitBuildForReviewEvent",
+        since_upgrade_number: 0,
+        decode: decode_submit_build_for_review_event,
+    },
+    EventDecoder {
+        event_name: "bounty_app::AcceptBuildEvent",
+        since_upgrade_number: 0,
+        decode: decode_accept_build_event,
+    },
+];
+
+/// Decoder for an event emitted by the given upgrade of the contract package, None if the indexer
+/// doesn't know the event.
+pub fn find_event_decoder(event_name: &str, upgrade_number: i64) -> Option<&'static EventDecoder> {
+    select_event_decoder(EVENT_DECODERS, event_name, upgrade_number)
+}
+
+fn select_event_decoder<'a>(
+    decoders: &'a [EventDecoder],
+    event_name: &str,
+    upgrade_number: i64,
+) -> Option<&'a EventDecoder> {
+    let candidates = decoders
+        .iter()
+        .filter(|decoder| decoder.event_name == event_name);
+    candidates
+        .clone()
+        .filter(|decoder| decoder.since_upgrade_number <= upgrade_number)
+        .max_by_key(|decoder| decoder.since_upgrade_number)
+        // An event can't be older than its struct, so the upgrade history is incomplete, e.g. the
+        // indexer started after the upgrade. The oldest decoder is the best guess.
+        .or_else(|| candidates.min_by_key(|decoder| decoder.since_upgrade_number))
+}
+
+fn parse_event_data<T: DeserializeOwned>(event_name: &str, data: &str) -> T {
+    serde_json::from_str(data)
+        .unwrap_or_else(|e| panic!("Failed to parse {}, {}, {}", event_name, e, data))
+}
+
+fn decode_create_bounty_event(data: &str, _event_idx: i64, txn_version: i64) -> ContractEvent {
+    let event: CreateBountyEventOnChain = parse_event_data("CreateBountyEvent", data);
+    ContractEvent::CreateBountyEvent(event.to_db_bounty(txn_version), txn_version)
+}
+
+fn decode_end_bounty_event(data: &str, event_idx: i64, txn_version: i64) -> ContractEvent {
+    let event: EndBountyEventOnChain = parse_event_data("EndBountyEvent", data);
+    ContractEvent::EndBountyEvent(
+        event.to_db_bounty(txn_version, event_idx),
+        event.to_payment_sent_back_to_creator(),
+        txn_version,
+    )
+}
+
+fn decode_create_build_event(data: &str, _event_idx: i64, txn_version: i64) -> ContractEvent {
+    let event: CreateBuildEventOnChain = parse_event_data("CreateBuildEvent", data);
+    ContractEvent::CreateBuildEvent(event.to_db_build(txn_version), txn_version)
+}
+
+fn decode_cancel_build_event(data: &str, event_idx: i64, txn_version: i64) -> ContractEvent {
+    let event: CancelBuildEventOnChain = parse_event_data("CancelBuildEvent", data);
+    ContractEvent::CancelBuildEvent(event.to_db_build(txn_version, event_idx), txn_version)
+}
+
+fn decode_submit_build_for_review_event(
+    data: &str,
+    event_idx: i64,
+    txn_version: i64,
+) -> ContractEvent {
+    let event: SubmitBuildForReviewEventOnChain =
+        parse_event_data("SubmitBuildForReviewEvent", data);
+    ContractEvent::SubmitBuildForReviewEvent(event.to_db_build(txn_version, event_idx), txn_version)
+}
+
+fn decode_accept_build_event(data: &str, event_idx: i64, txn_version: i64) -> ContractEvent {
+    let event: AcceptBuildEventOnChain = parse_event_data("AcceptBuildEvent", data);
+    ContractEvent::AcceptBuildEvent(
+        event.to_db_bounty(txn_version, event_idx),
+        event.to_db_build(txn_version, event_idx),
+        txn_version,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const BUILD_EVENT_JSON: &str = r#"{
+        "build_obj_addr": "0xb",
+        "build": {
+            "creator": "0x1",
+            "payment_recipient": "0x1",
+            "payment_amount": "0",
+            "create_timestamp": "1729000000",
+            "last_update_timestamp": "1729000000",
+            "proof_link": "",
+            "bounty_object": {"inner": "0xa"},
+            "status": "1"
+        }
+    }"#;
+
+    // Two shapes of the same event, told apart by the variant they decode to
+    const DECODERS: &[EventDecoder] = &[
+        EventDecoder {
+            event_name: "bounty_app::SomeEvent",
+            since_upgrade_number: 1,
+            decode: decode_cancel_build_event,
+        },
+        EventDecoder {
+            event_name: "bounty_app::SomeEvent",
+            since_upgrade_number: 3,
+            decode: decode_submit_build_for_review_event,
+        },
+    ];
+
+    fn decode_with(upgrade_number: i64) -> Option<ContractEvent> {
+        select_event_decoder(DECODERS, "bounty_app::SomeEvent", upgrade_number)
+            .map(|decoder| (decoder.decode)(BUILD_EVENT_JSON, 0, 7))
+    }
+
+    #[test]
+    fn test_select_event_decoder_by_upgrade_number() {
+        assert!(matches!(
+            decode_with(1),
+            Some(ContractEvent::CancelBuildEvent(_, 7))
+        ));
+        assert!(matches!(
+            decode_with(2),
+            Some(ContractEvent::CancelBuildEvent(_, 7))
+        ));
+        assert!(matches!(
+            decode_with(3),
+            Some(ContractEvent::SubmitBuildForReviewEvent(_, 7))
+        ));
+        // Older than any decoder, falls back to the oldest one
+        assert!(matches!(
+            decode_with(0),
+            Some(ContractEvent::CancelBuildEvent(_, 7))
+        ));
+        assert!(select_event_decoder(DECODERS, "bounty_app::OtherEvent", 3).is_none());
+    }
+
+    #[test]
+    fn test_find_event_decoder() {
+        let decoder = find_event_decoder("bounty_app::CreateBuildEvent", 0).unwrap();
+        match (decoder.decode)(BUILD_EVENT_JSON, 0, 7) {
+            ContractEvent::CreateBuildEvent(build, 7) => {
+                assert_eq!(build.bounty_obj_addr, format!("0x{:0>64}", "a"))
+            }
+            event => panic!("Unexpected event {:?}", event),
+        }
+        // Used to be a prefix match, which decoded a V2 event as V1
+        assert!(find_event_decoder("bounty_app::CreateBuildEventV2", 5).is_none());
+        assert!(find_event_decoder("other_module::CreateBuildEvent", 0).is_none());
+    }
+}
@@ -6,14 +6,13 @@ use aptos_indexer_processor_sdk::{
     utils::errors::ProcessorError,
 };
 use async_trait::async_trait;
+use bigdecimal::BigDecimal;
 use rayon::prelude::*;
+use serde::Serialize;
 
+use super::{event_decoders::find_event_decoder, module_upgrade_tracker::ModuleUpgradeTracker};
 use crate::db_models::{
-    bounty::{Bounty, CreateBountyEventOnChain, EndBountyEventOnChain},
-    build::{
-        AcceptBuildEventOnChain, Build, CancelBuildEventOnChain, CreateBuildEventOnChain,
-        SubmitBuildForReviewEventOnChain,
-    },
+    bounty::Bounty, build::Build, module_upgrade::ModuleUpgrade, unhandled_event::UnhandledEvent,
 };
 
 /// EventsExtractor is a step that extracts events and their metadata from transactions.
@@ -22,11 +21,17 @@ where
     Self: Sized + Send + 'static,
 {
     contract_address: String,
+    module_upgrade_tracker: ModuleUpgradeTracker,
 }
 
 impl EventsExtractor {
-    pub fn new(contract_address: String) -> Self {
-        Self { contract_address }
+    pub fn new(contract_address: String, known_module_upgrades: &[ModuleUpgrade]) -> Self {
+        let module_upgrade_tracker =
+            ModuleUpgradeTracker::new(&contract_address, known_module_upgrades);
+        Self {
+            contract_address,
+            module_upgrade_tracker,
+        }
     }
 }
 
@@ -48,7 +53,27 @@ impl Processable for EventsExtractor {
         &mut self,
         item: TransactionContext<Transaction>,
     ) -> Result<Option<TransactionContext<ContractEvent>>, ProcessorError> {
-        let events = item
+        // Upgrades must be recorded before decoding the events of the batch they are in
+        let module_upgrades = item
+            .data
+            .iter()
+            .filter_map(|txn| self.module_upgrade_tracker.detect_upgrade(txn))
+            .collect::<Vec<_>>();
+        for upgrade in &module_upgrades {
+            tracing::info!(
+                transaction_version = upgrade.txn_version,
+                upgrade_number = upgrade.upgrade_number,
+                "Contract package upgraded"
+            );
+            self.module_upgrade_tracker.record_upgrade(upgrade);
+        }
+
+        let mut events = module_upgrades
+            .into_iter()
+            .map(ContractEvent::ModuleUpgradeEvent)
+            .collect::<Vec<_>>();
+        // Collecting keeps the (version, event index) order, the storer relies on it
+        let decoded_events = item
             .data
             .par_iter()
             .map(|txn| {
@@ -72,13 +97,23 @@ impl Processable for EventsExtractor {
                     _ => &default,
                 };
 
-                let txn_events =
-                    ContractEvent::from_events(self.contract_address.as_str(), raw_events);
+                let txn_events = ContractEvent::from_events(
+                    self.contract_address.as_str(),
+                    raw_events,
+                    txn_version,
+                    self.module_upgrade_tracker
+                        .get_upgrade_number_at(txn_version),
+                    txn.timestamp
+                        .as_ref()
+                        .map(|t| t.seconds)
+                        .unwrap_or_default(),
+                );
                 events.extend(txn_events);
                 events
             })
             .flatten()
             .collect::<Vec<ContractEvent>>();
+        events.extend(decoded_events);
         Ok(Some(TransactionContext {
             data: events,
             start_version: item.start_version,
@@ -91,109 +126,200 @@ impl Processable for EventsExtractor {
 }
 
 #[derive(Debug, Clone)]
+/// The last i64 of every bounty and build variant is the version of the transaction that emitted
+/// the event
 pub enum ContractEvent {
-    CreateBountyEvent(Bounty),
-    EndBountyEvent(Bounty, i64),
-    CreateBuildEvent(Build),
-    CancelBuildEvent(Build),
-    SubmitBuildForReviewEvent(Build),
-    AcceptBuildEvent(Bounty, Build),
+    CreateBountyEvent(Bounty, i64),
+    // The amount is the payment sent back to creator
+    EndBountyEvent(Bounty, BigDecimal, i64),
+    CreateBuildEvent(Build, i64),
+    CancelBuildEvent(Build, i64),
+    SubmitBuildForReviewEvent(Build, i64),
+    AcceptBuildEvent(Bounty, Build, i64),
+    // Not an event, the transaction published or upgraded the contract package
+    ModuleUpgradeEvent(ModuleUpgrade),
+    // Emitted by the contract but no decoder knows it
+    UnhandledEvent(UnhandledEvent),
 }
 
 impl ContractEvent {
-    fn from_event(contract_address: &str, event_idx: usize, event: &EventPB) -> Option<Self> {
-        let t: &str = event.type_str.as_ref();
-        let should_include = t.starts_with(contract_address);
-
-        if should_include {
-            if t.starts_with(
-                format!("{}::bounty_app::CreateBountyEvent", contract_address).as_str(),
-            ) {
-                println!("CreateBountyEvent {}", event.data.as_str());
-                let create_bounty_event_on_chain: CreateBountyEventOnChain =
-                    serde_json::from_str(event.data.as_str()).unwrap_or_else(|_| {
-                        panic!("Failed to parse CreateBountyEvent, {}", event.data.as_str())
-                    });
-                Some(ContractEvent::CreateBountyEvent(
-                    create_bounty_event_on_chain.to_db_bounty(),
-                ))
-            } else if t
-                .starts_with(format!("{}::bounty_app::EndBountyEvent", contract_address).as_str())
-            {
-                println!("EndBountyEvent {}", event.data.as_str());
-                let end_bounty_event_on_chain: EndBountyEventOnChain =
-                    serde_json::from_str(event.data.as_str()).unwrap_or_else(|_| {
-                        panic!("Failed to parse EndBountyEvent, {}", event.data.as_str())
-                    });
-                Some(ContractEvent::EndBountyEvent(
-                    end_bounty_event_on_chain.to_db_bounty(event_idx as i64),
-                    end_bounty_event_on_chain.to_payment_sent_back_to_creator(),
-                ))
-            } else if t
-                .starts_with(format!("{}::bounty_app::CreateBuildEvent", contract_address).as_str())
-            {
-                println!("CreateBuildEvent {}", event.data.as_str());
-                let create_build_event_on_chain: CreateBuildEventOnChain =
-                    serde_json::from_str(event.data.as_str()).unwrap_or_else(|_| {
-                        panic!("Failed to parse CreateBuildEvent, {}", event.data.as_str())
-                    });
-                Some(ContractEvent::CreateBuildEvent(
-                    create_build_event_on_chain.to_db_build(),
-                ))
-            } else if t
-                .starts_with(format!("{}::bounty_app::CancelBuildEvent", contract_address).as_str())
-            {
-                println!("CancelBuildEvent {}", event.data.as_str());
-                let cancel_build_event_on_chain: CancelBuildEventOnChain =
-                    serde_json::from_str(event.data.as_str()).unwrap_or_else(|_| {
-                        panic!("Failed to parse CancelBuildEvent, {}", event.data.as_str())
-                    });
-                Some(ContractEvent::CancelBuildEvent(
-                    cancel_build_event_on_chain.to_db_build(event_idx as i64),
-                ))
-            } else if t.starts_with(
-                format!(
-                    "{}::bounty_app::SubmitBuildForReviewEvent",
-                    contract_address
-                )
-                .as_str(),
-            ) {
-                println!("SubmitBuildForReviewEvent {}", event.data.as_str());
-                let submit_build_for_review_event_on_chain: SubmitBuildForReviewEventOnChain =
-                    serde_json::from_str(event.data.as_str()).unwrap_or_else(|_| {
-                        panic!(
-                            "Failed to parse SubmitBuildForReviewEvent, {}",
-                            event.data.as_str()
-                        )
-                    });
-                Some(ContractEvent::SubmitBuildForReviewEvent(
-                    submit_build_for_review_event_on_chain.to_db_build(event_idx as i64),
-                ))
-            } else if t
-                .starts_with(format!("{}::bounty_app::AcceptBuildEvent", contract_address).as_str())
-            {
-                println!("AcceptBuildEvent {}", event.data.as_str());
-                let accept_build_event_on_chain: AcceptBuildEventOnChain =
-                    serde_json::from_str(event.data.as_str()).unwrap_or_else(|_| {
-                        panic!("Failed to parse AcceptBuildEvent, {}", event.data.as_str())
-                    });
-                Some(ContractEvent::AcceptBuildEvent(
-                    accept_build_event_on_chain.to_db_bounty(event_idx as i64),
-                    accept_build_event_on_chain.to_db_build(event_idx as i64),
+    fn from_event(
+        contract_address: &str,
+        event_idx: usize,
+        event: &EventPB,
+        txn_version: i64,
+        upgrade_number: i64,
+        timestamp: i64,
+    ) -> Option<Self> {
+        let event_name = event
+            .type_str
+            .strip_prefix(contract_address)?
+            .strip_prefix("::")?;
+        match find_event_decoder(event_name, upgrade_number) {
+            Some(decoder) => {
+                tracing::debug!(
+                    transaction_version = txn_version,
+                    event_idx = event_idx,
+                    data = event.data.as_str(),
+                    "Decoding event {}",
+                    event_name
+                );
+                Some((decoder.decode)(
+                    event.data.as_str(),
+                    event_idx as i64,
+                    txn_version,
                 ))
-            } else {
-                None
             }
-        } else {
-            None
+            None => {
+                tracing::warn!(
+                    transaction_version = txn_version,
+                    upgrade_number = upgrade_number,
+                    "No decoder for event {}, storing it in unhandled_events",
+                    event.type_str
+                );
+                Some(ContractEvent::UnhandledEvent(UnhandledEvent {
+                    txn_version,
+                    event_idx: event_idx as i64,
+                    type_str: event.type_str.clone(),
+                    data: event.data.clone(),
+                    upgrade_number,
+                    timestamp,
+                }))
+            }
         }
     }
 
-    pub fn from_events(contract_address: &str, events: &[EventPB]) -> Vec<Self> {
+    pub fn from_events(
+        contract_address: &str,
+        events: &[EventPB],
+        txn_version: i64,
+        upgrade_number: i64,
+        timestamp: i64,
+    ) -> Vec<Self> {
         events
             .iter()
             .enumerate()
-            .filter_map(|(idx, event)| Self::from_event(contract_address, idx, event))
+            .filter_map(|(idx, event)| {
+                Self::from_event(
+                    contract_address,
+                    idx,
+                    event,
+                    txn_version,
+                    upgrade_number,
+                    timestamp,
+                )
+            })
             .collect()
     }
 }
+
+/// Event of the bounty_app module as the chain emits it, `data` is one of the `*OnChain` events.
+/// The mock stream serves these and tests build their events with it.
+pub fn new_bounty_app_event(
+    contract_address: &str,
+    event_name: &str,
+    data: &impl Serialize,
+) -> EventPB {
+    EventPB {
+        type_str: format!("{}::bounty_app::{}", contract_address, event_name),
+        data: serde_json::to_string(data).unwrap(),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_events {
+    use super::*;
+    use crate::{
+        db_models::on_chain::{
+            AcceptBuildEventOnChain, BountyOnChain, BuildOnChain, CancelBuildEventOnChain,
+            CreateBuildEventOnChain, MoveObject, SubmitBuildForReviewEventOnChain,
+        },
+        processors::events::storers::APT_FA_ADDR,
+    };
+
+    const CONTRACT_ADDRESS: &str = "0xc";
+
+    /// Decode a build event of bounty 0xa created by 0x1, through the same decoders as the chain
+    pub(crate) fn new_build_event(
+        event_name: &str,
+        build_obj_addr: &str,
+        status: i64,
+        last_update_timestamp: i64,
+        txn_version: i64,
+    ) -> ContractEvent {
+        let build_obj_addr = build_obj_addr.to_string();
+        let build = BuildOnChain {
+            creator: "0x1".to_string(),
+            payment_recipient: "0x1".to_string(),
+            payment_amount: 0,
+            create_timestamp: 1729000000,
+            last_update_timestamp: last_update_timestamp as u64,
+            proof_link: String::new(),
+            bounty_object: MoveObject {
+                inner: "0xa".to_string(),
+            },
+            status: status as u64,
+        };
+        let event = match event_name {
+            "CreateBuildEvent" => new_bounty_app_event(
+                CONTRACT_ADDRESS,
+                event_name,
+                &CreateBuildEventOnChain {
+                    build_obj_addr,
+                    build,
+                },
+            ),
+            "CancelBuildEvent" => new_bounty_app_event(
+                CONTRACT_ADDRESS,
+                event_name,
+                &CancelBuildEventOnChain {
+                    build_obj_addr,
+                    build,
+                },
+            ),
+            "SubmitBuildForReviewEvent" => new_bounty_app_event(
+                CONTRACT_ADDRESS,
+                event_name,
+                &SubmitBuildForReviewEventOnChain {
+                    build_obj_addr,
+                    build,
+                },
+            ),
+            "AcceptBuildEvent" => new_bounty_app_event(
+                CONTRACT_ADDRESS,
+                event_name,
+                &AcceptBuildEventOnChain {
+                    build_obj_addr,
+                    bounty: BountyOnChain {
+                        creator: "0x2".to_string(),
+                        create_timestamp: 1729000000,
+                        last_update_timestamp: last_update_timestamp as u64,
+                        end_timestamp: 1729604800,
+                        title: "Bounty".to_string(),
+                        description_link: String::new(),
+                        payment_metadata_object: MoveObject {
+                            inner: APT_FA_ADDR.to_string(),
+                        },
+                        payment_per_winner: build.payment_amount,
+                        stake_required: 0,
+                        stake_lockup_in_seconds: 0,
+                        winner_count: 1,
+                        winner_limit: 1,
+                        contact_info: String::new(),
+                    },
+                    build,
+                },
+            ),
+            _ => panic!("{} is not a build event", event_name),
+        };
+        ContractEvent::from_events(
+            CONTRACT_ADDRESS,
+            &[event],
+            txn_version,
+            0,
+            last_update_timestamp,
+        )
+        .remove(0)
+    }
+}
@@ -6,13 +6,25 @@ use aptos_indexer_processor_sdk::{
     traits::IntoRunnableStep,
 };
 
-use super::{events_extractor::EventsExtractor, events_storer::EventsStorer};
+use super::{
+    events_extractor::EventsExtractor, events_storer::EventsStorer,
+    leaderboard_snapshotter::LeaderboardSnapshotter,
+    storers::daily_metrics_storer::backfill_daily_metrics,
+};
 use crate::{
     config::indexer_processor_config::IndexerProcessorConfig,
+    db_models::module_upgrade::ModuleUpgrade,
+    enrichment::spawn_enrichment_worker,
     utils::{
-        chain_id::check_or_update_chain_id, database_connection::new_db_pool,
-        database_utils::ArcDbPool, latest_processed_version_tracker::LatestVersionProcessedTracker,
+        chain_id::check_or_update_chain_id,
+        database_connection::{get_db_connection, new_db_pool},
+        database_migrations::check_or_run_pending_migrations,
+        database_utils::ArcDbPool,
+        latest_processed_version_tracker::LatestVersionProcessedTracker,
         starting_version::get_starting_version,
+        transaction_files::{
+            get_recorded_chain_id, TransactionFileRecorder, TransactionFileReplayStep,
+        },
     },
 };
 
@@ -23,11 +35,14 @@ pub struct EventsProcessor {
 
 impl EventsProcessor {
     pub async fn new(config: IndexerProcessorConfig) -> Result<Self> {
+        config.pipeline_config.validate()?;
+        config.transaction_files_config.validate()?;
+        config.analytics_config.validate()?;
         let conn_pool = new_db_pool(
             &config.db_config.postgres_connection_string,
             config.db_config.db_pool_size,
         )
-        .await;
+        .await?;
 
         Ok(Self {
             config,
@@ -36,6 +51,12 @@ impl EventsProcessor {
     }
 
     pub async fn run_processor(self) -> Result<()> {
+        check_or_run_pending_migrations(
+            &self.config.db_config.postgres_connection_string,
+            self.config.db_config.run_migrations_on_startup,
+        )
+        .await?;
+
         // Merge the starting version from config and the latest processed version from the DB
         let starting_version = get_starting_version(&self.config, self.db_pool.clone()).await?;
 
@@ -45,20 +66,51 @@ impl EventsProcessor {
         );
 
         // Check and update the ledger chain id to ensure we're indexing the correct chain
-        let grpc_chain_id = TransactionStream::new(self.config.transaction_stream_config.clone())
-            .await?
-            .get_chain_id()
-            .await?;
+        let transaction_files_config = &self.config.transaction_files_config;
+        let grpc_chain_id = match &transaction_files_config.replay_dir {
+            Some(replay_dir) => get_recorded_chain_id(replay_dir)?,
+            None => {
+                TransactionStream::new(self.config.transaction_stream_config.clone())
+                    .await?
+                    .get_chain_id()
+                    .await?
+            }
+        };
         check_or_update_chain_id(grpc_chain_id as i64, self.db_pool.clone()).await?;
 
+        if self
+            .config
+            .analytics_config
+            .backfill_daily_metrics_on_startup
+        {
+            backfill_daily_metrics(
+                self.db_pool.clone(),
+                self.config.contract_config.contract_address.clone(),
+            )
+            .await?;
+        }
+
+        if self.config.enrichment_config.enabled {
+            spawn_enrichment_worker(self.db_pool.clone(), self.config.enrichment_config.clone())?;
+        }
+
         // Define processor steps
-        let transaction_stream = TransactionStreamStep::new(TransactionStreamConfig {
-            starting_version: Some(starting_version),
-            ..self.config.transaction_stream_config
-        })
-        .await?;
-        let events_extractor = EventsExtractor::new(self.config.contract_config.contract_address);
-        let events_storer = EventsStorer::new(self.db_pool.clone());
+        let known_module_upgrades =
+            ModuleUpgrade::get_all(&mut get_db_connection(&self.db_pool).await?).await?;
+        let events_extractor = EventsExtractor::new(
+            self.config.contract_config.contract_address.clone(),
+            &known_module_upgrades,
+        );
+        let events_storer = EventsStorer::new(
+            self.db_pool.clone(),
+            self.config.contract_config.contract_address,
+            self.config.processor_config.name().to_string(),
+            self.config.db_config.commit_batch_atomically,
+            self.config.pipeline_config.clone(),
+            self.config.analytics_config.clone(),
+        );
+        let leaderboard_snapshotter =
+            LeaderboardSnapshotter::new(self.db_pool.clone(), self.config.leaderboard_config);
         let version_tracker = LatestVersionProcessedTracker::new(
             self.config.db_config,
             starting_version,
@@ -66,14 +118,36 @@ impl EventsProcessor {
         )
         .await?;
 
-        // Connect processor steps together
-        let (_, buffer_receiver) = ProcessorBuilder::new_with_inputless_first_step(
-            transaction_stream.into_runnable_step(),
-        )
-        .connect_to(events_extractor.into_runnable_step(), 10)
-        .connect_to(events_storer.into_runnable_step(), 10)
-        .connect_to(version_tracker.into_runnable_step(), 10)
-        .end_and_return_output_receiver(10);
+        // Connect processor steps together, the transactions come from the gRPC stream or from
+        // recorded files
+        let channel_size = self.config.pipeline_config.channel_size;
+        let builder = match &transaction_files_config.replay_dir {
+            Some(replay_dir) => ProcessorBuilder::new_with_inputless_first_step(
+                TransactionFileReplayStep::new(replay_dir, starting_version)?.into_runnable_step(),
+            ),
+            None => ProcessorBuilder::new_with_inputless_first_step(
+                TransactionStreamStep::new(TransactionStreamConfig {
+                    starting_version: Some(starting_version),
+                    ..self.config.transaction_stream_config.clone()
+                })
+                .await?
+                .into_runnable_step(),
+            ),
+        };
+        let builder = match &transaction_files_config.record_dir {
+            Some(record_dir) => builder.connect_to(
+                TransactionFileRecorder::new(record_dir.clone(), grpc_chain_id)?
+                    .into_runnable_step(),
+                channel_size,
+            ),
+            None => builder,
+        };
+        let (_, buffer_receiver) = builder
+            .connect_to(events_extractor.into_runnable_step(), channel_size)
+            .connect_to(events_storer.into_runnable_step(), channel_size)
+            .connect_to(leaderboard_snapshotter.into_runnable_step(), channel_size)
+            .connect_to(version_tracker.into_runnable_step(), channel_size)
+            .end_and_return_output_receiver(channel_size);
 
         // (Optional) Parse the results
         loop {
@@ -1,4 +1,4 @@
-use ahash::AHashMap;
+use ahash::AHashSet;
 use anyhow::Result;
 use aptos_indexer_processor_sdk::{
     traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
@@ -6,26 +6,44 @@ use aptos_indexer_processor_sdk::{
     utils::errors::ProcessorError,
 };
 use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use std::sync::Arc;
 
 use super::{
     events_extractor::ContractEvent,
+    storage::{postgres_events_storage::PostgresEventsStorage, EventsStorage},
     storers::{
-        accept_build_event_storer::process_accept_build_events,
-        cancel_build_event_storer::process_cancel_build_events,
-        create_bounty_event_storer::process_create_bounty_events,
-        create_build_event_storer::process_create_build_events,
-        end_bounty_event_storer::process_end_bounty_events,
-        submit_build_for_review_event_storer::process_submit_build_for_review_events,
+        accept_build_event_storer::get_accept_build_user_stat_change,
+        cancel_build_event_storer::get_cancel_build_user_stat_change,
+        create_bounty_event_storer::get_create_bounty_user_stat_change,
+        create_build_event_storer::get_create_build_user_stat_change,
+        creator_stats_storer::reduce_creator_refunds,
+        daily_metrics_storer::get_day_timestamp,
+        end_bounty_event_storer::{
+            get_end_bounty_creator_stat_change, get_end_bounty_user_stat_change,
+        },
+        submit_build_for_review_event_storer::get_submit_build_for_review_user_stat_change,
+        user_stats_storer::reduce_user_stat_changes,
     },
 };
-use crate::utils::database_utils::ArcDbPool;
+use crate::{
+    config::indexer_processor_config::{AnalyticsConfig, PipelineConfig},
+    db_models::{
+        bounty::Bounty, build::Build, creator_stat::CreatorStat, module_upgrade::ModuleUpgrade,
+        unhandled_event::UnhandledEvent, user_stat::UserStat,
+    },
+    utils::{database_utils::ArcDbPool, latest_processed_version_tracker::new_processor_status},
+};
 
-/// EventsStorer is a step that inserts events in the database.
+/// EventsStorer is a step that writes events to an EventsStorage, Postgres unless another
+/// storage is given.
 pub struct EventsStorer
 where
     Self: Sized + Send + 'static,
 {
-    pool: ArcDbPool,
+    storage: Arc<dyn EventsStorage>,
+    processor_name: String,
+    commit_batch_atomically: bool,
 }
 
 impl AsyncStep for EventsStorer {}
@@ -37,8 +55,69 @@ impl NamedStep for EventsStorer {
 }
 
 impl EventsStorer {
-    pub fn new(pool: ArcDbPool) -> Self {
-        Self { pool }
+    pub fn new(
+        pool: ArcDbPool,
+        contract_address: String,
+        processor_name: String,
+        commit_batch_atomically: bool,
+        pipeline_config: PipelineConfig,
+        analytics_config: AnalyticsConfig,
+    ) -> Self {
+        Self::with_storage(
+            Arc::new(PostgresEventsStorage::new(
+                pool,
+                contract_address,
+                pipeline_config,
+                analytics_config,
+            )),
+            processor_name,
+            commit_batch_atomically,
+        )
+    }
+
+    pub fn with_storage(
+        storage: Arc<dyn EventsStorage>,
+        processor_name: String,
+        commit_batch_atomically: bool,
+    ) -> Self {
+        Self {
+            storage,
+            processor_name,
+            commit_batch_atomically,
+        }
+    }
+
+    async fn process_segment(&self, segment: EventSegment) -> Result<(), ProcessorError> {
+        let EventSegment {
+            create_bounty_events,
+            end_bounty_events,
+            create_build_events,
+            cancel_build_events,
+            submit_build_for_review_events,
+            accept_build_events,
+            module_upgrades,
+            unhandled_events,
+        } = segment;
+
+        self.storage.insert_module_upgrades(module_upgrades).await?;
+
+        self.storage
+            .insert_unhandled_events(unhandled_events)
+            .await?;
+
+        self.storage.create_bounties(create_bounty_events).await?;
+
+        self.storage.end_bounties(end_bounty_events).await?;
+
+        self.storage.create_builds(create_build_events).await?;
+
+        self.storage.cancel_builds(cancel_build_events).await?;
+
+        self.storage
+            .submit_builds_for_review(submit_build_for_review_events)
+            .await?;
+
+        self.storage.accept_builds(accept_build_events).await
     }
 }
 
@@ -52,98 +131,293 @@ impl Processable for EventsStorer {
         &mut self,
         events: TransactionContext<ContractEvent>,
     ) -> Result<Option<TransactionContext<ContractEvent>>, ProcessorError> {
-        let per_table_chunk_sizes: AHashMap<String, usize> = AHashMap::new();
-        let (
-            create_bounty_events,
-            end_bounty_events,
-            create_build_events,
-            cancel_build_events,
-            submit_build_for_review_events,
-            accept_build_events,
-        ) = events.clone().data.into_iter().fold(
-            (vec![], vec![], vec![], vec![], vec![], vec![]),
-            |(
-                mut create_bounty_events,
-                mut end_bounty_events,
-                mut create_build_events,
-                mut cancel_build_events,
-                mut submit_build_for_review_events,
-                mut accept_build_events,
-            ),
-             event| {
-                match event {
-                    ContractEvent::CreateBountyEvent(bounty) => {
-                        create_bounty_events.push(bounty);
-                    }
-                    ContractEvent::EndBountyEvent(bounty, payment_sent_back_to_creator) => {
-                        end_bounty_events.push((bounty, payment_sent_back_to_creator));
-                    }
-                    ContractEvent::CreateBuildEvent(build) => {
-                        create_build_events.push(build);
-                    }
-                    ContractEvent::CancelBuildEvent(build) => {
-                        cancel_build_events.push(build);
-                    }
-                    ContractEvent::SubmitBuildForReviewEvent(build) => {
-                        submit_build_for_review_events.push(build);
-                    }
-                    ContractEvent::AcceptBuildEvent(bounty, build) => {
-                        accept_build_events.push((bounty, build));
-                    }
+        let writes = BatchWrites::from_events(events.data.clone());
+        if self.commit_batch_atomically {
+            let checkpoint = new_processor_status(self.processor_name.clone(), &events);
+            self.storage
+                .write_batch_atomically(writes, checkpoint)
+                .await?;
+            return Ok(Some(events));
+        }
+
+        let BatchWrites {
+            segments,
+            user_stat_changes,
+            creator_refunds,
+            touched_bounty_obj_addrs,
+            touched_day_timestamps,
+            latest_timestamp,
+        } = writes;
+        for segment in segments {
+            self.process_segment(segment).await?;
+        }
+
+        self.storage
+            .apply_user_stat_changes(user_stat_changes)
+            .await?;
+
+        self.storage.apply_creator_refunds(creator_refunds).await?;
+
+        self.storage
+            .refresh_aggregates(
+                touched_bounty_obj_addrs,
+                touched_day_timestamps,
+                latest_timestamp,
+            )
+            .await?;
+
+        Ok(Some(events))
+    }
+}
+
+/// Everything a batch writes, derived from its events
+pub struct BatchWrites {
+    pub segments: Vec<EventSegment>,
+    pub user_stat_changes: Vec<UserStat>,
+    pub creator_refunds: Vec<CreatorStat>,
+    pub touched_bounty_obj_addrs: Vec<String>,
+    pub touched_day_timestamps: Vec<i64>,
+    pub latest_timestamp: i64,
+}
+
+impl BatchWrites {
+    pub fn from_events(events: Vec<ContractEvent>) -> Self {
+        let (touched_bounty_obj_addrs, touched_day_timestamps, latest_timestamp) =
+            get_touched_bounties_and_days(&events);
+        let user_stat_changes = get_user_stat_changes(&events);
+        let creator_refunds = get_creator_refunds(&events);
+        Self {
+            segments: split_into_ordered_segments(events),
+            user_stat_changes,
+            creator_refunds,
+            touched_bounty_obj_addrs,
+            touched_day_timestamps,
+            latest_timestamp,
+        }
+    }
+}
+
+/// Consecutive events of a batch grouped by type, written one type after another
+#[derive(Default)]
+pub struct EventSegment {
+    pub create_bounty_events: Vec<(Bounty, i64)>,
+    pub end_bounty_events: Vec<(Bounty, BigDecimal)>,
+    pub create_build_events: Vec<(Build, i64)>,
+    pub cancel_build_events: Vec<(Build, i64)>,
+    pub submit_build_for_review_events: Vec<Build>,
+    pub accept_build_events: Vec<(Bounty, Build, i64)>,
+    pub module_upgrades: Vec<ModuleUpgrade>,
+    pub unhandled_events: Vec<UnhandledEvent>,
+}
+
+impl EventSegment {
+    fn from_events(events: Vec<ContractEvent>) -> Self {
+        let mut segment = Self::default();
+        for event in events {
+            match event {
+                ContractEvent::CreateBountyEvent(bounty, txn_version) => {
+                    segment.create_bounty_events.push((bounty, txn_version));
                 }
-                (
-                    create_bounty_events,
-                    end_bounty_events,
-                    create_build_events,
-                    cancel_build_events,
-                    submit_build_for_review_events,
-                    accept_build_events,
-                )
-            },
-        );
+                ContractEvent::EndBountyEvent(bounty, payment_sent_back_to_creator, _) => {
+                    segment
+                        .end_bounty_events
+                        .push((bounty, payment_sent_back_to_creator));
+                }
+                ContractEvent::CreateBuildEvent(build, txn_version) => {
+                    segment.create_build_events.push((build, txn_version));
+                }
+                ContractEvent::CancelBuildEvent(build, txn_version) => {
+                    segment.cancel_build_events.push((build, txn_version));
+                }
+                ContractEvent::SubmitBuildForReviewEvent(build, _) => {
+                    segment.submit_build_for_review_events.push(build);
+                }
+                ContractEvent::AcceptBuildEvent(bounty, build, txn_version) => {
+                    segment
+                        .accept_build_events
+                        .push((bounty, build, txn_version));
+                }
+                ContractEvent::ModuleUpgradeEvent(upgrade) => {
+                    segment.module_upgrades.push(upgrade);
+                }
+                ContractEvent::UnhandledEvent(event) => {
+                    segment.unhandled_events.push(event);
+                }
+            }
+        }
+        segment
+    }
+}
 
-        process_create_bounty_events(
-            self.pool.clone(),
-            per_table_chunk_sizes.clone(),
-            create_bounty_events,
-        )
-        .await?;
+/// Object addresses of the bounties and builds the event writes
+fn get_touched_obj_addrs(event: &ContractEvent) -> Vec<String> {
+    match event {
+        ContractEvent::CreateBountyEvent(bounty, _)
+        | ContractEvent::EndBountyEvent(bounty, _, _) => {
+            vec![bounty.bounty_obj_addr.clone()]
+        }
+        ContractEvent::CreateBuildEvent(build, _)
+        | ContractEvent::CancelBuildEvent(build, _)
+        | ContractEvent::SubmitBuildForReviewEvent(build, _) => vec![build.build_obj_addr.clone()],
+        ContractEvent::AcceptBuildEvent(bounty, build, _) => {
+            vec![bounty.bounty_obj_addr.clone(), build.build_obj_addr.clone()]
+        }
+        ContractEvent::ModuleUpgradeEvent(_) | ContractEvent::UnhandledEvent(_) => vec![],
+    }
+}
 
-        process_end_bounty_events(
-            self.pool.clone(),
-            per_table_chunk_sizes.clone(),
-            end_bounty_events,
-        )
-        .await?;
+/// Events come in (version, event index) order. Grouping them by type is only safe while no two
+/// of them write the same bounty or build, e.g. a bounty whose last winner is accepted then ended
+/// in the same batch must not be ended first. So a new segment starts at the first event writing
+/// an object the current segment already writes, and segments are written in order.
+fn split_into_ordered_segments(events: Vec<ContractEvent>) -> Vec<EventSegment> {
+    let mut segments = vec![];
+    let mut segment_events = vec![];
+    let mut segment_obj_addrs = AHashSet::new();
+    for event in events {
+        let obj_addrs = get_touched_obj_addrs(&event);
+        if obj_addrs
+            .iter()
+            .any(|obj_addr| segment_obj_addrs.contains(obj_addr))
+        {
+            segments.push(EventSegment::from_events(std::mem::take(
+                &mut segment_events,
+            )));
+            segment_obj_addrs.clear();
+        }
+        segment_obj_addrs.extend(obj_addrs);
+        segment_events.push(event);
+    }
+    if !segment_events.is_empty() {
+        segments.push(EventSegment::from_events(segment_events));
+    }
+    segments
+}
 
-        process_create_build_events(
-            self.pool.clone(),
-            per_table_chunk_sizes.clone(),
-            create_build_events,
-        )
-        .await?;
+/// One change per user for the whole batch, so chunking the events can't apply a user's delta
+/// more than once
+fn get_user_stat_changes(events: &[ContractEvent]) -> Vec<UserStat> {
+    reduce_user_stat_changes(events.iter().filter_map(|event| match event {
+        ContractEvent::CreateBountyEvent(bounty, _) => {
+            Some(get_create_bounty_user_stat_change(bounty))
+        }
+        ContractEvent::EndBountyEvent(bounty, payment_sent_back_to_creator, _) => Some(
+            get_end_bounty_user_stat_change(bounty, payment_sent_back_to_creator),
+        ),
+        ContractEvent::CreateBuildEvent(build, _) => Some(get_create_build_user_stat_change(build)),
+        ContractEvent::CancelBuildEvent(build, _) => Some(get_cancel_build_user_stat_change(build)),
+        ContractEvent::SubmitBuildForReviewEvent(build, _) => {
+            Some(get_submit_build_for_review_user_stat_change(build))
+        }
+        ContractEvent::AcceptBuildEvent(bounty, build, _) => {
+            Some(get_accept_build_user_stat_change(bounty, build))
+        }
+        ContractEvent::ModuleUpgradeEvent(_) | ContractEvent::UnhandledEvent(_) => None,
+    }))
+}
 
-        process_cancel_build_events(
-            self.pool.clone(),
-            per_table_chunk_sizes.clone(),
-            cancel_build_events,
-        )
-        .await?;
+/// One refund change per creator for the whole batch
+fn get_creator_refunds(events: &[ContractEvent]) -> Vec<CreatorStat> {
+    reduce_creator_refunds(events.iter().filter_map(|event| match event {
+        ContractEvent::EndBountyEvent(bounty, payment_sent_back_to_creator, _) => Some(
+            get_end_bounty_creator_stat_change(bounty, payment_sent_back_to_creator),
+        ),
+        _ => None,
+    }))
+}
 
-        process_submit_build_for_review_events(
-            self.pool.clone(),
-            per_table_chunk_sizes.clone(),
-            submit_build_for_review_events,
-        )
-        .await?;
+/// Returns the deduplicated addresses of all bounties affected by the events, the deduplicated UTC
+/// days the events happened on, and the latest on-chain timestamp among the events.
+fn get_touched_bounties_and_days(events: &[ContractEvent]) -> (Vec<String>, Vec<i64>, i64) {
+    let mut bounty_obj_addrs = AHashSet::new();
+    let mut day_timestamps = AHashSet::new();
+    let mut latest_timestamp = 0;
+    for event in events {
+        let (bounty_obj_addr, timestamp) = match event {
+            ContractEvent::CreateBountyEvent(bounty, _)
+            | ContractEvent::AcceptBuildEvent(bounty, _, _) => {
+                (&bounty.bounty_obj_addr, bounty.last_update_timestamp)
+            }
+            // Ending a bounty sets the end timestamp to now without touching last update timestamp
+            ContractEvent::EndBountyEvent(bounty, _, _) => {
+                (&bounty.bounty_obj_addr, bounty.end_timestamp)
+            }
+            ContractEvent::CreateBuildEvent(build, _)
+            | ContractEvent::CancelBuildEvent(build, _)
+            | ContractEvent::SubmitBuildForReviewEvent(build, _) => {
+                (&build.bounty_obj_addr, build.last_update_timestamp)
+            }
+            ContractEvent::ModuleUpgradeEvent(_) | ContractEvent::UnhandledEvent(_) => continue,
+        };
+        bounty_obj_addrs.insert(bounty_obj_addr.clone());
+        day_timestamps.insert(get_day_timestamp(timestamp));
+        latest_timestamp = latest_timestamp.max(timestamp);
+    }
+    (
+        bounty_obj_addrs.into_iter().collect(),
+        day_timestamps.into_iter().collect(),
+        latest_timestamp,
+    )
+}
 
-        process_accept_build_events(
-            self.pool.clone(),
-            per_table_chunk_sizes.clone(),
-            accept_build_events,
-        )
-        .await?;
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        processors::events::events_extractor::test_events::new_build_event,
+        utils::database_utils::get_config_table_chunk_size,
+    };
+    use ahash::AHashMap;
 
-        Ok(Some(events))
+    #[test]
+    fn test_split_into_ordered_segments() {
+        let segments = split_into_ordered_segments(vec![
+            new_build_event("CreateBuildEvent", "0xb1", 1, 1729000000, 1),
+            new_build_event("CreateBuildEvent", "0xb2", 1, 1729000000, 1),
+            new_build_event("SubmitBuildForReviewEvent", "0xb3", 2, 1729000100, 2),
+            // Same build as the previous event, it must be accepted after the submit
+            new_build_event("AcceptBuildEvent", "0xb3", 4, 1729000200, 3),
+            new_build_event("CancelBuildEvent", "0xb1", 3, 1729000200, 4),
+        ]);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].create_build_events.len(), 2);
+        assert_eq!(segments[0].submit_build_for_review_events.len(), 1);
+        assert!(segments[0].accept_build_events.is_empty());
+        assert_eq!(segments[1].accept_build_events.len(), 1);
+        assert_eq!(segments[1].cancel_build_events.len(), 1);
+
+        assert!(split_into_ordered_segments(vec![]).is_empty());
+    }
+
+    #[test]
+    fn test_user_stat_changes_with_multiple_chunks() {
+        let mut per_table_chunk_sizes = AHashMap::new();
+        per_table_chunk_sizes.insert("builds".to_string(), 1);
+        per_table_chunk_sizes.insert("user_stats".to_string(), 1);
+        let chunk_size = get_config_table_chunk_size::<Build>("builds", &per_table_chunk_sizes);
+        let events = vec![
+            new_build_event("CreateBuildEvent", "0xb1", 1, 1729000000, 1),
+            new_build_event("CreateBuildEvent", "0xb2", 1, 1729000000, 2),
+            new_build_event("CreateBuildEvent", "0xb3", 1, 1729000000, 3),
+            new_build_event("SubmitBuildForReviewEvent", "0xb2", 2, 1729000100, 4),
+        ];
+        let segments = split_into_ordered_segments(events.clone());
+        assert_eq!(
+            segments[0].create_build_events.chunks(chunk_size).count(),
+            3
+        );
+
+        // Used to add the stats of all 3 builds once per chunk
+        let changes = get_user_stat_changes(&events);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].build_created, 3);
+        assert_eq!(changes[0].build_submitted_for_review, 1);
+        assert_eq!(changes[0].total_points, 3);
+
+        let chunked_changes =
+            reduce_user_stat_changes(events.chunks(chunk_size).flat_map(get_user_stat_changes));
+        assert_eq!(chunked_changes.len(), 1);
+        assert_eq!(chunked_changes[0].build_created, 3);
+        assert_eq!(chunked_changes[0].total_points, 3);
     }
 }
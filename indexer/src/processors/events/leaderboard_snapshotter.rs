@@ -0,0 +1,158 @@
+use anyhow::Result;
+use aptos_indexer_processor_sdk::{
+    traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
+    types::transaction_context::TransactionContext,
+    utils::errors::ProcessorError,
+};
+use async_trait::async_trait;
+use diesel::{
+    sql_query,
+    sql_types::{Int8, Text},
+    QueryResult,
+};
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+
+use super::events_extractor::ContractEvent;
+use crate::{
+    config::indexer_processor_config::LeaderboardConfig,
+    db_models::leaderboard_snapshot::{LeaderboardSnapshot, LEADERBOARD_SEASONS},
+    utils::{database_connection::get_db_connection, database_utils::ArcDbPool},
+};
+
+/// LeaderboardSnapshotter is a step that periodically saves the rank of every user in each season,
+/// it must run after EventsStorer so the snapshot includes the batch.
+pub struct LeaderboardSnapshotter
+where
+    Self: Sized + Send + 'static,
+{
+    pool: ArcDbPool,
+    config: LeaderboardConfig,
+    // Version and timestamp of the latest snapshot, loaded from the DB when the first batch arrives
+    last_snapshot: Option<Option<(i64, i64)>>,
+}
+
+impl AsyncStep for LeaderboardSnapshotter {}
+
+impl NamedStep for LeaderboardSnapshotter {
+    fn name(&self) -> String {
+        "LeaderboardSnapshotter".to_string()
+    }
+}
+
+impl LeaderboardSnapshotter {
+    pub fn new(pool: ArcDbPool, config: LeaderboardConfig) -> Self {
+        Self {
+            pool,
+            config,
+            last_snapshot: None,
+        }
+    }
+
+    fn should_snapshot(
+        &self,
+        last_snapshot: Option<(i64, i64)>,
+        end_version: i64,
+        end_timestamp: i64,
+    ) -> bool {
+        let time_interval = self.config.snapshot_interval_in_seconds;
+        let version_interval = self.config.snapshot_version_interval;
+        if time_interval.is_none() && version_interval.is_none() {
+            return false;
+        }
+        let (last_version, last_timestamp) = match last_snapshot {
+            Some(last_snapshot) => last_snapshot,
+            None => return true,
+        };
+        let time_interval_passed =
+            time_interval.is_some_and(|interval| end_timestamp - last_timestamp >= interval as i64);
+        let version_checkpoint_crossed = version_interval.is_some_and(|interval| {
+            let interval = interval as i64;
+            last_version / interval < end_version / interval
+        });
+        time_interval_passed || version_checkpoint_crossed
+    }
+}
+
+/// Rank users by points, ties are broken by who joined first, then by address so ranks are stable.
+fn get_insert_snapshot_sql(points_column: &str) -> String {
+    format!(
+        "
+INSERT INTO leaderboard_snapshots (season, snapshot_version, snapshot_timestamp, user_addr, rank, points)
+SELECT
+    $1,
+    $2,
+    $3,
+    user_addr,
+    ROW_NUMBER() OVER (ORDER BY {points_column} DESC, create_timestamp ASC, user_addr ASC),
+    {points_column}
+FROM user_stats
+ON CONFLICT (season, snapshot_version, user_addr) DO NOTHING
+"
+    )
+}
+
+async fn execute_leaderboard_snapshot_sql(
+    conn: &mut AsyncPgConnection,
+    snapshot_version: i64,
+    snapshot_timestamp: i64,
+) -> QueryResult<()> {
+    conn.transaction(|conn| {
+        Box::pin(async move {
+            for (season, points_column) in LEADERBOARD_SEASONS {
+                sql_query(get_insert_snapshot_sql(points_column))
+                    .bind::<Text, _>(season)
+                    .bind::<Int8, _>(snapshot_version)
+                    .bind::<Int8, _>(snapshot_timestamp)
+                    .execute(conn)
+                    .await?;
+            }
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[async_trait]
+impl Processable for LeaderboardSnapshotter {
+    type Input = ContractEvent;
+    type Output = ContractEvent;
+    type RunType = AsyncRunType;
+
+    async fn process(
+        &mut self,
+        events: TransactionContext<ContractEvent>,
+    ) -> Result<Option<TransactionContext<ContractEvent>>, ProcessorError> {
+        let conn = &mut get_db_connection(&self.pool).await?;
+        let last_snapshot = match self.last_snapshot {
+            Some(last_snapshot) => last_snapshot,
+            None => LeaderboardSnapshot::get_latest_version_and_timestamp(conn)
+                .await
+                .map_err(|e| ProcessorError::DBStoreError {
+                    message: format!("Failed to get latest leaderboard snapshot: {}", e),
+                })?,
+        };
+
+        let end_version = events.end_version as i64;
+        let end_timestamp = events
+            .end_transaction_timestamp
+            .as_ref()
+            .map(|t| t.seconds)
+            .unwrap_or_default();
+        if self.should_snapshot(last_snapshot, end_version, end_timestamp) {
+            execute_leaderboard_snapshot_sql(conn, end_version, end_timestamp)
+                .await
+                .map_err(|e| {
+                    tracing::warn!("Error running query: {:?}", e);
+                    ProcessorError::DBStoreError {
+                        message: format!("Failed to save leaderboard snapshot: {}", e),
+                    }
+                })?;
+            tracing::info!(snapshot_version = end_version, "Saved leaderboard snapshot");
+            self.last_snapshot = Some(Some((end_version, end_timestamp)));
+        } else {
+            self.last_snapshot = Some(last_snapshot);
+        }
+
+        Ok(Some(events))
+    }
+}
@@ -1,4 +1,8 @@
+pub mod event_decoders;
 pub mod events_extractor;
 pub mod events_processor;
 pub mod events_storer;
+pub mod leaderboard_snapshotter;
+pub mod module_upgrade_tracker;
+pub mod storage;
 pub mod storers;
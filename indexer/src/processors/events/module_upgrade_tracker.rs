@@ -0,0 +1,176 @@
+use aptos_indexer_processor_sdk::{
+    aptos_protos::transaction::v1::{write_set_change::Change, Transaction},
+    utils::convert::standardize_address,
+};
+use std::collections::BTreeMap;
+
+use crate::{
+    client::payloads::BOUNTY_APP_MODULE_NAME,
+    db_models::{
+        module_upgrade::ModuleUpgrade,
+        on_chain::{to_i64, PackageRegistryOnChain},
+    },
+};
+
+const PACKAGE_REGISTRY_TYPE: &str = "0x1::code::PackageRegistry";
+
+/// Keeps track of which upgrade of the contract package was live at each transaction version, so
+/// events are decoded with the decoder of the code that emitted them.
+pub struct ModuleUpgradeTracker {
+    contract_address: String,
+    // Key is the version of the publish or upgrade transaction, value is its upgrade number
+    upgrade_numbers: BTreeMap<i64, i64>,
+}
+
+impl ModuleUpgradeTracker {
+    pub fn new(contract_address: &str, known_upgrades: &[ModuleUpgrade]) -> Self {
+        Self {
+            contract_address: standardize_address(contract_address),
+            upgrade_numbers: known_upgrades
+                .iter()
+                .map(|upgrade| (upgrade.txn_version, upgrade.upgrade_number))
+                .collect(),
+        }
+    }
+
+    /// Publishing or upgrading a package rewrites the PackageRegistry resource at its address,
+    /// we read the upgrade number of the package containing bounty_app from it.
+    pub fn detect_upgrade(&self, txn: &Transaction) -> Option<ModuleUpgrade> {
+        let info = txn.info.as_ref()?;
+        info.changes.iter().find_map(|change| {
+            let resource = match change.change.as_ref()? {
+                Change::WriteResource(resource) => resource,
+                _ => return None,
+            };
+            if resource.type_str != PACKAGE_REGISTRY_TYPE
+                || standardize_address(&resource.address) != self.contract_address
+            {
+                return None;
+            }
+            let registry: PackageRegistryOnChain = match serde_json::from_str(&resource.data) {
+                Ok(registry) => registry,
+                Err(e) => {
+                    tracing::warn!(
+                        transaction_version = txn.version,
+                        "Failed to parse PackageRegistry: {}",
+                        e
+                    );
+                    return None;
+                }
+            };
+            let package = registry.packages.into_iter().find(|package| {
+                package
+                    .modules
+                    .iter()
+                    .any(|module| module.name == BOUNTY_APP_MODULE_NAME)
+            })?;
+            Some(ModuleUpgrade {
+                txn_version: txn.version as i64,
+                module_addr: self.contract_address.clone(),
+                package_name: package.name,
+                upgrade_number: to_i64(package.upgrade_number),
+                module_names: package
+                    .modules
+                    .into_iter()
+                    .map(|module| Some(module.name))
+                    .collect(),
+                timestamp: txn
+                    .timestamp
+                    .as_ref()
+                    .map(|t| t.seconds)
+                    .unwrap_or_default(),
+            })
+        })
+    }
+
+    pub fn record_upgrade(&mut self, upgrade: &ModuleUpgrade) {
+        self.upgrade_numbers
+            .insert(upgrade.txn_version, upgrade.upgrade_number);
+    }
+
+    /// Events of the upgrade transaction itself are emitted by the new code.
+    /// Before the first known publish we assume the initial version.
+    pub fn get_upgrade_number_at(&self, txn_version: i64) -> i64 {
+        self.upgrade_numbers
+            .range(..=txn_version)
+            .next_back()
+            .map(|(_, upgrade_number)| *upgrade_number)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aptos_indexer_processor_sdk::aptos_protos::{
+        transaction::v1::{TransactionInfo, WriteResource, WriteSetChange},
+        util::timestamp::Timestamp,
+    };
+
+    const CONTRACT_ADDR: &str =
+        "0x81453e030e20e46297aea5bc6c22343825f2f7d9cbc9131979d779491586f187";
+
+    fn new_publish_txn(version: u64, address: &str, upgrade_number: u64) -> Transaction {
+        let data = format!(
+            r#"{{"packages": [
+                {{"name": "Other", "upgrade_number": "9", "modules": [{{"name": "other"}}]}},
+                {{"name": "BountyApp", "upgrade_number": "{}", "upgrade_policy": {{"policy": 1}}, "modules": [{{"name": "bounty_app"}}]}}
+            ]}}"#,
+            upgrade_number
+        );
+        Transaction {
+            version,
+            timestamp: Some(Timestamp {
+                seconds: 1729000000,
+                nanos: 0,
+            }),
+            info: Some(TransactionInfo {
+                changes: vec![WriteSetChange {
+                    change: Some(Change::WriteResource(WriteResource {
+                        address: address.to_string(),
+                        type_str: PACKAGE_REGISTRY_TYPE.to_string(),
+                        data,
+                        ..Default::default()
+                    })),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_detect_upgrade() {
+        let tracker = ModuleUpgradeTracker::new(CONTRACT_ADDR, &[]);
+        let upgrade = tracker
+            .detect_upgrade(&new_publish_txn(100, CONTRACT_ADDR, 2))
+            .unwrap();
+        assert_eq!(upgrade.txn_version, 100);
+        assert_eq!(upgrade.package_name, "BountyApp");
+        assert_eq!(upgrade.upgrade_number, 2);
+        assert_eq!(upgrade.module_names, vec![Some("bounty_app".to_string())]);
+        assert_eq!(upgrade.timestamp, 1729000000);
+
+        // Another account publishing the same package
+        assert!(tracker
+            .detect_upgrade(&new_publish_txn(100, "0x999", 2))
+            .is_none());
+        assert!(tracker.detect_upgrade(&Transaction::default()).is_none());
+    }
+
+    #[test]
+    fn test_get_upgrade_number_at() {
+        let mut tracker = ModuleUpgradeTracker::new(CONTRACT_ADDR, &[]);
+        for (txn_version, upgrade_number) in [(10, 0), (50, 1)] {
+            let upgrade = tracker
+                .detect_upgrade(&new_publish_txn(txn_version, CONTRACT_ADDR, upgrade_number))
+                .unwrap();
+            tracker.record_upgrade(&upgrade);
+        }
+        assert_eq!(tracker.get_upgrade_number_at(5), 0);
+        assert_eq!(tracker.get_upgrade_number_at(49), 0);
+        assert_eq!(tracker.get_upgrade_number_at(50), 1);
+        assert_eq!(tracker.get_upgrade_number_at(1000), 1);
+    }
+}
@@ -0,0 +1,521 @@
+use ahash::AHashMap;
+use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use std::{
+    collections::BTreeMap,
+    sync::{Mutex, MutexGuard},
+};
+
+use super::EventsStorage;
+use crate::{
+    db_models::{
+        bounty::Bounty, build::Build, creator_stat::CreatorStat, module_upgrade::ModuleUpgrade,
+        points_ledger_entry::PointsLedgerEntry, processor_status::ProcessorStatus,
+        unhandled_event::UnhandledEvent, user_stat::UserStat,
+    },
+    processors::events::{
+        events_storer::{BatchWrites, EventSegment},
+        storers::{
+            accept_build_event_storer::get_accept_build_points_ledger_entry,
+            cancel_build_event_storer::get_cancel_build_points_ledger_entry,
+            create_bounty_event_storer::get_create_bounty_points_ledger_entry,
+            create_build_event_storer::get_create_build_points_ledger_entry,
+            creator_stats_storer::reduce_creator_refunds,
+            user_stats_storer::reduce_user_stat_changes,
+        },
+    },
+};
+
+// Primary key of points_ledger
+type PointsLedgerKey = (i64, String, String, String);
+
+#[derive(Default)]
+struct Tables {
+    bounties: AHashMap<String, Bounty>,
+    builds: AHashMap<String, Build>,
+    user_stats: AHashMap<String, UserStat>,
+    creator_refunds: AHashMap<String, CreatorStat>,
+    points_ledger: BTreeMap<PointsLedgerKey, PointsLedgerEntry>,
+    module_upgrades: BTreeMap<i64, ModuleUpgrade>,
+    unhandled_events: BTreeMap<(i64, i64), UnhandledEvent>,
+    last_success_version: Option<i64>,
+}
+
+fn get_bounty_update_order(bounty: &Bounty) -> (i64, i64, i64) {
+    (
+        bounty.last_update_timestamp,
+        bounty.last_update_txn_version,
+        bounty.last_update_event_idx,
+    )
+}
+
+fn get_build_update_order(build: &Build) -> (i64, i64, i64) {
+    (
+        build.last_update_timestamp,
+        build.last_update_txn_version,
+        build.last_update_event_idx,
+    )
+}
+
+impl Tables {
+    /// Insert a new bounty, or update the stored one if the update comes after it, like the
+    /// upserts of the storers
+    fn upsert_bounty(&mut self, bounty: Bounty, update: impl FnOnce(&mut Bounty, Bounty)) {
+        match self.bounties.get_mut(&bounty.bounty_obj_addr) {
+            Some(stored) if get_bounty_update_order(stored) < get_bounty_update_order(&bounty) => {
+                update(stored, bounty);
+            }
+            Some(_) => {}
+            None => {
+                self.bounties.insert(bounty.bounty_obj_addr.clone(), bounty);
+            }
+        }
+    }
+
+    fn upsert_build(&mut self, build: Build, update: impl FnOnce(&mut Build, Build)) {
+        match self.builds.get_mut(&build.build_obj_addr) {
+            Some(stored) if get_build_update_order(stored) < get_build_update_order(&build) => {
+                update(stored, build);
+            }
+            Some(_) => {}
+            None => {
+                self.builds.insert(build.build_obj_addr.clone(), build);
+            }
+        }
+    }
+
+    fn insert_points_ledger_entry(&mut self, entry: PointsLedgerEntry) {
+        if entry.points_delta == 0 {
+            return;
+        }
+        let key = (
+            entry.txn_version,
+            entry.event_type.clone(),
+            entry.object_addr.clone(),
+            entry.user_addr.clone(),
+        );
+        self.points_ledger.entry(key).or_insert(entry);
+    }
+
+    fn insert_module_upgrades(&mut self, upgrades: Vec<ModuleUpgrade>) {
+        for upgrade in upgrades {
+            self.module_upgrades
+                .entry(upgrade.txn_version)
+                .or_insert(upgrade);
+        }
+    }
+
+    fn insert_unhandled_events(&mut self, events: Vec<UnhandledEvent>) {
+        for event in events {
+            self.unhandled_events
+                .entry((event.txn_version, event.event_idx))
+                .or_insert(event);
+        }
+    }
+
+    fn create_bounties(&mut self, bounties: Vec<(Bounty, i64)>) {
+        for (bounty, txn_version) in bounties {
+            self.insert_points_ledger_entry(get_create_bounty_points_ledger_entry(
+                &bounty,
+                txn_version,
+            ));
+            self.bounties
+                .entry(bounty.bounty_obj_addr.clone())
+                .or_insert(bounty);
+        }
+    }
+
+    fn end_bounties(&mut self, bounties: Vec<(Bounty, BigDecimal)>) {
+        for (bounty, _) in bounties {
+            self.upsert_bounty(bounty, |stored, bounty| {
+                stored.end_timestamp = bounty.end_timestamp;
+                stored.last_update_timestamp = bounty.last_update_timestamp;
+                stored.last_update_event_idx = bounty.last_update_event_idx;
+                stored.last_update_txn_version = bounty.last_update_txn_version;
+                stored.refunded_amount = bounty.refunded_amount;
+            });
+        }
+    }
+
+    fn create_builds(&mut self, builds: Vec<(Build, i64)>) {
+        for (build, txn_version) in builds {
+            self.insert_points_ledger_entry(get_create_build_points_ledger_entry(
+                &build,
+                txn_version,
+            ));
+            self.builds
+                .entry(build.build_obj_addr.clone())
+                .or_insert(build);
+        }
+    }
+
+    fn cancel_builds(&mut self, builds: Vec<(Build, i64)>) {
+        for (build, txn_version) in builds {
+            self.insert_points_ledger_entry(get_cancel_build_points_ledger_entry(
+                &build,
+                txn_version,
+            ));
+            self.upsert_build(build, |stored, build| {
+                stored.last_update_timestamp = build.last_update_timestamp;
+                stored.build_status = build.build_status;
+                stored.last_update_event_idx = build.last_update_event_idx;
+                stored.last_update_txn_version = build.last_update_txn_version;
+            });
+        }
+    }
+
+    fn submit_builds_for_review(&mut self, builds: Vec<Build>) {
+        for build in builds {
+            self.upsert_build(build, |stored, build| {
+                stored.last_update_timestamp = build.last_update_timestamp;
+                stored.proof_link = build.proof_link;
+                stored.build_status = build.build_status;
+                stored.last_update_event_idx = build.last_update_event_idx;
+                stored.last_update_txn_version = build.last_update_txn_version;
+                stored.submit_timestamp = build.submit_timestamp;
+                stored.proof_link_kind = build.proof_link_kind;
+                stored.proof_link_host = build.proof_link_host;
+                stored.proof_link_owner = build.proof_link_owner;
+                stored.proof_link_repo = build.proof_link_repo;
+                stored.proof_link_pr_number = build.proof_link_pr_number;
+                stored.proof_link_normalized = build.proof_link_normalized;
+            });
+        }
+    }
+
+    fn accept_builds(&mut self, accepted: Vec<(Bounty, Build, i64)>) {
+        for (bounty, build, txn_version) in accepted {
+            self.insert_points_ledger_entry(get_accept_build_points_ledger_entry(
+                &build,
+                txn_version,
+            ));
+            self.upsert_bounty(bounty, |stored, bounty| {
+                stored.last_update_timestamp = bounty.last_update_timestamp;
+                stored.winner_count = bounty.winner_count;
+                stored.last_update_event_idx = bounty.last_update_event_idx;
+                stored.last_update_txn_version = bounty.last_update_txn_version;
+            });
+            self.upsert_build(build, |stored, build| {
+                stored.payment_amount = build.payment_amount;
+                stored.last_update_timestamp = build.last_update_timestamp;
+                stored.build_status = build.build_status;
+                stored.last_update_event_idx = build.last_update_event_idx;
+                stored.last_update_txn_version = build.last_update_txn_version;
+            });
+        }
+    }
+
+    fn write_segment(&mut self, segment: EventSegment) {
+        self.insert_module_upgrades(segment.module_upgrades);
+        self.insert_unhandled_events(segment.unhandled_events);
+        self.create_bounties(segment.create_bounty_events);
+        self.end_bounties(segment.end_bounty_events);
+        self.create_builds(segment.create_build_events);
+        self.cancel_builds(segment.cancel_build_events);
+        self.submit_builds_for_review(segment.submit_build_for_review_events);
+        self.accept_builds(segment.accept_build_events);
+    }
+
+    fn apply_user_stat_changes(&mut self, changes: Vec<UserStat>) {
+        // Adding a change to the stored stats is the same as reducing both
+        for change in changes {
+            let stat = match self.user_stats.remove(&change.user_addr) {
+                Some(stored) => reduce_user_stat_changes([stored, change]).remove(0),
+                None => change,
+            };
+            self.user_stats.insert(stat.user_addr.clone(), stat);
+        }
+    }
+
+    fn apply_creator_refunds(&mut self, refunds: Vec<CreatorStat>) {
+        for refund in refunds {
+            let stat = match self.creator_refunds.remove(&refund.creator_addr) {
+                Some(stored) => reduce_creator_refunds([stored, refund]).remove(0),
+                None => refund,
+            };
+            self.creator_refunds.insert(stat.creator_addr.clone(), stat);
+        }
+    }
+}
+
+/// Keeps the rows the storers write in memory, so the event handling of the EventsStorer can be
+/// tested without a database. Creator stats only get their refund columns, daily metrics and
+/// bounty search documents are not kept: Postgres derives them with SQL.
+#[derive(Default)]
+pub struct InMemoryEventsStorage {
+    tables: Mutex<Tables>,
+}
+
+impl InMemoryEventsStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn tables(&self) -> MutexGuard<'_, Tables> {
+        self.tables.lock().expect("Tables lock is poisoned")
+    }
+
+    pub fn get_bounty(&self, bounty_obj_addr: &str) -> Option<Bounty> {
+        self.tables().bounties.get(bounty_obj_addr).cloned()
+    }
+
+    pub fn get_build(&self, build_obj_addr: &str) -> Option<Build> {
+        self.tables().builds.get(build_obj_addr).cloned()
+    }
+
+    pub fn get_user_stat(&self, user_addr: &str) -> Option<UserStat> {
+        self.tables().user_stats.get(user_addr).cloned()
+    }
+
+    pub fn get_creator_refunds(&self, creator_addr: &str) -> Option<CreatorStat> {
+        self.tables().creator_refunds.get(creator_addr).cloned()
+    }
+
+    /// Entries in primary key order
+    pub fn get_points_ledger(&self) -> Vec<PointsLedgerEntry> {
+        self.tables().points_ledger.values().cloned().collect()
+    }
+
+    pub fn get_module_upgrades(&self) -> Vec<ModuleUpgrade> {
+        self.tables().module_upgrades.values().cloned().collect()
+    }
+
+    pub fn get_unhandled_events(&self) -> Vec<UnhandledEvent> {
+        self.tables().unhandled_events.values().cloned().collect()
+    }
+
+    /// Only moved by write_batch_atomically
+    pub fn get_last_success_version(&self) -> Option<i64> {
+        self.tables().last_success_version
+    }
+}
+
+#[async_trait]
+impl EventsStorage for InMemoryEventsStorage {
+    async fn insert_module_upgrades(
+        &self,
+        upgrades: Vec<ModuleUpgrade>,
+    ) -> Result<(), ProcessorError> {
+        self.tables().insert_module_upgrades(upgrades);
+        Ok(())
+    }
+
+    async fn insert_unhandled_events(
+        &self,
+        events: Vec<UnhandledEvent>,
+    ) -> Result<(), ProcessorError> {
+        self.tables().insert_unhandled_events(events);
+        Ok(())
+    }
+
+    async fn create_bounties(&self, bounties: Vec<(Bounty, i64)>) -> Result<(), ProcessorError> {
+        self.tables().create_bounties(bounties);
+        Ok(())
+    }
+
+    async fn end_bounties(
+        &self,
+        bounties: Vec<(Bounty, BigDecimal)>,
+    ) -> Result<(), ProcessorError> {
+        self.tables().end_bounties(bounties);
+        Ok(())
+    }
+
+    async fn create_builds(&self, builds: Vec<(Build, i64)>) -> Result<(), ProcessorError> {
+        self.tables().create_builds(builds);
+        Ok(())
+    }
+
+    async fn cancel_builds(&self, builds: Vec<(Build, i64)>) -> Result<(), ProcessorError> {
+        self.tables().cancel_builds(builds);
+        Ok(())
+    }
+
+    async fn submit_builds_for_review(&self, builds: Vec<Build>) -> Result<(), ProcessorError> {
+        self.tables().submit_builds_for_review(builds);
+        Ok(())
+    }
+
+    async fn accept_builds(
+        &self,
+        accepted: Vec<(Bounty, Build, i64)>,
+    ) -> Result<(), ProcessorError> {
+        self.tables().accept_builds(accepted);
+        Ok(())
+    }
+
+    async fn apply_user_stat_changes(&self, changes: Vec<UserStat>) -> Result<(), ProcessorError> {
+        self.tables().apply_user_stat_changes(changes);
+        Ok(())
+    }
+
+    async fn apply_creator_refunds(&self, refunds: Vec<CreatorStat>) -> Result<(), ProcessorError> {
+        self.tables().apply_creator_refunds(refunds);
+        Ok(())
+    }
+
+    async fn refresh_aggregates(
+        &self,
+        _touched_bounty_obj_addrs: Vec<String>,
+        _touched_day_timestamps: Vec<i64>,
+        _latest_timestamp: i64,
+    ) -> Result<(), ProcessorError> {
+        // Nothing derived is kept, see InMemoryEventsStorage
+        Ok(())
+    }
+
+    async fn write_batch_atomically(
+        &self,
+        writes: BatchWrites,
+        checkpoint: ProcessorStatus,
+    ) -> Result<(), ProcessorError> {
+        // Writes can't fail, holding the lock for the whole batch is enough to make it atomic
+        let mut tables = self.tables();
+        for segment in writes.segments {
+            tables.write_segment(segment);
+        }
+        tables.apply_user_stat_changes(writes.user_stat_changes);
+        tables.apply_creator_refunds(writes.creator_refunds);
+        tables.last_success_version = tables
+            .last_success_version
+            .max(Some(checkpoint.last_success_version));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::processors::events::{
+        events_extractor::{test_events::new_build_event, ContractEvent},
+        events_storer::EventsStorer,
+    };
+    use aptos_indexer_processor_sdk::{
+        traits::Processable, types::transaction_context::TransactionContext,
+    };
+    use std::sync::Arc;
+
+    async fn process(
+        storage: Arc<InMemoryEventsStorage>,
+        commit_batch_atomically: bool,
+        events: Vec<ContractEvent>,
+        end_version: u64,
+    ) {
+        let mut storer =
+            EventsStorer::with_storage(storage, "test".to_string(), commit_batch_atomically);
+        storer
+            .process(TransactionContext {
+                data: events,
+                start_version: 0,
+                end_version,
+                start_transaction_timestamp: None,
+                end_transaction_timestamp: None,
+                total_size_in_bytes: 0,
+            })
+            .await
+            .unwrap();
+    }
+
+    fn lifecycle_events() -> Vec<ContractEvent> {
+        vec![
+            new_build_event("CreateBuildEvent", "0xb1", 1, 1729000000, 1),
+            new_build_event("CreateBuildEvent", "0xb2", 1, 1729000000, 1),
+            new_build_event("SubmitBuildForReviewEvent", "0xb1", 2, 1729000100, 2),
+            new_build_event("CancelBuildEvent", "0xb2", 3, 1729000200, 3),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_atomic_and_parallel_writes_match() {
+        let atomic = Arc::new(InMemoryEventsStorage::new());
+        process(atomic.clone(), true, lifecycle_events(), 3).await;
+        let parallel = Arc::new(InMemoryEventsStorage::new());
+        process(parallel.clone(), false, lifecycle_events(), 3).await;
+
+        for storage in [&atomic, &parallel] {
+            assert_eq!(storage.get_build("0xb1").unwrap().build_status, 2);
+            assert_eq!(storage.get_build("0xb2").unwrap().build_status, 3);
+            let stat = storage.get_user_stat("0x1").unwrap();
+            assert_eq!(stat.build_created, 2);
+            assert_eq!(stat.build_submitted_for_review, 1);
+            // Cancelling gives no points, so only the creations are in the ledger
+            assert_eq!(storage.get_points_ledger().len(), 2);
+        }
+        assert_eq!(atomic.get_user_stat("0x1"), parallel.get_user_stat("0x1"));
+        assert_eq!(atomic.get_last_success_version(), Some(3));
+        assert_eq!(parallel.get_last_success_version(), None);
+    }
+
+    #[tokio::test]
+    async fn test_older_updates_are_skipped() {
+        let storage = Arc::new(InMemoryEventsStorage::new());
+        process(storage.clone(), true, lifecycle_events(), 3).await;
+        // Replayed creation and an update older than the stored one
+        process(
+            storage.clone(),
+            true,
+            vec![
+                new_build_event("CreateBuildEvent", "0xb1", 1, 1729000000, 1),
+                new_build_event("SubmitBuildForReviewEvent", "0xb2", 2, 1729000100, 2),
+            ],
+            2,
+        )
+        .await;
+
+        assert_eq!(storage.get_build("0xb1").unwrap().build_status, 2);
+        assert_eq!(storage.get_build("0xb2").unwrap().build_status, 3);
+        assert_eq!(storage.get_points_ledger().len(), 2);
+        // The checkpoint never moves back
+        assert_eq!(storage.get_last_success_version(), Some(3));
+    }
+
+    #[tokio::test]
+    async fn test_same_second_updates_follow_version_order() {
+        let storage = Arc::new(InMemoryEventsStorage::new());
+        process(
+            storage.clone(),
+            true,
+            vec![
+                new_build_event("CreateBuildEvent", "0xb1", 1, 1729000000, 1),
+                new_build_event("SubmitBuildForReviewEvent", "0xb1", 2, 1729000100, 3),
+            ],
+            3,
+        )
+        .await;
+
+        // Same second as the stored submit but an earlier transaction, e.g. a replayed batch
+        process(
+            storage.clone(),
+            true,
+            vec![new_build_event(
+                "AcceptBuildEvent",
+                "0xb1",
+                4,
+                1729000100,
+                2,
+            )],
+            2,
+        )
+        .await;
+        assert_eq!(storage.get_build("0xb1").unwrap().build_status, 2);
+
+        // Same second, later transaction
+        process(
+            storage.clone(),
+            true,
+            vec![new_build_event(
+                "AcceptBuildEvent",
+                "0xb1",
+                4,
+                1729000100,
+                4,
+            )],
+            4,
+        )
+        .await;
+        let build = storage.get_build("0xb1").unwrap();
+        assert_eq!(build.build_status, 4);
+        assert_eq!(build.last_update_txn_version, 4);
+    }
+}
@@ -0,0 +1,69 @@
+pub mod in_memory_events_storage;
+pub mod postgres_events_storage;
+
+use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+
+use super::events_storer::BatchWrites;
+use crate::db_models::{
+    bounty::Bounty, build::Build, creator_stat::CreatorStat, module_upgrade::ModuleUpgrade,
+    processor_status::ProcessorStatus, unhandled_event::UnhandledEvent, user_stat::UserStat,
+};
+
+/// Writes the EventsStorer makes, one per event type plus the per batch aggregates. The
+/// EventsStorer decides what to write and in which order, an implementation only applies it.
+/// Bounty and build updates must be skipped unless they come after the stored row in
+/// (last_update_timestamp, last_update_txn_version, last_update_event_idx) order, and inserts of
+/// rows that already exist are ignored, so a batch can be written again after a restart.
+#[async_trait]
+pub trait EventsStorage: Send + Sync + 'static {
+    async fn insert_module_upgrades(
+        &self,
+        upgrades: Vec<ModuleUpgrade>,
+    ) -> Result<(), ProcessorError>;
+
+    async fn insert_unhandled_events(
+        &self,
+        events: Vec<UnhandledEvent>,
+    ) -> Result<(), ProcessorError>;
+
+    /// Bounties with the version of the transaction that created them
+    async fn create_bounties(&self, bounties: Vec<(Bounty, i64)>) -> Result<(), ProcessorError>;
+
+    /// Bounties with the payment sent back to their creator
+    async fn end_bounties(&self, bounties: Vec<(Bounty, BigDecimal)>)
+        -> Result<(), ProcessorError>;
+
+    async fn create_builds(&self, builds: Vec<(Build, i64)>) -> Result<(), ProcessorError>;
+
+    async fn cancel_builds(&self, builds: Vec<(Build, i64)>) -> Result<(), ProcessorError>;
+
+    async fn submit_builds_for_review(&self, builds: Vec<Build>) -> Result<(), ProcessorError>;
+
+    async fn accept_builds(
+        &self,
+        accepted: Vec<(Bounty, Build, i64)>,
+    ) -> Result<(), ProcessorError>;
+
+    /// Add the reduced changes of a whole batch, see reduce_user_stat_changes
+    async fn apply_user_stat_changes(&self, changes: Vec<UserStat>) -> Result<(), ProcessorError>;
+
+    /// Add the reduced refunds of a whole batch, see reduce_creator_refunds
+    async fn apply_creator_refunds(&self, refunds: Vec<CreatorStat>) -> Result<(), ProcessorError>;
+
+    /// Recompute the stats derived from bounties and builds, after every other write of a batch
+    async fn refresh_aggregates(
+        &self,
+        touched_bounty_obj_addrs: Vec<String>,
+        touched_day_timestamps: Vec<i64>,
+        latest_timestamp: i64,
+    ) -> Result<(), ProcessorError>;
+
+    /// Apply all writes of a batch and move the processor status to its end, all or nothing
+    async fn write_batch_atomically(
+        &self,
+        writes: BatchWrites,
+        checkpoint: ProcessorStatus,
+    ) -> Result<(), ProcessorError>;
+}
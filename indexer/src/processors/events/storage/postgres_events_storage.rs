@@ -0,0 +1,316 @@
+use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use diesel::QueryResult;
+use diesel_async::{AsyncConnection, AsyncPgConnection};
+use std::cmp;
+
+use super::EventsStorage;
+use crate::{
+    config::indexer_processor_config::{AnalyticsConfig, PipelineConfig},
+    db_models::{
+        bounty::Bounty, build::Build, creator_stat::CreatorStat, module_upgrade::ModuleUpgrade,
+        processor_status::ProcessorStatus, unhandled_event::UnhandledEvent, user_stat::UserStat,
+    },
+    processors::events::{
+        events_storer::{BatchWrites, EventSegment},
+        storers::{
+            accept_build_event_storer::{
+                execute_accept_build_events_sql, process_accept_build_events,
+            },
+            cancel_build_event_storer::{
+                execute_cancel_build_events_sql, process_cancel_build_events,
+            },
+            create_bounty_event_storer::{
+                execute_create_bounty_events_sql, process_create_bounty_events,
+            },
+            create_build_event_storer::{
+                execute_create_build_events_sql, process_create_build_events,
+            },
+            creator_stats_storer::{
+                execute_creator_refunds_sql, execute_refresh_creator_stats_sql,
+                process_creator_refunds, process_creator_stats,
+            },
+            daily_metrics_storer::{execute_refresh_daily_metrics_sql, process_daily_metrics},
+            end_bounty_event_storer::{execute_end_bounty_events_sql, process_end_bounty_events},
+            module_upgrade_storer::{execute_module_upgrades_sql, process_module_upgrades},
+            submit_build_for_review_event_storer::{
+                execute_submit_build_for_review_events_sql, process_submit_build_for_review_events,
+            },
+            unhandled_event_storer::{execute_unhandled_events_sql, process_unhandled_events},
+            user_stats_storer::{execute_user_stats_sql, process_user_stats},
+        },
+    },
+    utils::{
+        database_connection::get_db_connection,
+        database_utils::{get_config_table_chunk_size, ArcDbPool},
+        latest_processed_version_tracker::upsert_processor_status,
+    },
+};
+
+/// The default storage. Writes of one event type are split in chunks of the configured size and
+/// written concurrently, each chunk on its own connection.
+pub struct PostgresEventsStorage {
+    pool: ArcDbPool,
+    contract_address: String,
+    pipeline_config: PipelineConfig,
+    analytics_config: AnalyticsConfig,
+}
+
+impl PostgresEventsStorage {
+    pub fn new(
+        pool: ArcDbPool,
+        contract_address: String,
+        pipeline_config: PipelineConfig,
+        analytics_config: AnalyticsConfig,
+    ) -> Self {
+        Self {
+            pool,
+            contract_address,
+            pipeline_config,
+            analytics_config,
+        }
+    }
+}
+
+#[async_trait]
+impl EventsStorage for PostgresEventsStorage {
+    async fn insert_module_upgrades(
+        &self,
+        upgrades: Vec<ModuleUpgrade>,
+    ) -> Result<(), ProcessorError> {
+        process_module_upgrades(self.pool.clone(), upgrades).await
+    }
+
+    async fn insert_unhandled_events(
+        &self,
+        events: Vec<UnhandledEvent>,
+    ) -> Result<(), ProcessorError> {
+        process_unhandled_events(self.pool.clone(), events).await
+    }
+
+    async fn create_bounties(&self, bounties: Vec<(Bounty, i64)>) -> Result<(), ProcessorError> {
+        process_create_bounty_events(
+            self.pool.clone(),
+            self.pipeline_config.per_table_chunk_sizes.clone(),
+            self.pipeline_config.max_concurrent_db_tasks,
+            bounties,
+        )
+        .await
+    }
+
+    async fn end_bounties(
+        &self,
+        bounties: Vec<(Bounty, BigDecimal)>,
+    ) -> Result<(), ProcessorError> {
+        process_end_bounty_events(
+            self.pool.clone(),
+            self.pipeline_config.per_table_chunk_sizes.clone(),
+            self.pipeline_config.max_concurrent_db_tasks,
+            bounties,
+        )
+        .await
+    }
+
+    async fn create_builds(&self, builds: Vec<(Build, i64)>) -> Result<(), ProcessorError> {
+        process_create_build_events(
+            self.pool.clone(),
+            self.pipeline_config.per_table_chunk_sizes.clone(),
+            self.pipeline_config.max_concurrent_db_tasks,
+            builds,
+        )
+        .await
+    }
+
+    async fn cancel_builds(&self, builds: Vec<(Build, i64)>) -> Result<(), ProcessorError> {
+        process_cancel_build_events(
+            self.pool.clone(),
+            self.pipeline_config.per_table_chunk_sizes.clone(),
+            self.pipeline_config.max_concurrent_db_tasks,
+            builds,
+        )
+        .await
+    }
+
+    async fn submit_builds_for_review(&self, builds: Vec<Build>) -> Result<(), ProcessorError> {
+        process_submit_build_for_review_events(
+            self.pool.clone(),
+            self.pipeline_config.per_table_chunk_sizes.clone(),
+            self.pipeline_config.max_concurrent_db_tasks,
+            builds,
+        )
+        .await
+    }
+
+    async fn accept_builds(
+        &self,
+        accepted: Vec<(Bounty, Build, i64)>,
+    ) -> Result<(), ProcessorError> {
+        process_accept_build_events(
+            self.pool.clone(),
+            self.pipeline_config.per_table_chunk_sizes.clone(),
+            self.pipeline_config.max_concurrent_db_tasks,
+            accepted,
+        )
+        .await
+    }
+
+    async fn apply_user_stat_changes(&self, changes: Vec<UserStat>) -> Result<(), ProcessorError> {
+        process_user_stats(
+            self.pool.clone(),
+            self.pipeline_config.per_table_chunk_sizes.clone(),
+            changes,
+        )
+        .await
+    }
+
+    async fn apply_creator_refunds(&self, refunds: Vec<CreatorStat>) -> Result<(), ProcessorError> {
+        process_creator_refunds(self.pool.clone(), refunds).await
+    }
+
+    async fn refresh_aggregates(
+        &self,
+        touched_bounty_obj_addrs: Vec<String>,
+        touched_day_timestamps: Vec<i64>,
+        latest_timestamp: i64,
+    ) -> Result<(), ProcessorError> {
+        process_creator_stats(
+            self.pool.clone(),
+            touched_bounty_obj_addrs,
+            latest_timestamp,
+            self.analytics_config.review_overdue_threshold_in_seconds,
+        )
+        .await?;
+        process_daily_metrics(
+            self.pool.clone(),
+            self.contract_address.clone(),
+            touched_day_timestamps,
+        )
+        .await
+    }
+
+    async fn write_batch_atomically(
+        &self,
+        writes: BatchWrites,
+        checkpoint: ProcessorStatus,
+    ) -> Result<(), ProcessorError> {
+        let conn = &mut get_db_connection(&self.pool).await?;
+        execute_batch_sql(
+            conn,
+            &self.pipeline_config,
+            &self.analytics_config,
+            writes,
+            self.contract_address.clone(),
+            checkpoint,
+        )
+        .await
+        .map_err(|e| {
+            tracing::warn!("Error running query: {:?}", e);
+            ProcessorError::ProcessError {
+                message: format!("Error running query: {:?}", e),
+            }
+        })
+    }
+}
+
+async fn execute_segment_sql(
+    conn: &mut AsyncPgConnection,
+    bounty_chunk_size: usize,
+    build_chunk_size: usize,
+    segment: EventSegment,
+) -> QueryResult<()> {
+    execute_module_upgrades_sql(conn, segment.module_upgrades).await?;
+    execute_unhandled_events_sql(conn, segment.unhandled_events).await?;
+
+    for chunk in segment.create_bounty_events.chunks(bounty_chunk_size) {
+        execute_create_bounty_events_sql(conn, chunk.to_vec()).await?;
+    }
+
+    for chunk in segment.end_bounty_events.chunks(bounty_chunk_size) {
+        execute_end_bounty_events_sql(
+            conn,
+            chunk.iter().map(|(bounty, _)| bounty.clone()).collect(),
+        )
+        .await?;
+    }
+
+    for chunk in segment.create_build_events.chunks(build_chunk_size) {
+        execute_create_build_events_sql(conn, chunk.to_vec()).await?;
+    }
+
+    for chunk in segment.cancel_build_events.chunks(build_chunk_size) {
+        execute_cancel_build_events_sql(conn, chunk.to_vec()).await?;
+    }
+
+    for chunk in segment
+        .submit_build_for_review_events
+        .chunks(build_chunk_size)
+    {
+        execute_submit_build_for_review_events_sql(conn, chunk.to_vec()).await?;
+    }
+
+    // Every accept writes a bounty and a build
+    let accept_chunk_size = cmp::min(bounty_chunk_size, build_chunk_size);
+    for chunk in segment.accept_build_events.chunks(accept_chunk_size) {
+        execute_accept_build_events_sql(conn, chunk.to_vec()).await?;
+    }
+    Ok(())
+}
+
+/// Apply the whole batch and move the processor status to its end in one transaction, segment by
+/// segment like the parallel path. Chunks run one after another on the same connection, so a
+/// failure at any point rolls back everything and the batch is simply processed again after a
+/// restart.
+async fn execute_batch_sql(
+    conn: &mut AsyncPgConnection,
+    pipeline_config: &PipelineConfig,
+    analytics_config: &AnalyticsConfig,
+    writes: BatchWrites,
+    contract_addr: String,
+    checkpoint: ProcessorStatus,
+) -> QueryResult<()> {
+    let BatchWrites {
+        segments,
+        user_stat_changes,
+        creator_refunds,
+        touched_bounty_obj_addrs,
+        touched_day_timestamps,
+        latest_timestamp,
+    } = writes;
+    let per_table_chunk_sizes = &pipeline_config.per_table_chunk_sizes;
+    let bounty_chunk_size =
+        get_config_table_chunk_size::<Bounty>("bounties", per_table_chunk_sizes);
+    let build_chunk_size = get_config_table_chunk_size::<Build>("builds", per_table_chunk_sizes);
+    let user_stat_chunk_size =
+        get_config_table_chunk_size::<UserStat>("user_stats", per_table_chunk_sizes);
+    let review_overdue_threshold_in_seconds = analytics_config.review_overdue_threshold_in_seconds;
+
+    conn.transaction(|conn| {
+        Box::pin(async move {
+            for segment in segments {
+                execute_segment_sql(conn, bounty_chunk_size, build_chunk_size, segment).await?;
+            }
+            for chunk in user_stat_changes.chunks(user_stat_chunk_size) {
+                execute_user_stats_sql(conn, chunk.to_vec()).await?;
+            }
+            execute_creator_refunds_sql(conn, creator_refunds).await?;
+
+            if !touched_bounty_obj_addrs.is_empty() {
+                execute_refresh_creator_stats_sql(
+                    conn,
+                    touched_bounty_obj_addrs,
+                    latest_timestamp,
+                    review_overdue_threshold_in_seconds,
+                )
+                .await?;
+            }
+            if !touched_day_timestamps.is_empty() {
+                execute_refresh_daily_metrics_sql(conn, touched_day_timestamps, contract_addr)
+                    .await?;
+            }
+
+            upsert_processor_status(conn, &checkpoint).await
+        })
+    })
+    .await
+}
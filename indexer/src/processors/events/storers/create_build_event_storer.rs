@@ -1,13 +1,13 @@
 use ahash::AHashMap;
 use anyhow::Result;
 use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
-use diesel::{insert_into, upsert::excluded, ExpressionMethods, QueryResult};
+use diesel::{insert_into, QueryResult};
 use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
-use std::cmp;
 
+use super::{insert_points_ledger_entries, new_db_task_limiter, CURRENT_SEASON};
 use crate::{
-    db_models::{build::Build, user_stat::UserStat},
-    schema::{builds, user_stats},
+    db_models::{build::Build, points_ledger_entry::PointsLedgerEntry, user_stat::UserStat},
+    schema::builds,
     utils::{
         database_connection::get_db_connection,
         database_utils::{get_config_table_chunk_size, ArcDbPool},
@@ -16,75 +16,29 @@ use crate::{
 
 const POINT_PER_NEW_BUILD: i64 = 1;
 
-async fn execute_create_build_events_sql(
+pub async fn execute_create_build_events_sql(
     conn: &mut AsyncPgConnection,
-    items_to_insert: Vec<Build>,
-    user_stats_changes: AHashMap<String, (i64, i64, i64)>,
+    items_to_insert: Vec<(Build, i64)>,
 ) -> QueryResult<()> {
     conn.transaction(|conn| {
         Box::pin(async move {
+            let (builds, txn_versions): (Vec<Build>, Vec<i64>) =
+                items_to_insert.into_iter().unzip();
+            let points_ledger_entries = builds
+                .iter()
+                .zip(txn_versions)
+                .map(|(build, txn_version)| {
+                    get_create_build_points_ledger_entry(build, txn_version)
+                })
+                .collect::<Vec<_>>();
+
             let create_build_query = insert_into(builds::table)
-                .values(items_to_insert.clone())
+                .values(builds)
                 .on_conflict(builds::build_obj_addr)
                 .do_nothing();
             create_build_query.execute(conn).await?;
 
-            /*
-            DO NOT backfill data (i.e. process same event twice), you would mess up the user stat!!!!
-            Instead, if you want to change the point calculation logic, you should delete all data and re-index from scratch.
-            You can delete all data by revert all DB migrations, see README.md for more details.
-             */
-            let update_user_stat_query = insert_into(user_stats::table)
-                .values(
-                    user_stats_changes
-                        .iter()
-                        .map(
-                            |(
-                                user_addr,
-                                (new_build_count, earliest_build_time, latest_build_time),
-                            )| UserStat {
-                                user_addr: user_addr.clone(),
-                                create_timestamp: *earliest_build_time,
-                                last_update_timestamp: *latest_build_time,
-                                season_1_points: new_build_count * POINT_PER_NEW_BUILD,
-                                total_points: new_build_count * POINT_PER_NEW_BUILD,
-                                bounty_created: 0,
-                                apt_spent: 0,
-                                stable_spent: 0,
-                                build_created: *new_build_count,
-                                build_submitted_for_review: 0,
-                                build_canceled: 0,
-                                build_completed: 0,
-                                apt_received: 0,
-                                stable_received: 0,
-                            },
-                        )
-                        .collect::<Vec<_>>(),
-                )
-                .on_conflict(user_stats::user_addr)
-                .do_update()
-                .set((
-                    user_stats::user_addr.eq(user_stats::user_addr),
-                    user_stats::create_timestamp.eq(excluded(user_stats::create_timestamp)),
-                    user_stats::last_update_timestamp
-                        .eq(excluded(user_stats::last_update_timestamp)),
-                    user_stats::season_1_points
-                        .eq(user_stats::season_1_points + excluded(user_stats::season_1_points)),
-                    user_stats::total_points
-                        .eq(user_stats::total_points + excluded(user_stats::total_points)),
-                    user_stats::bounty_created.eq(user_stats::bounty_created),
-                    user_stats::apt_spent.eq(user_stats::apt_spent),
-                    user_stats::stable_spent.eq(user_stats::stable_spent),
-                    user_stats::build_created
-                        .eq(user_stats::build_created + excluded(user_stats::build_created)),
-                    user_stats::build_submitted_for_review
-                        .eq(user_stats::build_submitted_for_review),
-                    user_stats::build_canceled.eq(user_stats::build_canceled),
-                    user_stats::build_completed.eq(user_stats::build_completed),
-                    user_stats::apt_received.eq(user_stats::apt_received),
-                    user_stats::stable_received.eq(user_stats::stable_received),
-                ));
-            update_user_stat_query.execute(conn).await?;
+            insert_points_ledger_entries(conn, points_ledger_entries).await?;
 
             Ok(())
         })
@@ -92,41 +46,51 @@ async fn execute_create_build_events_sql(
     .await
 }
 
+pub fn get_create_build_points_ledger_entry(build: &Build, txn_version: i64) -> PointsLedgerEntry {
+    PointsLedgerEntry {
+        txn_version,
+        event_type: "CreateBuildEvent".to_string(),
+        object_addr: build.build_obj_addr.clone(),
+        user_addr: build.creator_addr.clone(),
+        season: CURRENT_SEASON.to_string(),
+        points_delta: POINT_PER_NEW_BUILD,
+        timestamp: build.create_timestamp,
+    }
+}
+
+/// Change to the builder's stats, applied once per batch by the user stats storer
+pub fn get_create_build_user_stat_change(build: &Build) -> UserStat {
+    UserStat {
+        season_1_points: POINT_PER_NEW_BUILD,
+        total_points: POINT_PER_NEW_BUILD,
+        build_created: 1,
+        ..UserStat::new_change(&build.creator_addr, build.create_timestamp)
+    }
+}
+
 pub async fn process_create_build_events(
     pool: ArcDbPool,
     per_table_chunk_sizes: AHashMap<String, usize>,
-    create_events: Vec<Build>,
+    max_concurrent_db_tasks: Option<usize>,
+    create_events: Vec<(Build, i64)>,
 ) -> Result<(), ProcessorError> {
-    // Key is user address
-    // Value is (number of build created, earliest create build time, latest create build time)
-    let mut user_stats_changes: AHashMap<String, (i64, i64, i64)> = AHashMap::new();
-    for build in create_events.clone() {
-        let default_value = (0, i64::MAX, 0);
-        let (new_count, earliest_time, latest_time) = user_stats_changes
-            .get(&build.creator_addr)
-            .unwrap_or(&default_value);
-        user_stats_changes.insert(
-            build.creator_addr,
-            (
-                new_count + 1,
-                cmp::min(*earliest_time, build.create_timestamp),
-                cmp::max(*latest_time, build.create_timestamp),
-            ),
-        );
-    }
-
     let chunk_size = get_config_table_chunk_size::<Build>("builds", &per_table_chunk_sizes);
+    let db_task_limiter = new_db_task_limiter(max_concurrent_db_tasks);
     let tasks = create_events
         .chunks(chunk_size)
         .map(|chunk| {
             let pool = pool.clone();
             let items = chunk.to_vec();
-            let user_stats_changes = user_stats_changes.clone();
+            let db_task_limiter = db_task_limiter.clone();
             tokio::spawn(async move {
+                let _permit = db_task_limiter
+                    .acquire_owned()
+                    .await
+                    .expect("DB task limiter is never closed");
                 let conn = &mut get_db_connection(&pool).await.expect(
                     "Failed to get connection from pool while processing create build events",
                 );
-                execute_create_build_events_sql(conn, items, user_stats_changes).await
+                execute_create_build_events_sql(conn, items).await
             })
         })
         .collect::<Vec<_>>();
@@ -0,0 +1,204 @@
+use ahash::AHashMap;
+use anyhow::Result;
+use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
+use diesel::{
+    insert_into, sql_query,
+    sql_types::{Array, Int8, Text},
+    upsert::excluded,
+    ExpressionMethods, QueryResult,
+};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::cmp;
+
+use super::{greatest, BUILD_STATUS_COMPLETED, BUILD_STATUS_READY_FOR_REVIEW};
+use crate::{
+    db_models::creator_stat::CreatorStat,
+    schema::creator_stats,
+    utils::{database_connection::get_db_connection, database_utils::ArcDbPool},
+};
+
+/// Recompute review metrics of every creator owning one of the given bounties, plus every creator
+/// that still has a build in review, so the overdue count moves forward with chain time.
+/// Unlike user stats, this is derived from the bounties and builds tables, so it's safe to re-run.
+/// Refund columns are accumulated by the end bounty storer and left untouched here.
+/// Builds accepted before submit_timestamp existed have none, they still count as submitted but
+/// are left out of the review times.
+const REFRESH_CREATOR_STATS_SQL: &str = "
+INSERT INTO creator_stats (
+    creator_addr,
+    last_update_timestamp,
+    bounty_created,
+    bounty_ended,
+    apt_refunded,
+    stable_refunded,
+    build_submitted_for_review,
+    build_accepted,
+    build_in_review,
+    build_overdue_in_review,
+    acceptance_ratio,
+    median_review_time_in_seconds,
+    p90_review_time_in_seconds
+)
+SELECT
+    bounties.creator_addr,
+    $2,
+    COUNT(DISTINCT bounties.bounty_obj_addr),
+    0,
+    0,
+    0,
+    COUNT(builds.build_obj_addr) FILTER (
+        WHERE builds.build_status IN ($3, $4) OR builds.submit_timestamp IS NOT NULL
+    ),
+    COUNT(builds.build_obj_addr) FILTER (WHERE builds.build_status = $4),
+    COUNT(builds.build_obj_addr) FILTER (WHERE builds.build_status = $3),
+    COUNT(builds.build_obj_addr) FILTER (
+        WHERE builds.build_status = $3 AND builds.submit_timestamp < $2 - $5
+    ),
+    COALESCE(
+        COUNT(builds.build_obj_addr) FILTER (WHERE builds.build_status = $4)::FLOAT8
+            / NULLIF(COUNT(builds.build_obj_addr) FILTER (
+                WHERE builds.build_status IN ($3, $4) OR builds.submit_timestamp IS NOT NULL
+            ), 0),
+        0
+    ),
+    (PERCENTILE_CONT(0.5) WITHIN GROUP (
+        ORDER BY (builds.last_update_timestamp - builds.submit_timestamp)::FLOAT8
+    ) FILTER (WHERE builds.build_status = $4 AND builds.submit_timestamp IS NOT NULL))::BIGINT,
+    (PERCENTILE_CONT(0.9) WITHIN GROUP (
+        ORDER BY (builds.last_update_timestamp - builds.submit_timestamp)::FLOAT8
+    ) FILTER (WHERE builds.build_status = $4 AND builds.submit_timestamp IS NOT NULL))::BIGINT
+FROM bounties
+LEFT JOIN builds ON builds.bounty_obj_addr = bounties.bounty_obj_addr
+WHERE bounties.creator_addr IN (
+    SELECT creator_addr FROM bounties WHERE bounty_obj_addr = ANY($1)
+    UNION
+    SELECT bounties.creator_addr FROM bounties
+    JOIN builds ON builds.bounty_obj_addr = bounties.bounty_obj_addr
+    WHERE builds.build_status = $3
+)
+GROUP BY bounties.creator_addr
+ON CONFLICT (creator_addr) DO UPDATE SET
+    last_update_timestamp = GREATEST(creator_stats.last_update_timestamp, EXCLUDED.last_update_timestamp),
+    bounty_created = EXCLUDED.bounty_created,
+    build_submitted_for_review = EXCLUDED.build_submitted_for_review,
+    build_accepted = EXCLUDED.build_accepted,
+    build_in_review = EXCLUDED.build_in_review,
+    build_overdue_in_review = EXCLUDED.build_overdue_in_review,
+    acceptance_ratio = EXCLUDED.acceptance_ratio,
+    median_review_time_in_seconds = EXCLUDED.median_review_time_in_seconds,
+    p90_review_time_in_seconds = EXCLUDED.p90_review_time_in_seconds
+";
+
+pub async fn execute_refresh_creator_stats_sql(
+    conn: &mut AsyncPgConnection,
+    bounty_obj_addrs: Vec<String>,
+    latest_timestamp: i64,
+    review_overdue_threshold_in_seconds: i64,
+) -> QueryResult<()> {
+    sql_query(REFRESH_CREATOR_STATS_SQL)
+        .bind::<Array<Text>, _>(bounty_obj_addrs)
+        .bind::<Int8, _>(latest_timestamp)
+        .bind::<Int8, _>(BUILD_STATUS_READY_FOR_REVIEW)
+        .bind::<Int8, _>(BUILD_STATUS_COMPLETED)
+        .bind::<Int8, _>(review_overdue_threshold_in_seconds)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Must run after all the other storers of a batch so it sees the latest bounties and builds.
+/// `latest_timestamp` is the latest on-chain timestamp in the batch, we use it instead of the wall
+/// clock so re-indexing produces the same overdue counts.
+pub async fn process_creator_stats(
+    pool: ArcDbPool,
+    bounty_obj_addrs: Vec<String>,
+    latest_timestamp: i64,
+    review_overdue_threshold_in_seconds: i64,
+) -> Result<(), ProcessorError> {
+    if bounty_obj_addrs.is_empty() {
+        return Ok(());
+    }
+
+    let conn = &mut get_db_connection(&pool).await?;
+    execute_refresh_creator_stats_sql(
+        conn,
+        bounty_obj_addrs,
+        latest_timestamp,
+        review_overdue_threshold_in_seconds,
+    )
+    .await
+    .map_err(|e| {
+        tracing::warn!("Error running query: {:?}", e);
+        ProcessorError::ProcessError {
+            message: format!("Error running query: {:?}", e),
+        }
+    })
+}
+
+/// Sum the refunds of every end bounty event in a batch into one change per creator, like
+/// reduce_user_stat_changes
+pub fn reduce_creator_refunds(refunds: impl IntoIterator<Item = CreatorStat>) -> Vec<CreatorStat> {
+    let mut reduced: AHashMap<String, CreatorStat> = AHashMap::new();
+    for refund in refunds {
+        let Some(stat) = reduced.get_mut(&refund.creator_addr) else {
+            reduced.insert(refund.creator_addr.clone(), refund);
+            continue;
+        };
+        stat.last_update_timestamp =
+            cmp::max(stat.last_update_timestamp, refund.last_update_timestamp);
+        stat.bounty_ended += refund.bounty_ended;
+        stat.apt_refunded += refund.apt_refunded;
+        stat.stable_refunded += refund.stable_refunded;
+    }
+    reduced.into_values().collect()
+}
+
+/// Expects reduced refunds, i.e. at most one per creator. Only the refund columns are
+/// accumulated, the rest is recomputed by execute_refresh_creator_stats_sql.
+pub async fn execute_creator_refunds_sql(
+    conn: &mut AsyncPgConnection,
+    refunds: Vec<CreatorStat>,
+) -> QueryResult<()> {
+    if refunds.is_empty() {
+        return Ok(());
+    }
+
+    let update_creator_stat_query = insert_into(creator_stats::table)
+        .values(refunds)
+        .on_conflict(creator_stats::creator_addr)
+        .do_update()
+        .set((
+            creator_stats::last_update_timestamp.eq(greatest(
+                creator_stats::last_update_timestamp,
+                excluded(creator_stats::last_update_timestamp),
+            )),
+            creator_stats::bounty_ended
+                .eq(creator_stats::bounty_ended + excluded(creator_stats::bounty_ended)),
+            creator_stats::apt_refunded
+                .eq(creator_stats::apt_refunded + excluded(creator_stats::apt_refunded)),
+            creator_stats::stable_refunded
+                .eq(creator_stats::stable_refunded + excluded(creator_stats::stable_refunded)),
+        ));
+    update_creator_stat_query.execute(conn).await?;
+    Ok(())
+}
+
+/// Must run once per batch, before process_creator_stats.
+pub async fn process_creator_refunds(
+    pool: ArcDbPool,
+    refunds: Vec<CreatorStat>,
+) -> Result<(), ProcessorError> {
+    if refunds.is_empty() {
+        return Ok(());
+    }
+
+    let conn = &mut get_db_connection(&pool).await?;
+    execute_creator_refunds_sql(conn, refunds)
+        .await
+        .map_err(|e| {
+            tracing::warn!("Error running query: {:?}", e);
+            ProcessorError::ProcessError {
+                message: format!("Error running query: {:?}", e),
+            }
+        })
+}
@@ -0,0 +1,286 @@
+use anyhow::Result;
+use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
+use diesel::{
+    sql_query,
+    sql_types::{Array, Int8, Text},
+    QueryResult, QueryableByName,
+};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+use super::{BUILD_STATUS_CANCELED, BUILD_STATUS_COMPLETED};
+use crate::utils::{database_connection::get_db_connection, database_utils::ArcDbPool};
+
+pub const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Keep the first activity of every user active on the given UTC days, see REFRESH_DAILY_METRICS_SQL.
+/// Taking the least timestamp makes it safe to re-run and independent of the order days come in.
+const UPDATE_USER_FIRST_ACTIVITIES_SQL: &str = "
+WITH touched_days AS (
+    SELECT UNNEST($1::BIGINT[]) AS day_timestamp
+),
+user_activities AS (
+    SELECT bounties.creator_addr AS user_addr, bounties.create_timestamp AS ts
+    FROM bounties JOIN touched_days
+        ON bounties.create_timestamp >= touched_days.day_timestamp
+        AND bounties.create_timestamp < touched_days.day_timestamp + $2
+    UNION ALL
+    SELECT builds.creator_addr, builds.create_timestamp
+    FROM builds JOIN touched_days
+        ON builds.create_timestamp >= touched_days.day_timestamp
+        AND builds.create_timestamp < touched_days.day_timestamp + $2
+    UNION ALL
+    SELECT builds.creator_addr, builds.submit_timestamp
+    FROM builds JOIN touched_days
+        ON builds.submit_timestamp >= touched_days.day_timestamp
+        AND builds.submit_timestamp < touched_days.day_timestamp + $2
+)
+INSERT INTO user_first_activities (user_addr, first_activity_timestamp)
+SELECT user_addr, MIN(ts) FROM user_activities GROUP BY user_addr
+ON CONFLICT (user_addr) DO UPDATE SET
+    first_activity_timestamp = LEAST(user_first_activities.first_activity_timestamp, EXCLUDED.first_activity_timestamp)
+";
+
+/// Recompute the daily metrics of the given UTC days from the bounties and builds of those days
+/// only, so the cost of a batch doesn't grow with the indexed history. Because each row is derived
+/// from the full history of that day, re-running it is safe, which is what makes the backfill
+/// possible.
+/// A user is active on a day when they create a bounty, create a build or submit a build, and is
+/// counted as new on the day of their first activity, returning on any later day. First
+/// activities must be up to date for those days, see UPDATE_USER_FIRST_ACTIVITIES_SQL.
+const REFRESH_DAILY_METRICS_SQL: &str = "
+WITH touched_days AS (
+    SELECT UNNEST($1::BIGINT[]) AS day_timestamp
+),
+metric_events AS (
+    SELECT bounties.create_timestamp AS ts, bounties.payment_metadata_obj_addr, 'bounty_created' AS kind, 0::NUMERIC AS amount
+    FROM bounties JOIN touched_days
+        ON bounties.create_timestamp >= touched_days.day_timestamp
+        AND bounties.create_timestamp < touched_days.day_timestamp + $5
+    UNION ALL
+    SELECT bounties.end_timestamp, bounties.payment_metadata_obj_addr, 'bounty_refunded', bounties.refunded_amount
+    FROM bounties JOIN touched_days
+        ON bounties.end_timestamp >= touched_days.day_timestamp
+        AND bounties.end_timestamp < touched_days.day_timestamp + $5
+    WHERE bounties.refunded_amount > 0
+    UNION ALL
+    SELECT builds.create_timestamp, bounties.payment_metadata_obj_addr, 'build_created', 0
+    FROM builds JOIN touched_days
+        ON builds.create_timestamp >= touched_days.day_timestamp
+        AND builds.create_timestamp < touched_days.day_timestamp + $5
+    JOIN bounties ON builds.bounty_obj_addr = bounties.bounty_obj_addr
+    UNION ALL
+    SELECT builds.submit_timestamp, bounties.payment_metadata_obj_addr, 'build_submitted_for_review', 0
+    FROM builds JOIN touched_days
+        ON builds.submit_timestamp >= touched_days.day_timestamp
+        AND builds.submit_timestamp < touched_days.day_timestamp + $5
+    JOIN bounties ON builds.bounty_obj_addr = bounties.bounty_obj_addr
+    UNION ALL
+    SELECT builds.last_update_timestamp, bounties.payment_metadata_obj_addr, 'build_canceled', 0
+    FROM builds JOIN touched_days
+        ON builds.last_update_timestamp >= touched_days.day_timestamp
+        AND builds.last_update_timestamp < touched_days.day_timestamp + $5
+    JOIN bounties ON builds.bounty_obj_addr = bounties.bounty_obj_addr
+    WHERE builds.build_status = $3
+    UNION ALL
+    SELECT builds.last_update_timestamp, bounties.payment_metadata_obj_addr, 'build_completed', builds.payment_amount
+    FROM builds JOIN touched_days
+        ON builds.last_update_timestamp >= touched_days.day_timestamp
+        AND builds.last_update_timestamp < touched_days.day_timestamp + $5
+    JOIN bounties ON builds.bounty_obj_addr = bounties.bounty_obj_addr
+    WHERE builds.build_status = $4
+),
+user_activities AS (
+    SELECT bounties.creator_addr AS user_addr, bounties.payment_metadata_obj_addr, bounties.create_timestamp AS ts
+    FROM bounties JOIN touched_days
+        ON bounties.create_timestamp >= touched_days.day_timestamp
+        AND bounties.create_timestamp < touched_days.day_timestamp + $5
+    UNION ALL
+    SELECT builds.creator_addr, bounties.payment_metadata_obj_addr, builds.create_timestamp
+    FROM builds JOIN touched_days
+        ON builds.create_timestamp >= touched_days.day_timestamp
+        AND builds.create_timestamp < touched_days.day_timestamp + $5
+    JOIN bounties ON builds.bounty_obj_addr = bounties.bounty_obj_addr
+    UNION ALL
+    SELECT builds.creator_addr, bounties.payment_metadata_obj_addr, builds.submit_timestamp
+    FROM builds JOIN touched_days
+        ON builds.submit_timestamp >= touched_days.day_timestamp
+        AND builds.submit_timestamp < touched_days.day_timestamp + $5
+    JOIN bounties ON builds.bounty_obj_addr = bounties.bounty_obj_addr
+),
+daily_users AS (
+    SELECT DISTINCT
+        user_activities.ts - user_activities.ts % $5 AS day_timestamp,
+        user_activities.payment_metadata_obj_addr,
+        user_activities.user_addr,
+        user_first_activities.first_activity_timestamp
+            - user_first_activities.first_activity_timestamp % $5 AS first_day_timestamp
+    FROM user_activities
+    JOIN user_first_activities ON user_activities.user_addr = user_first_activities.user_addr
+),
+daily_user_counts AS (
+    SELECT
+        day_timestamp,
+        payment_metadata_obj_addr,
+        COUNT(*) FILTER (WHERE first_day_timestamp = day_timestamp) AS new_users,
+        COUNT(*) FILTER (WHERE first_day_timestamp < day_timestamp) AS returning_users
+    FROM daily_users GROUP BY day_timestamp, payment_metadata_obj_addr
+)
+INSERT INTO daily_metrics (
+    day_timestamp,
+    payment_metadata_obj_addr,
+    contract_addr,
+    last_update_timestamp,
+    bounty_created,
+    build_created,
+    build_submitted_for_review,
+    build_canceled,
+    build_completed,
+    payout_volume,
+    refunded_volume,
+    new_users,
+    returning_users
+)
+SELECT
+    metric_events.ts - metric_events.ts % $5,
+    metric_events.payment_metadata_obj_addr,
+    $2,
+    MAX(metric_events.ts),
+    COUNT(*) FILTER (WHERE metric_events.kind = 'bounty_created'),
+    COUNT(*) FILTER (WHERE metric_events.kind = 'build_created'),
+    COUNT(*) FILTER (WHERE metric_events.kind = 'build_submitted_for_review'),
+    COUNT(*) FILTER (WHERE metric_events.kind = 'build_canceled'),
+    COUNT(*) FILTER (WHERE metric_events.kind = 'build_completed'),
+    COALESCE(SUM(metric_events.amount) FILTER (WHERE metric_events.kind = 'build_completed'), 0),
+    COALESCE(SUM(metric_events.amount) FILTER (WHERE metric_events.kind = 'bounty_refunded'), 0),
+    COALESCE(MAX(daily_user_counts.new_users), 0),
+    COALESCE(MAX(daily_user_counts.returning_users), 0)
+FROM metric_events
+LEFT JOIN daily_user_counts
+    ON daily_user_counts.day_timestamp = metric_events.ts - metric_events.ts % $5
+    AND daily_user_counts.payment_metadata_obj_addr = metric_events.payment_metadata_obj_addr
+GROUP BY metric_events.ts - metric_events.ts % $5, metric_events.payment_metadata_obj_addr
+ON CONFLICT (day_timestamp, payment_metadata_obj_addr, contract_addr) DO UPDATE SET
+    last_update_timestamp = EXCLUDED.last_update_timestamp,
+    bounty_created = EXCLUDED.bounty_created,
+    build_created = EXCLUDED.build_created,
+    build_submitted_for_review = EXCLUDED.build_submitted_for_review,
+    build_canceled = EXCLUDED.build_canceled,
+    build_completed = EXCLUDED.build_completed,
+    payout_volume = EXCLUDED.payout_volume,
+    refunded_volume = EXCLUDED.refunded_volume,
+    new_users = EXCLUDED.new_users,
+    returning_users = EXCLUDED.returning_users
+";
+
+/// Every UTC day with at least one bounty or build metric, for the backfill
+const SELECT_INDEXED_DAYS_SQL: &str = "
+SELECT DISTINCT ts - ts % $1 AS day_timestamp
+FROM (
+    SELECT create_timestamp AS ts FROM bounties
+    UNION ALL
+    SELECT end_timestamp FROM bounties WHERE refunded_amount > 0
+    UNION ALL
+    SELECT create_timestamp FROM builds
+    UNION ALL
+    SELECT submit_timestamp FROM builds WHERE submit_timestamp IS NOT NULL
+    UNION ALL
+    SELECT last_update_timestamp FROM builds
+) AS indexed_timestamps
+ORDER BY day_timestamp
+";
+
+// Days refreshed per query by the backfill
+const BACKFILL_DAYS_PER_QUERY: usize = 30;
+
+#[derive(QueryableByName)]
+struct IndexedDay {
+    #[diesel(sql_type = Int8)]
+    day_timestamp: i64,
+}
+
+async fn execute_update_user_first_activities_sql(
+    conn: &mut AsyncPgConnection,
+    day_timestamps: Vec<i64>,
+) -> QueryResult<()> {
+    sql_query(UPDATE_USER_FIRST_ACTIVITIES_SQL)
+        .bind::<Array<Int8>, _>(day_timestamps)
+        .bind::<Int8, _>(SECONDS_PER_DAY)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+async fn execute_daily_metrics_sql(
+    conn: &mut AsyncPgConnection,
+    day_timestamps: Vec<i64>,
+    contract_addr: String,
+) -> QueryResult<()> {
+    sql_query(REFRESH_DAILY_METRICS_SQL)
+        .bind::<Array<Int8>, _>(day_timestamps)
+        .bind::<Text, _>(contract_addr)
+        .bind::<Int8, _>(BUILD_STATUS_CANCELED)
+        .bind::<Int8, _>(BUILD_STATUS_COMPLETED)
+        .bind::<Int8, _>(SECONDS_PER_DAY)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Update the first activities of the users active on the given days, then their daily metrics
+pub async fn execute_refresh_daily_metrics_sql(
+    conn: &mut AsyncPgConnection,
+    day_timestamps: Vec<i64>,
+    contract_addr: String,
+) -> QueryResult<()> {
+    execute_update_user_first_activities_sql(conn, day_timestamps.clone()).await?;
+    execute_daily_metrics_sql(conn, day_timestamps, contract_addr).await
+}
+
+/// Returns the unix timestamp of 00:00:00 UTC of the day the timestamp falls in.
+pub fn get_day_timestamp(timestamp: i64) -> i64 {
+    timestamp - timestamp.rem_euclid(SECONDS_PER_DAY)
+}
+
+/// Must run after all the other storers of a batch so it sees the latest bounties and builds.
+pub async fn process_daily_metrics(
+    pool: ArcDbPool,
+    contract_addr: String,
+    day_timestamps: Vec<i64>,
+) -> Result<(), ProcessorError> {
+    if day_timestamps.is_empty() {
+        return Ok(());
+    }
+
+    let conn = &mut get_db_connection(&pool).await?;
+    execute_refresh_daily_metrics_sql(conn, day_timestamps, contract_addr)
+        .await
+        .map_err(|e| {
+            tracing::warn!("Error running query: {:?}", e);
+            ProcessorError::ProcessError {
+                message: format!("Error running query: {:?}", e),
+            }
+        })
+}
+
+/// Rebuild the daily metrics of every day already indexed, e.g. after adding this table to a DB
+/// that was indexed before it existed. First activities are updated for every day before any
+/// metric is refreshed, so the new users of a day don't depend on the order days are refreshed in.
+pub async fn backfill_daily_metrics(pool: ArcDbPool, contract_addr: String) -> Result<()> {
+    tracing::info!("Backfilling daily metrics from indexed bounties and builds");
+    let conn = &mut get_db_connection(&pool).await?;
+    let day_timestamps: Vec<i64> = sql_query(SELECT_INDEXED_DAYS_SQL)
+        .bind::<Int8, _>(SECONDS_PER_DAY)
+        .load::<IndexedDay>(conn)
+        .await?
+        .into_iter()
+        .map(|day| day.day_timestamp)
+        .collect();
+
+    for chunk in day_timestamps.chunks(BACKFILL_DAYS_PER_QUERY) {
+        execute_update_user_first_activities_sql(conn, chunk.to_vec()).await?;
+    }
+    for chunk in day_timestamps.chunks(BACKFILL_DAYS_PER_QUERY) {
+        execute_daily_metrics_sql(conn, chunk.to_vec(), contract_addr.clone()).await?;
+    }
+    Ok(())
+}
@@ -1,17 +1,17 @@
 use ahash::AHashMap;
 use anyhow::Result;
 use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
+use bigdecimal::{BigDecimal, Zero};
 use diesel::{
     insert_into, query_dsl::methods::FilterDsl, upsert::excluded, BoolExpressionMethods,
     ExpressionMethods, QueryResult,
 };
 use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
-use std::cmp;
 
-use super::APT_FA_ADDR;
+use super::{new_db_task_limiter, APT_FA_ADDR};
 use crate::{
-    db_models::{bounty::Bounty, user_stat::UserStat},
-    schema::{bounties, user_stats},
+    db_models::{bounty::Bounty, creator_stat::CreatorStat, user_stat::UserStat},
+    schema::bounties,
     utils::{
         database_connection::get_db_connection,
         database_utils::{get_config_table_chunk_size, ArcDbPool},
@@ -20,10 +20,9 @@ use crate::{
 
 const POINT_PER_END_BOUNTY: i64 = 0;
 
-async fn execute_end_bounty_events_sql(
+pub async fn execute_end_bounty_events_sql(
     conn: &mut AsyncPgConnection,
     items_to_insert: Vec<Bounty>,
-    user_stats_changes: AHashMap<String, (i64, i64, i64, i64)>,
 ) -> QueryResult<()> {
     conn.transaction(|conn| {
         Box::pin(async move {
@@ -48,127 +47,104 @@ async fn execute_end_bounty_events_sql(
                     bounties::total_payment.eq(bounties::total_payment),
                     bounties::contact_info.eq(bounties::contact_info),
                     bounties::last_update_event_idx.eq(excluded(bounties::last_update_event_idx)),
+                    bounties::last_update_txn_version
+                        .eq(excluded(bounties::last_update_txn_version)),
+                    bounties::refunded_amount.eq(excluded(bounties::refunded_amount)),
+                    bounties::contact_handle_type.eq(bounties::contact_handle_type),
+                    bounties::contact_handle.eq(bounties::contact_handle),
                 ))
                 .filter(
-                    // Update only if the last update timestamp is greater than the existing one
-                    // or if the last update timestamp is the same but the event index is greater
+                    // Update only if the event is after the stored update in (timestamp, version,
+                    // event index) order, so re-processing a batch never restores an older state
                     bounties::last_update_timestamp
                         .lt(excluded(bounties::last_update_timestamp))
                         .or(bounties::last_update_timestamp
                             .eq(excluded(bounties::last_update_timestamp))
                             .and(
-                                bounties::last_update_event_idx
-                                    .lt(excluded(bounties::last_update_event_idx)),
+                                bounties::last_update_txn_version
+                                    .lt(excluded(bounties::last_update_txn_version))
+                                    .or(bounties::last_update_txn_version
+                                        .eq(excluded(bounties::last_update_txn_version))
+                                        .and(
+                                            bounties::last_update_event_idx
+                                                .lt(excluded(bounties::last_update_event_idx)),
+                                        )),
                             )),
                 );
             end_bounty_query.execute(conn).await?;
 
-            /*
-            DO NOT backfill data (i.e. process same event twice), you would mess up the user stat!!!!
-            Instead, if you want to change the point calculation logic, you should delete all data and re-index from scratch.
-            You can delete all data by revert all DB migrations, see README.md for more details.
-             */
-            let update_user_stat_query = insert_into(user_stats::table)
-                .values(
-                    user_stats_changes
-                        .iter()
-                        .map(
-                            |(
-                                user_addr,
-                                (
-                                    end_bounty_count,
-                                    latest_end_bounty_time,
-                                    apt_withdraw,
-                                    stable_withdraw,
-                                ),
-                            )| UserStat {
-                                user_addr: user_addr.clone(),
-                                create_timestamp: *latest_end_bounty_time,
-                                last_update_timestamp: *latest_end_bounty_time,
-                                season_1_points: end_bounty_count * POINT_PER_END_BOUNTY,
-                                total_points: end_bounty_count * POINT_PER_END_BOUNTY,
-                                bounty_created: 0,
-                                apt_spent: -*apt_withdraw,
-                                stable_spent: -*stable_withdraw,
-                                build_created: 0,
-                                build_submitted_for_review: 0,
-                                build_canceled: 0,
-                                build_completed: 0,
-                                apt_received: 0,
-                                stable_received: 0,
-                            },
-                        )
-                        .collect::<Vec<_>>(),
-                )
-                .on_conflict(user_stats::user_addr)
-                .do_update()
-                .set((
-                    user_stats::user_addr.eq(user_stats::user_addr),
-                    user_stats::create_timestamp.eq(user_stats::create_timestamp),
-                    user_stats::last_update_timestamp
-                        .eq(excluded(user_stats::last_update_timestamp)),
-                    user_stats::season_1_points
-                        .eq(user_stats::season_1_points + excluded(user_stats::season_1_points)),
-                    user_stats::total_points
-                        .eq(user_stats::total_points + excluded(user_stats::total_points)),
-                    user_stats::bounty_created.eq(user_stats::bounty_created),
-                    user_stats::apt_spent
-                        .eq(user_stats::apt_spent - excluded(user_stats::apt_spent)),
-                    user_stats::stable_spent
-                        .eq(user_stats::stable_spent - excluded(user_stats::stable_spent)),
-                    user_stats::build_created.eq(user_stats::build_created),
-                    user_stats::build_submitted_for_review
-                        .eq(user_stats::build_submitted_for_review),
-                    user_stats::build_canceled.eq(user_stats::build_canceled),
-                    user_stats::build_completed.eq(user_stats::build_completed),
-                    user_stats::apt_received.eq(user_stats::apt_received),
-                    user_stats::stable_received.eq(user_stats::stable_received),
-                ));
-            update_user_stat_query.execute(conn).await?;
-
             Ok(())
         })
     })
     .await
 }
 
+/// Change to the creator's stats, applied once per batch by the user stats storer. The payment
+/// sent back is no longer spent.
+pub fn get_end_bounty_user_stat_change(
+    bounty: &Bounty,
+    payment_sent_back_to_creator: &BigDecimal,
+) -> UserStat {
+    let apt_spent = if bounty.payment_metadata_obj_addr == APT_FA_ADDR {
+        -payment_sent_back_to_creator.clone()
+    } else {
+        BigDecimal::zero()
+    };
+    UserStat {
+        season_1_points: POINT_PER_END_BOUNTY,
+        total_points: POINT_PER_END_BOUNTY,
+        apt_spent,
+        ..UserStat::new_change(&bounty.creator_addr, bounty.end_timestamp)
+    }
+}
+
+/// Refunds are only known from the end bounty event, so they are accumulated from here, the rest
+/// of the creator stat is recomputed by the creator stats storer
+pub fn get_end_bounty_creator_stat_change(
+    bounty: &Bounty,
+    payment_sent_back_to_creator: &BigDecimal,
+) -> CreatorStat {
+    let apt_refunded = if bounty.payment_metadata_obj_addr == APT_FA_ADDR {
+        payment_sent_back_to_creator.clone()
+    } else {
+        BigDecimal::zero()
+    };
+    CreatorStat {
+        creator_addr: bounty.creator_addr.clone(),
+        last_update_timestamp: bounty.end_timestamp,
+        bounty_created: 0,
+        bounty_ended: 1,
+        apt_refunded,
+        stable_refunded: BigDecimal::zero(),
+        build_submitted_for_review: 0,
+        build_accepted: 0,
+        build_in_review: 0,
+        build_overdue_in_review: 0,
+        acceptance_ratio: 0.0,
+        median_review_time_in_seconds: None,
+        p90_review_time_in_seconds: None,
+    }
+}
+
 pub async fn process_end_bounty_events(
     pool: ArcDbPool,
     per_table_chunk_sizes: AHashMap<String, usize>,
-    end_events: Vec<(Bounty, i64)>,
+    max_concurrent_db_tasks: Option<usize>,
+    end_events: Vec<(Bounty, BigDecimal)>,
 ) -> Result<(), ProcessorError> {
-    // Key is user address
-    // Value is (number of bounty created, latest end bounty time, APT withdraw amount, stable withdraw amount)
-    let mut user_stats_changes: AHashMap<String, (i64, i64, i64, i64)> = AHashMap::new();
-    for (bounty, payment_sent_back_to_creator) in end_events.clone() {
-        let default_value = (0, 0, 0, 0);
-        let (end_count, latest_time, apt_withdraw, stable_withdraw) = user_stats_changes
-            .get(&bounty.creator_addr)
-            .unwrap_or(&default_value);
-        let new_apt_withdraw = if bounty.payment_metadata_obj_addr == APT_FA_ADDR {
-            apt_withdraw + payment_sent_back_to_creator
-        } else {
-            *apt_withdraw
-        };
-        user_stats_changes.insert(
-            bounty.creator_addr,
-            (
-                end_count + 1,
-                cmp::max(*latest_time, bounty.create_timestamp),
-                new_apt_withdraw,
-                *stable_withdraw,
-            ),
-        );
-    }
-
     let chunk_size = get_config_table_chunk_size::<Bounty>("bounties", &per_table_chunk_sizes);
+    let db_task_limiter = new_db_task_limiter(max_concurrent_db_tasks);
     let tasks = end_events
         .chunks(chunk_size)
         .map(|chunk| {
             let pool = pool.clone();
             let items = chunk.to_vec();
-            let user_stats_changes = user_stats_changes.clone();
+            let db_task_limiter = db_task_limiter.clone();
             tokio::spawn(async move {
+                let _permit = db_task_limiter
+                    .acquire_owned()
+                    .await
+                    .expect("DB task limiter is never closed");
                 let conn = &mut get_db_connection(&pool).await.expect(
                     "Failed to get connection from pool while processing create bounty events",
                 );
@@ -178,7 +154,6 @@ pub async fn process_end_bounty_events(
                         .into_iter()
                         .map(|(bounty, _)| bounty)
                         .collect::<Vec<_>>(),
-                    user_stats_changes,
                 )
                 .await
             })
@@ -2,11 +2,70 @@ pub mod accept_build_event_storer;
 pub mod cancel_build_event_storer;
 pub mod create_bounty_event_storer;
 pub mod create_build_event_storer;
+pub mod creator_stats_storer;
+pub mod daily_metrics_storer;
 pub mod end_bounty_event_storer;
+pub mod module_upgrade_storer;
 pub mod submit_build_for_review_event_storer;
+pub mod unhandled_event_storer;
+pub mod user_stats_storer;
+
+use diesel::{insert_into, QueryResult};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+use crate::{db_models::points_ledger_entry::PointsLedgerEntry, schema::points_ledger};
+
+diesel::sql_function! {
+    /// Postgres LEAST, returns the smaller value
+    fn least(a: diesel::sql_types::BigInt, b: diesel::sql_types::BigInt) -> diesel::sql_types::BigInt;
+}
+
+diesel::sql_function! {
+    /// Postgres GREATEST, returns the larger value
+    fn greatest(a: diesel::sql_types::BigInt, b: diesel::sql_types::BigInt) -> diesel::sql_types::BigInt;
+}
+
+// Mirrors the build status constants in bounty_app.move
+pub const BUILD_STATUS_IN_PROGRESS: i64 = 1;
+pub const BUILD_STATUS_READY_FOR_REVIEW: i64 = 2;
+pub const BUILD_STATUS_CANCELED: i64 = 3;
+pub const BUILD_STATUS_COMPLETED: i64 = 4;
+
+// Season new points count towards, must match a season in LEADERBOARD_SEASONS
+pub const CURRENT_SEASON: &str = "season_1";
 
 pub const APT_FA_ADDR: &str = "0x000000000000000000000000000000000000000000000000000000000000000a";
 
 // TODO: fill this after USDC and USDT are deployed
 // pub const USDC_FA_ADDR: &str = "";
 // pub const USDT_FA_ADDR: &str = "";
+
+/// Limits how many chunks of one storer are written at the same time, unlimited if None
+pub fn new_db_task_limiter(max_concurrent_db_tasks: Option<usize>) -> Arc<Semaphore> {
+    Arc::new(Semaphore::new(
+        max_concurrent_db_tasks.unwrap_or(Semaphore::MAX_PERMITS),
+    ))
+}
+
+/// Record point changes in the points ledger, entries with no points are skipped.
+/// Entries are per event, so unlike user stats they can be written chunk by chunk.
+pub async fn insert_points_ledger_entries(
+    conn: &mut AsyncPgConnection,
+    entries: Vec<PointsLedgerEntry>,
+) -> QueryResult<()> {
+    let entries = entries
+        .into_iter()
+        .filter(|entry| entry.points_delta != 0)
+        .collect::<Vec<_>>();
+    if entries.is_empty() {
+        return Ok(());
+    }
+    insert_into(points_ledger::table)
+        .values(entries)
+        .on_conflict_do_nothing()
+        .execute(conn)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use anyhow::Result;
+use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
+use diesel::{insert_into, QueryResult};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+use crate::{
+    db_models::module_upgrade::ModuleUpgrade,
+    schema::module_upgrades,
+    utils::{database_connection::get_db_connection, database_utils::ArcDbPool},
+};
+
+pub async fn execute_module_upgrades_sql(
+    conn: &mut AsyncPgConnection,
+    upgrades: Vec<ModuleUpgrade>,
+) -> QueryResult<()> {
+    if upgrades.is_empty() {
+        return Ok(());
+    }
+
+    insert_into(module_upgrades::table)
+        .values(upgrades)
+        .on_conflict(module_upgrades::txn_version)
+        .do_nothing()
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Upgrades are rare, they are inserted in one query without chunking
+pub async fn process_module_upgrades(
+    pool: ArcDbPool,
+    upgrades: Vec<ModuleUpgrade>,
+) -> Result<(), ProcessorError> {
+    if upgrades.is_empty() {
+        return Ok(());
+    }
+
+    let conn = &mut get_db_connection(&pool).await?;
+    execute_module_upgrades_sql(conn, upgrades)
+        .await
+        .map_err(|e| {
+            tracing::warn!("Error running query: {:?}", e);
+            ProcessorError::ProcessError {
+                message: format!("Error running query: {:?}", e),
+            }
+        })
+}
@@ -0,0 +1,48 @@
+use anyhow::Result;
+use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
+use diesel::{insert_into, QueryResult};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+use crate::{
+    db_models::unhandled_event::UnhandledEvent,
+    schema::unhandled_events,
+    utils::{database_connection::get_db_connection, database_utils::ArcDbPool},
+};
+
+pub async fn execute_unhandled_events_sql(
+    conn: &mut AsyncPgConnection,
+    events: Vec<UnhandledEvent>,
+) -> QueryResult<()> {
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    insert_into(unhandled_events::table)
+        .values(events)
+        .on_conflict((unhandled_events::txn_version, unhandled_events::event_idx))
+        .do_nothing()
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Keeps events no decoder knows so they can be processed after adding one, instead of being lost
+/// or stopping the indexer
+pub async fn process_unhandled_events(
+    pool: ArcDbPool,
+    events: Vec<UnhandledEvent>,
+) -> Result<(), ProcessorError> {
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    let conn = &mut get_db_connection(&pool).await?;
+    execute_unhandled_events_sql(conn, events)
+        .await
+        .map_err(|e| {
+            tracing::warn!("Error running query: {:?}", e);
+            ProcessorError::ProcessError {
+                message: format!("Error running query: {:?}", e),
+            }
+        })
+}
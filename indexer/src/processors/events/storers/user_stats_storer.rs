@@ -0,0 +1,170 @@
+use ahash::AHashMap;
+use anyhow::Result;
+use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
+use diesel::{insert_into, upsert::excluded, ExpressionMethods, QueryResult};
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use std::cmp;
+
+use super::{greatest, least};
+use crate::{
+    db_models::user_stat::UserStat,
+    schema::user_stats,
+    utils::{
+        database_connection::get_db_connection,
+        database_utils::{get_config_table_chunk_size, ArcDbPool},
+    },
+};
+
+/// Sum the changes of every event in a batch into one change per user. Applying the reduced
+/// changes once means a user's delta is counted exactly once however the events are chunked.
+pub fn reduce_user_stat_changes(changes: impl IntoIterator<Item = UserStat>) -> Vec<UserStat> {
+    let mut reduced: AHashMap<String, UserStat> = AHashMap::new();
+    for change in changes {
+        let Some(stat) = reduced.get_mut(&change.user_addr) else {
+            reduced.insert(change.user_addr.clone(), change);
+            continue;
+        };
+        stat.create_timestamp = cmp::min(stat.create_timestamp, change.create_timestamp);
+        stat.last_update_timestamp =
+            cmp::max(stat.last_update_timestamp, change.last_update_timestamp);
+        stat.bounty_created += change.bounty_created;
+        stat.apt_spent += change.apt_spent;
+        stat.stable_spent += change.stable_spent;
+        stat.build_created += change.build_created;
+        stat.build_submitted_for_review += change.build_submitted_for_review;
+        stat.build_canceled += change.build_canceled;
+        stat.build_completed += change.build_completed;
+        stat.apt_received += change.apt_received;
+        stat.stable_received += change.stable_received;
+        stat.season_1_points += change.season_1_points;
+        stat.total_points += change.total_points;
+    }
+    reduced.into_values().collect()
+}
+
+/// Expects reduced changes, i.e. at most one per user
+pub async fn execute_user_stats_sql(
+    conn: &mut AsyncPgConnection,
+    changes: Vec<UserStat>,
+) -> QueryResult<()> {
+    /*
+    DO NOT backfill data (i.e. process same event twice), you would mess up the user stat!!!!
+    Instead, if you want to change the point calculation logic, you should delete all data and re-index from scratch.
+    You can delete all data by revert all DB migrations, see README.md for more details.
+     */
+    let update_user_stat_query = insert_into(user_stats::table)
+        .values(changes)
+        .on_conflict(user_stats::user_addr)
+        .do_update()
+        .set((
+            user_stats::user_addr.eq(user_stats::user_addr),
+            // Keep the earliest timestamp, it breaks ties on the leaderboard
+            user_stats::create_timestamp.eq(least(
+                user_stats::create_timestamp,
+                excluded(user_stats::create_timestamp),
+            )),
+            user_stats::last_update_timestamp.eq(greatest(
+                user_stats::last_update_timestamp,
+                excluded(user_stats::last_update_timestamp),
+            )),
+            user_stats::season_1_points
+                .eq(user_stats::season_1_points + excluded(user_stats::season_1_points)),
+            user_stats::total_points
+                .eq(user_stats::total_points + excluded(user_stats::total_points)),
+            user_stats::bounty_created
+                .eq(user_stats::bounty_created + excluded(user_stats::bounty_created)),
+            user_stats::apt_spent.eq(user_stats::apt_spent + excluded(user_stats::apt_spent)),
+            user_stats::stable_spent
+                .eq(user_stats::stable_spent + excluded(user_stats::stable_spent)),
+            user_stats::build_created
+                .eq(user_stats::build_created + excluded(user_stats::build_created)),
+            user_stats::build_submitted_for_review.eq(user_stats::build_submitted_for_review
+                + excluded(user_stats::build_submitted_for_review)),
+            user_stats::build_canceled
+                .eq(user_stats::build_canceled + excluded(user_stats::build_canceled)),
+            user_stats::build_completed
+                .eq(user_stats::build_completed + excluded(user_stats::build_completed)),
+            user_stats::apt_received
+                .eq(user_stats::apt_received + excluded(user_stats::apt_received)),
+            user_stats::stable_received
+                .eq(user_stats::stable_received + excluded(user_stats::stable_received)),
+        ));
+    update_user_stat_query.execute(conn).await?;
+    Ok(())
+}
+
+/// Must run once per batch with the changes of all its events, see reduce_user_stat_changes.
+/// Every user appears in one chunk only, chunks are applied in one transaction.
+pub async fn process_user_stats(
+    pool: ArcDbPool,
+    per_table_chunk_sizes: AHashMap<String, usize>,
+    changes: Vec<UserStat>,
+) -> Result<(), ProcessorError> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let chunk_size = get_config_table_chunk_size::<UserStat>("user_stats", &per_table_chunk_sizes);
+    let conn = &mut get_db_connection(&pool).await?;
+    conn.transaction(|conn| {
+        Box::pin(async move {
+            for chunk in changes.chunks(chunk_size) {
+                execute_user_stats_sql(conn, chunk.to_vec()).await?;
+            }
+            Ok(())
+        })
+    })
+    .await
+    .map_err(|e: diesel::result::Error| {
+        tracing::warn!("Error running query: {:?}", e);
+        ProcessorError::ProcessError {
+            message: format!("Error running query: {:?}", e),
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bigdecimal::BigDecimal;
+
+    #[test]
+    fn test_reduce_user_stat_changes() {
+        let mut changes = reduce_user_stat_changes(vec![
+            UserStat {
+                bounty_created: 1,
+                apt_spent: BigDecimal::from(500),
+                season_1_points: 3,
+                total_points: 3,
+                ..UserStat::new_change("0x1", 200)
+            },
+            UserStat {
+                build_created: 1,
+                season_1_points: 1,
+                total_points: 1,
+                ..UserStat::new_change("0x2", 300)
+            },
+            UserStat {
+                apt_spent: BigDecimal::from(-100),
+                ..UserStat::new_change("0x1", 100)
+            },
+            UserStat {
+                bounty_created: 1,
+                season_1_points: 3,
+                total_points: 3,
+                ..UserStat::new_change("0x1", 400)
+            },
+        ]);
+        changes.sort_by(|a, b| a.user_addr.cmp(&b.user_addr));
+        assert_eq!(changes.len(), 2);
+        let stat = &changes[0];
+        assert_eq!(stat.user_addr, "0x1");
+        assert_eq!(stat.create_timestamp, 100);
+        assert_eq!(stat.last_update_timestamp, 400);
+        assert_eq!(stat.bounty_created, 2);
+        assert_eq!(stat.apt_spent, BigDecimal::from(400));
+        assert_eq!(stat.season_1_points, 6);
+        assert_eq!(stat.total_points, 6);
+        assert_eq!(changes[1].build_created, 1);
+    }
+}
@@ -1,70 +1,202 @@
+use anyhow::{bail, Context, Result};
 use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
-use diesel::ConnectionResult;
+use diesel::{result::ConnectionError, ConnectionResult};
 use diesel_async::{
     pooled_connection::{bb8::Pool, AsyncDieselConnectionManager, ManagerConfig},
     AsyncPgConnection,
 };
 use futures_util::{future::BoxFuture, FutureExt};
+use native_tls::{Certificate, Identity, TlsConnector};
+use postgres_native_tls::MakeTlsConnector;
 use std::sync::Arc;
+use tokio_postgres::{
+    config::SslMode,
+    tls::{MakeTlsConnect, TlsConnect},
+    Client, NoTls, Socket,
+};
 
 use super::database_utils::{ArcDbPool, DbPoolConnection};
 
-fn establish_connection(database_url: &str) -> BoxFuture<ConnectionResult<AsyncPgConnection>> {
-    use native_tls::{Certificate, TlsConnector};
-    use postgres_native_tls::MakeTlsConnector;
+/// sslmode values of libpq. tokio_postgres only knows disable, prefer and require and never
+/// checks certificates itself, so verification is configured on the TLS connector instead.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PgSslMode {
+    Disable,
+    Prefer,
+    Require,
+    VerifyCa,
+    VerifyFull,
+}
 
-    (async move {
-        let (url, cert_path) = parse_and_clean_db_url(database_url);
-        let connector = match cert_path {
-            Some(cert_path) => {
-                let cert = std::fs::read(cert_path).expect("Could not read certificate");
-
-                let cert = Certificate::from_pem(&cert).expect("Could not parse certificate");
-                let connector = TlsConnector::builder()
-                    .danger_accept_invalid_certs(true)
-                    .add_root_certificate(cert)
-                    .build()
-                    .expect("Could not build TLS connector");
-                MakeTlsConnector::new(connector)
-            }
-            None => {
-                let connector = TlsConnector::builder()
-                    .build()
-                    .expect("Could not build default TLS connector");
-                MakeTlsConnector::new(connector)
-            }
-        };
-        let (client, connection) = tokio_postgres::connect(&url, connector)
-            .await
-            .expect("Could not connect to database");
-        tokio::spawn(async move {
-            if let Err(e) = connection.await {
-                eprintln!("connection error: {}", e);
+impl PgSslMode {
+    fn parse(value: &str) -> Result<Self> {
+        match value {
+            "disable" => Ok(Self::Disable),
+            "prefer" => Ok(Self::Prefer),
+            "require" => Ok(Self::Require),
+            "verify-ca" => Ok(Self::VerifyCa),
+            "verify-full" => Ok(Self::VerifyFull),
+            _ => bail!(
+                "Unsupported sslmode {}, expected disable, prefer, require, verify-ca or verify-full",
+                value
+            ),
+        }
+    }
+
+    fn to_tokio_postgres(self) -> SslMode {
+        match self {
+            Self::Disable => SslMode::Disable,
+            Self::Prefer => SslMode::Prefer,
+            Self::Require | Self::VerifyCa | Self::VerifyFull => SslMode::Require,
+        }
+    }
+
+    /// Like libpq, require checks the certificate chain when a root certificate is given
+    fn verifies_ca(self, has_root_cert: bool) -> bool {
+        match self {
+            Self::Disable | Self::Prefer => false,
+            Self::Require => has_root_cert,
+            Self::VerifyCa | Self::VerifyFull => true,
+        }
+    }
+}
+
+/// TLS parameters of the database url, the rest of the url is passed to tokio_postgres
+#[derive(Debug, PartialEq)]
+struct PgConnectionParams {
+    url: String,
+    ssl_mode: PgSslMode,
+    // CA certificate (PEM) the server certificate must chain to, system roots if unset
+    root_cert_path: Option<String>,
+    // Client certificate (PEM) and its PKCS#8 key (PEM) for certificate authentication
+    client_cert_path: Option<String>,
+    client_key_path: Option<String>,
+}
+
+fn parse_db_url(database_url: &str) -> Result<PgConnectionParams> {
+    let mut db_url = url::Url::parse(database_url).context("Could not parse database url")?;
+    let mut params = PgConnectionParams {
+        url: String::new(),
+        // Same default as libpq
+        ssl_mode: PgSslMode::Prefer,
+        root_cert_path: None,
+        client_cert_path: None,
+        client_key_path: None,
+    };
+
+    let query_pairs = db_url.query_pairs().into_owned().collect::<Vec<_>>();
+    let mut query = url::form_urlencoded::Serializer::new(String::new());
+    for (k, v) in query_pairs {
+        match k.as_str() {
+            "sslmode" => params.ssl_mode = PgSslMode::parse(&v)?,
+            "sslrootcert" => params.root_cert_path = Some(v),
+            "sslcert" => params.client_cert_path = Some(v),
+            "sslkey" => params.client_key_path = Some(v),
+            _ => {
+                query.append_pair(&k, &v);
             }
-        });
-        AsyncPgConnection::try_from(client).await
-    })
-    .boxed()
+        }
+    }
+    let query = query.finish();
+    db_url.set_query((!query.is_empty()).then_some(query.as_str()));
+
+    if params.client_cert_path.is_some() != params.client_key_path.is_some() {
+        bail!("sslcert and sslkey must be set together");
+    }
+    if params.ssl_mode == PgSslMode::Disable
+        && (params.root_cert_path.is_some() || params.client_cert_path.is_some())
+    {
+        bail!("sslrootcert, sslcert and sslkey can't be used with sslmode=disable");
+    }
+    params.url = db_url.to_string();
+    Ok(params)
+}
+
+fn read_file(path: &str, param: &str) -> Result<Vec<u8>> {
+    std::fs::read(path).with_context(|| format!("Could not read {} {}", param, path))
 }
 
-fn parse_and_clean_db_url(url: &str) -> (String, Option<String>) {
-    let mut db_url = url::Url::parse(url).expect("Could not parse database url");
-    let mut cert_path = None;
+fn build_tls_connector(params: &PgConnectionParams) -> Result<MakeTlsConnector> {
+    let mut builder = TlsConnector::builder();
+    if let Some(root_cert_path) = &params.root_cert_path {
+        let cert = Certificate::from_pem(&read_file(root_cert_path, "sslrootcert")?)
+            .with_context(|| format!("Could not parse sslrootcert {}", root_cert_path))?;
+        builder.add_root_certificate(cert);
+    }
+    if let (Some(client_cert_path), Some(client_key_path)) =
+        (&params.client_cert_path, &params.client_key_path)
+    {
+        let identity = Identity::from_pkcs8(
+            &read_file(client_cert_path, "sslcert")?,
+            &read_file(client_key_path, "sslkey")?,
+        )
+        .context("Could not load the client certificate, sslkey must be a PKCS#8 PEM key")?;
+        builder.identity(identity);
+    }
+
+    let verifies_ca = params.ssl_mode.verifies_ca(params.root_cert_path.is_some());
+    builder
+        .danger_accept_invalid_certs(!verifies_ca)
+        .danger_accept_invalid_hostnames(params.ssl_mode != PgSslMode::VerifyFull);
+    let connector = builder.build().context("Could not build TLS connector")?;
+    Ok(MakeTlsConnector::new(connector))
+}
 
-    let mut query = "".to_string();
-    db_url.query_pairs().for_each(|(k, v)| {
-        if k == "sslrootcert" {
-            cert_path = Some(v.parse().unwrap());
-        } else {
-            query.push_str(&format!("{}={}&", k, v));
+async fn connect<T>(
+    pg_config: &tokio_postgres::Config,
+    tls: T,
+) -> Result<Client, tokio_postgres::Error>
+where
+    T: MakeTlsConnect<Socket> + Send,
+    T::Stream: Send + 'static,
+    T::TlsConnect: Send,
+    <T::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    let (client, connection) = pg_config.connect(tls).await?;
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            tracing::error!("Database connection error: {}", e);
         }
     });
-    db_url.set_query(Some(&query));
+    Ok(client)
+}
+
+/// Certificates and keys are read here, the TLS connector is None for sslmode=disable
+fn new_pg_config(database_url: &str) -> Result<(tokio_postgres::Config, Option<MakeTlsConnector>)> {
+    let params = parse_db_url(database_url)?;
+    let tls_connector = match params.ssl_mode {
+        PgSslMode::Disable => None,
+        _ => Some(build_tls_connector(&params)?),
+    };
+    let mut pg_config = params
+        .url
+        .parse::<tokio_postgres::Config>()
+        .context("Could not parse database url")?;
+    pg_config.ssl_mode(params.ssl_mode.to_tokio_postgres());
+    Ok((pg_config, tls_connector))
+}
 
-    (db_url.to_string(), cert_path)
+fn establish_connection(database_url: &str) -> BoxFuture<ConnectionResult<AsyncPgConnection>> {
+    (async move {
+        let (pg_config, tls_connector) = new_pg_config(database_url)
+            .map_err(|e| ConnectionError::BadConnection(format!("{:#}", e)))?;
+        let client = match tls_connector {
+            Some(tls_connector) => connect(&pg_config, tls_connector).await,
+            None => connect(&pg_config, NoTls).await,
+        }
+        .map_err(|e| {
+            ConnectionError::BadConnection(format!("Could not connect to database: {}", e))
+        })?;
+        AsyncPgConnection::try_from(client).await
+    })
+    .boxed()
 }
 
-pub async fn new_db_pool(database_url: &str, max_pool_size: u32) -> ArcDbPool {
+/// The database url accepts the TLS parameters of libpq: sslmode, sslrootcert, sslcert and sslkey.
+pub async fn new_db_pool(database_url: &str, max_pool_size: u32) -> Result<ArcDbPool> {
+    // Fail at startup on a bad url or certificate rather than on every connection attempt
+    new_pg_config(database_url)?;
+
     let mut config = ManagerConfig::<AsyncPgConnection>::default();
     config.custom_setup = Box::new(|conn| Box::pin(establish_connection(conn)));
     let manager =
@@ -74,9 +206,14 @@ pub async fn new_db_pool(database_url: &str, max_pool_size: u32) -> ArcDbPool {
         .max_size(max_pool_size)
         .build(manager)
         .await
-        .expect("Failed to create db pool");
+        .context("Failed to create db pool")?;
+
+    Ok(Arc::new(pool))
+}
 
-    Arc::new(pool)
+/// A connection outside of the pool, with the same TLS setup as pooled connections
+pub async fn new_db_connection(database_url: &str) -> Result<AsyncPgConnection> {
+    Ok(establish_connection(database_url).await?)
 }
 
 pub async fn get_db_connection(pool: &ArcDbPool) -> Result<DbPoolConnection, ProcessorError> {
@@ -87,3 +224,47 @@ pub async fn get_db_connection(pool: &ArcDbPool) -> Result<DbPoolConnection, Pro
         }
     })
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_db_url() {
+        let params = parse_db_url(
+            "postgresql://user:pw@localhost:5432/db?sslmode=verify-full&sslrootcert=/ca.pem&application_name=indexer",
+        )
+        .unwrap();
+        assert_eq!(
+            params,
+            PgConnectionParams {
+                url: "postgresql://user:pw@localhost:5432/db?application_name=indexer".to_string(),
+                ssl_mode: PgSslMode::VerifyFull,
+                root_cert_path: Some("/ca.pem".to_string()),
+                client_cert_path: None,
+                client_key_path: None,
+            }
+        );
+
+        let params = parse_db_url("postgresql://user:pw@localhost:5432/db").unwrap();
+        assert_eq!(params.url, "postgresql://user:pw@localhost:5432/db");
+        assert_eq!(params.ssl_mode, PgSslMode::Prefer);
+    }
+
+    #[test]
+    fn test_parse_invalid_db_url() {
+        assert!(parse_db_url("postgresql://localhost/db?sslmode=allow").is_err());
+        assert!(parse_db_url("postgresql://localhost/db?sslcert=/client.pem").is_err());
+        assert!(
+            parse_db_url("postgresql://localhost/db?sslmode=disable&sslrootcert=/ca.pem").is_err()
+        );
+    }
+
+    #[test]
+    fn test_certificate_verification() {
+        assert!(!PgSslMode::Prefer.verifies_ca(true));
+        assert!(!PgSslMode::Require.verifies_ca(false));
+        assert!(PgSslMode::Require.verifies_ca(true));
+        assert!(PgSslMode::VerifyCa.verifies_ca(false));
+    }
+}
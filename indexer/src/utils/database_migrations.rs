@@ -0,0 +1,118 @@
+//! The migrations in src/db_migrations/migrations are embedded in the binary, so a deployed
+//! indexer can apply them itself with `indexer migrate up` or on startup.
+
+use anyhow::{anyhow, bail, Result};
+use diesel::{migration::MigrationSource, pg::Pg};
+use diesel_async::{async_connection_wrapper::AsyncConnectionWrapper, AsyncPgConnection};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+
+use super::database_connection::new_db_connection;
+
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("src/db_migrations/migrations");
+
+type MigrationConnection = AsyncConnectionWrapper<AsyncPgConnection>;
+
+/// MigrationHarness is blocking, run it on a dedicated connection on a blocking thread
+async fn with_migration_connection<R, F>(database_url: &str, f: F) -> Result<R>
+where
+    R: Send + 'static,
+    F: FnOnce(&mut MigrationConnection) -> Result<R> + Send + 'static,
+{
+    let conn = new_db_connection(database_url).await?;
+    tokio::task::spawn_blocking(move || f(&mut MigrationConnection::from(conn))).await?
+}
+
+fn pending_migration_names(conn: &mut MigrationConnection) -> Result<Vec<String>> {
+    Ok(conn
+        .pending_migrations(MIGRATIONS)
+        .map_err(|e| anyhow!(e))?
+        .iter()
+        .map(|migration| migration.name().to_string())
+        .collect())
+}
+
+/// Names of the embedded migrations not applied to the database yet
+pub async fn get_pending_migrations(database_url: &str) -> Result<Vec<String>> {
+    with_migration_connection(database_url, pending_migration_names).await
+}
+
+/// Returns the versions that were applied
+pub async fn run_pending_migrations(database_url: &str) -> Result<Vec<String>> {
+    with_migration_connection(database_url, |conn| {
+        let versions = conn
+            .run_pending_migrations(MIGRATIONS)
+            .map_err(|e| anyhow!(e))?;
+        Ok(versions.iter().map(|version| version.to_string()).collect())
+    })
+    .await
+}
+
+/// Reverts the last applied migration, or all of them which drops every indexed table.
+/// Returns the versions that were reverted.
+pub async fn revert_migrations(database_url: &str, all: bool) -> Result<Vec<String>> {
+    with_migration_connection(database_url, move |conn| {
+        let versions = if all {
+            conn.revert_all_migrations(MIGRATIONS)
+                .map_err(|e| anyhow!(e))?
+        } else {
+            vec![conn
+                .revert_last_migration(MIGRATIONS)
+                .map_err(|e| anyhow!(e))?]
+        };
+        Ok(versions.iter().map(|version| version.to_string()).collect())
+    })
+    .await
+}
+
+/// Print every embedded migration and whether it is applied, like `diesel migration list`
+pub async fn print_migration_status(database_url: &str) -> Result<()> {
+    with_migration_connection(database_url, |conn| {
+        let applied = conn
+            .applied_migrations()
+            .map_err(|e| anyhow!(e))?
+            .iter()
+            .map(|version| version.to_string())
+            .collect::<Vec<_>>();
+        let embedded = MigrationSource::<Pg>::migrations(&MIGRATIONS).map_err(|e| anyhow!(e))?;
+        for migration in &embedded {
+            let is_applied = applied.contains(&migration.name().version().to_string());
+            println!(
+                "[{}] {}",
+                if is_applied { "X" } else { " " },
+                migration.name()
+            );
+        }
+
+        let embedded_versions = embedded
+            .iter()
+            .map(|migration| migration.name().version().to_string())
+            .collect::<Vec<_>>();
+        for version in applied.iter().filter(|v| !embedded_versions.contains(v)) {
+            println!("[?] {} is applied but unknown to this binary", version);
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// Refuse to index into a schema older than the binary, the queries would fail on missing
+/// tables or columns. With run_pending set, the pending migrations are applied instead.
+pub async fn check_or_run_pending_migrations(database_url: &str, run_pending: bool) -> Result<()> {
+    with_migration_connection(database_url, move |conn| {
+        let pending = pending_migration_names(conn)?;
+        if pending.is_empty() {
+            return Ok(());
+        }
+        if !run_pending {
+            bail!(
+                "Database schema is behind the indexer, pending migrations: {}. Run `indexer migrate up` or set db_config.run_migrations_on_startup",
+                pending.join(", ")
+            );
+        }
+        conn.run_pending_migrations(MIGRATIONS)
+            .map_err(|e| anyhow!(e))?;
+        tracing::info!("Applied migrations: {}", pending.join(", "));
+        Ok(())
+    })
+    .await
+}
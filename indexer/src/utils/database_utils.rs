@@ -3,8 +3,11 @@ use diesel_async::{
     pooled_connection::bb8::{Pool, PooledConnection},
     AsyncPgConnection,
 };
+use field_count::FieldCount;
 use std::sync::Arc;
 
+use crate::db_models::{bounty::Bounty, build::Build, user_stat::UserStat};
+
 pub type DbPool = Pool<AsyncPgConnection>;
 pub type ArcDbPool = Arc<DbPool>;
 pub type DbPoolConnection<'a> = PooledConnection<'a, AsyncPgConnection>;
@@ -15,7 +18,7 @@ const MAX_DIESEL_PARAM_SIZE: usize = (u16::MAX / 2) as usize;
 /// Returns the entry for the config hashmap, or the default field count for the insert
 /// Given diesel has a limit of how many parameters can be inserted in a single operation (u16::MAX),
 /// we default to chunk an array of items based on how many columns are in the table.
-pub fn get_config_table_chunk_size<T: field_count::FieldCount>(
+pub fn get_config_table_chunk_size<T: FieldCount>(
     table_name: &str,
     per_table_chunk_sizes: &AHashMap<String, usize>,
 ) -> usize {
@@ -24,3 +27,14 @@ pub fn get_config_table_chunk_size<T: field_count::FieldCount>(
         .copied()
         .unwrap_or_else(|| MAX_DIESEL_PARAM_SIZE / T::field_count())
 }
+
+/// Most rows of the table an insert can take, None for tables the storers don't chunk
+pub fn get_max_table_chunk_size(table_name: &str) -> Option<usize> {
+    let field_count = match table_name {
+        "bounties" => Bounty::field_count(),
+        "builds" => Build::field_count(),
+        "user_stats" => UserStat::field_count(),
+        _ => return None,
+    };
+    Some(MAX_DIESEL_PARAM_SIZE / field_count)
+}
@@ -6,7 +6,8 @@ use aptos_indexer_processor_sdk::{
     utils::{errors::ProcessorError, time::parse_timestamp},
 };
 use async_trait::async_trait;
-use diesel::{query_dsl::methods::FilterDsl, upsert::excluded, ExpressionMethods};
+use diesel::{query_dsl::methods::FilterDsl, upsert::excluded, ExpressionMethods, QueryResult};
+use diesel_async::AsyncPgConnection;
 
 use super::{
     database_connection::{get_db_connection, new_db_pool},
@@ -49,7 +50,7 @@ where
             &db_config.postgres_connection_string,
             db_config.db_pool_size,
         )
-        .await;
+        .await?;
         Ok(Self {
             pool,
             tracker_name,
@@ -73,42 +74,59 @@ where
     async fn save_processor_status(&mut self) -> Result<(), ProcessorError> {
         // Update the processor status
         if let Some(last_success_batch) = self.last_success_batch.as_ref() {
-            let end_timestamp = last_success_batch
-                .end_transaction_timestamp
-                .as_ref()
-                .map(|t| parse_timestamp(t, last_success_batch.end_version as i64))
-                .map(|t| t.naive_utc());
-            let status = ProcessorStatus {
-                processor: self.tracker_name.clone(),
-                last_success_version: last_success_batch.end_version as i64,
-                last_transaction_timestamp: end_timestamp,
-            };
-            let query = diesel::insert_into(processor_status::table)
-                .values(&status)
-                .on_conflict(processor_status::processor)
-                .do_update()
-                .set((
-                    processor_status::last_success_version
-                        .eq(excluded(processor_status::last_success_version)),
-                    processor_status::last_updated.eq(excluded(processor_status::last_updated)),
-                    processor_status::last_transaction_timestamp
-                        .eq(excluded(processor_status::last_transaction_timestamp)),
-                ))
-                .filter(
-                    processor_status::last_success_version
-                        .lt(excluded(processor_status::last_success_version)),
-                );
+            let status = new_processor_status(self.tracker_name.clone(), last_success_batch);
             let conn = &mut get_db_connection(&self.pool).await?;
-            execute_with_better_error(conn, vec![query])
-                .await
-                .map_err(|e| ProcessorError::DBStoreError {
+            upsert_processor_status(conn, &status).await.map_err(|e| {
+                ProcessorError::DBStoreError {
                     message: format!("Failed to update processor status: {}", e),
-                })?;
+                }
+            })?;
         }
         Ok(())
     }
 }
 
+/// Processor status after successfully processing the given batch
+pub fn new_processor_status<T>(
+    tracker_name: String,
+    batch: &TransactionContext<T>,
+) -> ProcessorStatus {
+    let end_timestamp = batch
+        .end_transaction_timestamp
+        .as_ref()
+        .map(|t| parse_timestamp(t, batch.end_version as i64))
+        .map(|t| t.naive_utc());
+    ProcessorStatus {
+        processor: tracker_name,
+        last_success_version: batch.end_version as i64,
+        last_transaction_timestamp: end_timestamp,
+    }
+}
+
+/// Never moves the processor status backwards, so the tracker and the events storer can both
+/// write it when batches are committed atomically.
+pub async fn upsert_processor_status(
+    conn: &mut AsyncPgConnection,
+    status: &ProcessorStatus,
+) -> QueryResult<()> {
+    let query = diesel::insert_into(processor_status::table)
+        .values(status)
+        .on_conflict(processor_status::processor)
+        .do_update()
+        .set((
+            processor_status::last_success_version
+                .eq(excluded(processor_status::last_success_version)),
+            processor_status::last_updated.eq(excluded(processor_status::last_updated)),
+            processor_status::last_transaction_timestamp
+                .eq(excluded(processor_status::last_transaction_timestamp)),
+        ))
+        .filter(
+            processor_status::last_success_version
+                .lt(excluded(processor_status::last_success_version)),
+        );
+    execute_with_better_error(conn, vec![query]).await
+}
+
 #[async_trait]
 impl<T> Processable for LatestVersionProcessedTracker<T>
 where
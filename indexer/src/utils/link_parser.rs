@@ -0,0 +1,339 @@
+use url::Url;
+
+pub const LINK_KIND_GITHUB_PR: &str = "github_pr";
+pub const LINK_KIND_GITHUB_COMMIT: &str = "github_commit";
+pub const LINK_KIND_GITHUB_REPO: &str = "github_repo";
+pub const LINK_KIND_GITHUB_GIST: &str = "github_gist";
+pub const LINK_KIND_TWEET: &str = "tweet";
+pub const LINK_KIND_OTHER: &str = "other";
+
+pub const CONTACT_TYPE_DISCORD: &str = "discord";
+pub const CONTACT_TYPE_X: &str = "x";
+pub const CONTACT_TYPE_TELEGRAM: &str = "telegram";
+pub const CONTACT_TYPE_EMAIL: &str = "email";
+pub const CONTACT_TYPE_OTHER: &str = "other";
+
+/// Structured form of a build's proof link
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParsedProofLink {
+    pub kind: &'static str,
+    // Lowercase, without www.
+    pub host: Option<String>,
+    // GitHub owner, or X user for tweets
+    pub owner: Option<String>,
+    pub repo: Option<String>,
+    pub pr_number: Option<i64>,
+    // Same proof submitted in different ways (http/https, trailing slash, query string, PR
+    // sub pages) normalizes to the same value, used to find proofs reused across bounties
+    pub normalized: Option<String>,
+}
+
+/// Structured form of a bounty's contact info
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParsedContactInfo {
+    pub handle_type: &'static str,
+    // Without the @ prefix, lowercase for email
+    pub handle: Option<String>,
+}
+
+fn parse_url(link: &str) -> Option<Url> {
+    let link = link.trim();
+    if link.is_empty() {
+        return None;
+    }
+    // People often omit the scheme, e.g. github.com/owner/repo/pull/1
+    let url = Url::parse(link)
+        .ok()
+        .or_else(|| Url::parse(&format!("https://{}", link)).ok())?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return None;
+    }
+    url.host_str()?.contains('.').then_some(url)
+}
+
+fn get_host(url: &Url) -> String {
+    let host = url.host_str().unwrap_or_default().to_lowercase();
+    host.strip_prefix("www.")
+        .map(str::to_string)
+        .unwrap_or(host)
+}
+
+fn get_path_segments(url: &Url) -> Vec<&str> {
+    url.path_segments()
+        .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+pub fn parse_proof_link(proof_link: &str) -> ParsedProofLink {
+    let url = match parse_url(proof_link) {
+        Some(url) => url,
+        None => {
+            return ParsedProofLink {
+                kind: LINK_KIND_OTHER,
+                ..Default::default()
+            }
+        }
+    };
+    let host = get_host(&url);
+    let segments = get_path_segments(&url);
+    let parsed = match (host.as_str(), segments.as_slice()) {
+        ("github.com", [owner, repo, "pull", number, ..]) if number.parse::<i64>().is_ok() => {
+            ParsedProofLink {
+                kind: LINK_KIND_GITHUB_PR,
+                owner: Some(owner.to_lowercase()),
+                repo: Some(repo.to_lowercase()),
+                pr_number: number.parse().ok(),
+                ..Default::default()
+            }
+        }
+        ("github.com", [owner, repo, "commit", sha, ..]) => ParsedProofLink {
+            kind: LINK_KIND_GITHUB_COMMIT,
+            owner: Some(owner.to_lowercase()),
+            repo: Some(repo.to_lowercase()),
+            normalized: Some(format!(
+                "github.com/{}/{}/commit/{}",
+                owner.to_lowercase(),
+                repo.to_lowercase(),
+                sha.to_lowercase()
+            )),
+            ..Default::default()
+        },
+        ("github.com", [owner, repo, ..]) => ParsedProofLink {
+            kind: LINK_KIND_GITHUB_REPO,
+            owner: Some(owner.to_lowercase()),
+            repo: Some(repo.trim_end_matches(".git").to_lowercase()),
+            ..Default::default()
+        },
+        ("gist.github.com", [owner, ..]) => ParsedProofLink {
+            kind: LINK_KIND_GITHUB_GIST,
+            owner: Some(owner.to_lowercase()),
+            ..Default::default()
+        },
+        ("x.com" | "twitter.com" | "mobile.twitter.com", [user, "status", id, ..]) => {
+            ParsedProofLink {
+                kind: LINK_KIND_TWEET,
+                owner: Some(user.to_lowercase()),
+                // Tweet ids are unique across users
+                normalized: Some(format!("x.com/i/status/{}", id)),
+                ..Default::default()
+            }
+        }
+        _ => ParsedProofLink {
+            kind: LINK_KIND_OTHER,
+            ..Default::default()
+        },
+    };
+    let normalized = parsed
+        .normalized
+        .clone()
+        .unwrap_or_else(|| match parsed.kind {
+            LINK_KIND_GITHUB_PR => format!(
+                "github.com/{}/{}/pull/{}",
+                parsed.owner.as_deref().unwrap_or_default(),
+                parsed.repo.as_deref().unwrap_or_default(),
+                parsed.pr_number.unwrap_or_default()
+            ),
+            LINK_KIND_GITHUB_REPO => format!(
+                "github.com/{}/{}",
+                parsed.owner.as_deref().unwrap_or_default(),
+                parsed.repo.as_deref().unwrap_or_default()
+            ),
+            _ => format!("{}/{}", host, segments.join("/"))
+                .trim_end_matches('/')
+                .to_string(),
+        });
+    ParsedProofLink {
+        host: Some(host),
+        normalized: Some(normalized),
+        ..parsed
+    }
+}
+
+/// Contact info is free text limited to 100 chars, we recognize a URL, a `label: handle` prefix,
+/// an email address or a legacy Discord tag like name#1234.
+pub fn parse_contact_info(contact_info: &str) -> ParsedContactInfo {
+    let contact_info = contact_info.trim();
+    if contact_info.is_empty() {
+        return ParsedContactInfo {
+            handle_type: CONTACT_TYPE_OTHER,
+            handle: None,
+        };
+    }
+
+    if contact_info.contains('/') {
+        if let Some(url) = parse_url(contact_info) {
+            let handle_type = match get_host(&url).as_str() {
+                "x.com" | "twitter.com" | "mobile.twitter.com" => CONTACT_TYPE_X,
+                "t.me" | "telegram.me" => CONTACT_TYPE_TELEGRAM,
+                "discord.com" | "discord.gg" | "discordapp.com" => CONTACT_TYPE_DISCORD,
+                _ => CONTACT_TYPE_OTHER,
+            };
+            let handle = get_path_segments(&url)
+                .last()
+                .map(|handle| handle.trim_start_matches('@').to_string());
+            return ParsedContactInfo {
+                handle_type,
+                handle,
+            };
+        }
+    }
+
+    if let Some((label, handle)) = contact_info.split_once(':') {
+        let handle_type = match label.trim().to_lowercase().as_str() {
+            "discord" | "dc" => Some(CONTACT_TYPE_DISCORD),
+            "x" | "twitter" => Some(CONTACT_TYPE_X),
+            "telegram" | "tg" => Some(CONTACT_TYPE_TELEGRAM),
+            "email" | "e-mail" | "mail" => Some(CONTACT_TYPE_EMAIL),
+            _ => None,
+        };
+        if let Some(handle_type) = handle_type {
+            let handle = handle.trim().trim_start_matches('@');
+            return ParsedContactInfo {
+                handle_type,
+                handle: Some(if handle_type == CONTACT_TYPE_EMAIL {
+                    handle.to_lowercase()
+                } else {
+                    handle.to_string()
+                }),
+            };
+        }
+    }
+
+    if is_email(contact_info) {
+        return ParsedContactInfo {
+            handle_type: CONTACT_TYPE_EMAIL,
+            handle: Some(contact_info.to_lowercase()),
+        };
+    }
+
+    if let Some((name, discriminator)) = contact_info.rsplit_once('#') {
+        if !name.is_empty()
+            && discriminator.len() == 4
+            && discriminator.chars().all(|c| c.is_ascii_digit())
+        {
+            return ParsedContactInfo {
+                handle_type: CONTACT_TYPE_DISCORD,
+                handle: Some(contact_info.to_string()),
+            };
+        }
+    }
+
+    ParsedContactInfo {
+        handle_type: CONTACT_TYPE_OTHER,
+        handle: Some(contact_info.trim_start_matches('@').to_string()),
+    }
+}
+
+fn is_email(text: &str) -> bool {
+    match text.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !text.contains(char::is_whitespace)
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !domain.contains('@')
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_proof_link() {
+        let pr =
+            parse_proof_link("https://www.github.com/Bulsico/Bounty-App/pull/12/files?diff=split");
+        assert_eq!(pr.kind, LINK_KIND_GITHUB_PR);
+        assert_eq!(pr.host.as_deref(), Some("github.com"));
+        assert_eq!(pr.owner.as_deref(), Some("bulsico"));
+        assert_eq!(pr.repo.as_deref(), Some("bounty-app"));
+        assert_eq!(pr.pr_number, Some(12));
+        assert_eq!(
+            pr.normalized,
+            parse_proof_link("github.com/bulsico/bounty-app/pull/12").normalized
+        );
+
+        let commit = parse_proof_link("https://github.com/o/r/commit/ABC123");
+        assert_eq!(commit.kind, LINK_KIND_GITHUB_COMMIT);
+        assert_eq!(
+            commit.normalized.as_deref(),
+            Some("github.com/o/r/commit/abc123")
+        );
+
+        let repo = parse_proof_link("https://github.com/o/r.git");
+        assert_eq!(repo.kind, LINK_KIND_GITHUB_REPO);
+        assert_eq!(repo.normalized.as_deref(), Some("github.com/o/r"));
+        assert_eq!(
+            parse_proof_link("https://gist.github.com/o/1a2b").kind,
+            LINK_KIND_GITHUB_GIST
+        );
+
+        let tweet = parse_proof_link("https://twitter.com/someone/status/1849/photo/1");
+        assert_eq!(tweet.kind, LINK_KIND_TWEET);
+        assert_eq!(tweet.normalized.as_deref(), Some("x.com/i/status/1849"));
+
+        let other = parse_proof_link("https://example.com/demo/");
+        assert_eq!(other.kind, LINK_KIND_OTHER);
+        assert_eq!(other.normalized.as_deref(), Some("example.com/demo"));
+
+        assert_eq!(parse_proof_link("").host, None);
+        assert_eq!(parse_proof_link("see my pr").host, None);
+    }
+
+    #[test]
+    fn test_parse_long_proof_link() {
+        // The contract doesn't limit proof links, nothing is cut off
+        let path = "a/".repeat(2_000);
+        let long = parse_proof_link(&format!("https://example.com/{}", path));
+        assert_eq!(long.kind, LINK_KIND_OTHER);
+        assert_eq!(
+            long.normalized,
+            Some(format!("example.com/{}", path.trim_end_matches('/')))
+        );
+    }
+
+    #[test]
+    fn test_parse_non_ascii_proof_link() {
+        // Percent-encoded, so longer than the link itself
+        let other = parse_proof_link("https://example.com/証明/démo");
+        assert_eq!(other.host.as_deref(), Some("example.com"));
+        assert_eq!(
+            other.normalized.as_deref(),
+            Some("example.com/%E8%A8%BC%E6%98%8E/d%C3%A9mo")
+        );
+        let idn = parse_proof_link("https://bücher.example/x");
+        assert_eq!(idn.host.as_deref(), Some("xn--bcher-kva.example"));
+    }
+
+    #[test]
+    fn test_parse_contact_info() {
+        let cases = [
+            ("https://x.com/@someone", CONTACT_TYPE_X, Some("someone")),
+            ("t.me/someone", CONTACT_TYPE_TELEGRAM, Some("someone")),
+            ("Discord: someone", CONTACT_TYPE_DISCORD, Some("someone")),
+            ("tg: @someone", CONTACT_TYPE_TELEGRAM, Some("someone")),
+            (
+                "Someone@Example.com",
+                CONTACT_TYPE_EMAIL,
+                Some("someone@example.com"),
+            ),
+            ("someone#1234", CONTACT_TYPE_DISCORD, Some("someone#1234")),
+            ("@someone", CONTACT_TYPE_OTHER, Some("someone")),
+            ("", CONTACT_TYPE_OTHER, None),
+        ];
+        for (contact_info, handle_type, handle) in cases {
+            assert_eq!(
+                parse_contact_info(contact_info),
+                ParsedContactInfo {
+                    handle_type,
+                    handle: handle.map(str::to_string),
+                },
+                "{}",
+                contact_info
+            );
+        }
+    }
+}
@@ -1,6 +1,10 @@
 pub mod chain_id;
 pub mod database_connection;
 pub mod database_execution;
+pub mod database_migrations;
 pub mod database_utils;
 pub mod latest_processed_version_tracker;
+pub mod link_parser;
+pub mod object_address;
 pub mod starting_version;
+pub mod transaction_files;
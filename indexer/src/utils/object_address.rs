@@ -0,0 +1,115 @@
+use anyhow::{bail, Context, Result};
+use sha3::{Digest, Sha3_256};
+
+// Mirrors CONFIG_OBJ_SEED in bounty_app.move
+pub const CONFIG_OBJ_SEED: &[u8] = b"CONFIG_OBJ";
+// Domain separator of object::create_object_address in the Aptos framework
+const OBJECT_FROM_SEED_ADDRESS_SCHEME: u8 = 0xFE;
+
+pub(crate) fn parse_address(addr: &str) -> Result<[u8; 32]> {
+    let hex_str = addr.trim().trim_start_matches("0x");
+    if hex_str.is_empty() || hex_str.len() > 64 {
+        bail!("Invalid address: {}", addr);
+    }
+    let mut bytes = [0; 32];
+    hex::decode_to_slice(format!("{:0>64}", hex_str), &mut bytes)
+        .with_context(|| format!("Invalid address: {}", addr))?;
+    Ok(bytes)
+}
+
+/// Same as AccountAddress::to_hex_literal, i.e. what string_utils prints for an address in Move.
+fn to_short_hex_literal(addr: &[u8; 32]) -> String {
+    let hex_str = hex::encode(addr);
+    let trimmed = hex_str.trim_start_matches('0');
+    format!("0x{}", if trimmed.is_empty() { "0" } else { trimmed })
+}
+
+/// Same as object::create_object_address, returns the address in standardized 0x + 64 hex form.
+pub fn create_object_address(source_addr: &str, seed: &[u8]) -> Result<String> {
+    let mut hasher = Sha3_256::new();
+    hasher.update(parse_address(source_addr)?);
+    hasher.update(seed);
+    hasher.update([OBJECT_FROM_SEED_ADDRESS_SCHEME]);
+    Ok(format!("0x{}", hex::encode(hasher.finalize())))
+}
+
+/// Address of the config object, every build object is created under it.
+pub fn get_config_obj_addr(contract_addr: &str) -> Result<String> {
+    create_object_address(contract_addr, CONFIG_OBJ_SEED)
+}
+
+/// Same as construct_build_obj_seed in bounty_app.move, i.e. the BCS bytes of
+/// `string_utils::format2(&b"{}_{}", bounty_addr, builder_addr)` which prints addresses as @0x1.
+pub fn construct_build_obj_seed(bounty_addr: &str, builder_addr: &str) -> Result<Vec<u8>> {
+    let seed = format!(
+        "@{}_@{}",
+        to_short_hex_literal(&parse_address(bounty_addr)?),
+        to_short_hex_literal(&parse_address(builder_addr)?)
+    );
+    Ok(bcs::to_bytes(&seed)?)
+}
+
+/// Address the build object of `builder_addr` on `bounty_addr` has, whether it exists yet or not.
+/// A builder can only have one build per bounty, so this is also how to look it up.
+pub fn get_build_obj_addr(
+    contract_addr: &str,
+    bounty_addr: &str,
+    builder_addr: &str,
+) -> Result<String> {
+    create_object_address(
+        &get_config_obj_addr(contract_addr)?,
+        &construct_build_obj_seed(bounty_addr, builder_addr)?,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Vectors computed independently with Python's hashlib.sha3_256 following the framework's
+    // create_object_address, so they don't share any code with the implementation above
+    const CONTRACT_ADDR: &str =
+        "0x81453e030e20e46297aea5bc6c22343825f2f7d9cbc9131979d779491586f187";
+
+    #[test]
+    fn test_get_config_obj_addr() {
+        assert_eq!(
+            get_config_obj_addr(CONTRACT_ADDR).unwrap(),
+            "0x4ae49c30570e661fa6e78f4439f240e65d7aafeb28b35db8a30da9a6d369a247"
+        );
+        // Short addresses are padded, e.g. the dev address in Move.toml
+        assert_eq!(
+            get_config_obj_addr("0x999").unwrap(),
+            "0xe76b5cb0fa3a61412dc39733d80573616d748040ac351ef0afac98aaa1b6cce2"
+        );
+    }
+
+    #[test]
+    fn test_construct_build_obj_seed() {
+        let seed = construct_build_obj_seed(
+            "0x0abc000000000000000000000000000000000000000000000000000000000001",
+            "0xff",
+        )
+        .unwrap();
+        // ULEB128 length prefix then the string with leading zeros trimmed from both addresses
+        assert_eq!(seed[0], 72);
+        assert_eq!(
+            &seed[1..],
+            b"@0xabc000000000000000000000000000000000000000000000000000000000001_@0xff"
+        );
+    }
+
+    #[test]
+    fn test_get_build_obj_addr() {
+        assert_eq!(
+            get_build_obj_addr(
+                CONTRACT_ADDR,
+                "0x0abc000000000000000000000000000000000000000000000000000000000001",
+                "0x00000000000000000000000000000000000000000000000000000000000000ff",
+            )
+            .unwrap(),
+            "0x4ef83147b137ea0a2b53b9356b3bc83219f4c91a302ce8058cbca03409fb6bf4"
+        );
+        assert!(get_build_obj_addr(CONTRACT_ADDR, "0xnot_hex", "0x1").is_err());
+    }
+}
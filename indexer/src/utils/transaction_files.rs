@@ -0,0 +1,293 @@
+//! Record the batches of the transaction stream to local files and replay them in place of the
+//! stream, so storers can be debugged without network access and bug reports can carry a
+//! reproducible fixture. Each batch is one `TransactionsResponse` protobuf, the message the
+//! gRPC stream sends, in a file named after its first and last version.
+
+use anyhow::{bail, Context, Result};
+use aptos_indexer_processor_sdk::{
+    aptos_protos::{indexer::v1::TransactionsResponse, transaction::v1::Transaction},
+    traits::{
+        async_step::AsyncRunType, AsyncStep, NamedStep, PollableAsyncRunType, PollableAsyncStep,
+        Processable,
+    },
+    types::transaction_context::TransactionContext,
+    utils::errors::ProcessorError,
+};
+use async_trait::async_trait;
+use prost::Message;
+use std::path::{Path, PathBuf};
+
+const FILE_EXTENSION: &str = "pb";
+
+/// Zero padded so sorting the file names sorts the batches by version
+fn batch_file_name(start_version: u64, end_version: u64) -> String {
+    format!(
+        "{:020}-{:020}.{}",
+        start_version, end_version, FILE_EXTENSION
+    )
+}
+
+fn parse_batch_file_name(path: &Path) -> Option<(u64, u64)> {
+    if path.extension()? != FILE_EXTENSION {
+        return None;
+    }
+    let (start_version, end_version) = path.file_stem()?.to_str()?.split_once('-')?;
+    Some((start_version.parse().ok()?, end_version.parse().ok()?))
+}
+
+pub fn encode_batch(transactions: Vec<Transaction>, chain_id: u64) -> Vec<u8> {
+    TransactionsResponse {
+        transactions,
+        chain_id: Some(chain_id),
+        ..Default::default()
+    }
+    .encode_to_vec()
+}
+
+/// Returns the transactions and the chain id they were recorded from
+pub fn decode_batch(bytes: &[u8]) -> Result<(Vec<Transaction>, u64)> {
+    let response = TransactionsResponse::decode(bytes).context("Invalid batch file")?;
+    let Some(chain_id) = response.chain_id else {
+        bail!("Batch file has no chain id");
+    };
+    Ok((response.transactions, chain_id))
+}
+
+/// Recorded batch files in version order, skipping the ones that end before starting_version
+pub fn list_batch_files(dir: &Path, starting_version: u64) -> Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for entry in
+        std::fs::read_dir(dir).with_context(|| format!("Could not read {}", dir.display()))?
+    {
+        let path = entry?.path();
+        match parse_batch_file_name(&path) {
+            Some((_, end_version)) if end_version >= starting_version => files.push(path),
+            Some(_) => {}
+            None => tracing::warn!("Skipping {}, not a batch file", path.display()),
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Chain id of the first batch recorded in dir, replaces the chain id of the gRPC stream
+pub fn get_recorded_chain_id(dir: &Path) -> Result<u64> {
+    let Some(path) = list_batch_files(dir, 0)?.into_iter().next() else {
+        bail!("No batch files in {}", dir.display());
+    };
+    let (_, chain_id) = decode_batch(&std::fs::read(&path)?)
+        .with_context(|| format!("Could not decode {}", path.display()))?;
+    Ok(chain_id)
+}
+
+/// TransactionFileRecorder writes every batch to its own file and passes it on unchanged.
+pub struct TransactionFileRecorder
+where
+    Self: Sized + Send + 'static,
+{
+    dir: PathBuf,
+    chain_id: u64,
+}
+
+impl TransactionFileRecorder {
+    pub fn new(dir: PathBuf, chain_id: u64) -> Result<Self> {
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Could not create {}", dir.display()))?;
+        Ok(Self { dir, chain_id })
+    }
+}
+
+impl AsyncStep for TransactionFileRecorder {}
+
+impl NamedStep for TransactionFileRecorder {
+    fn name(&self) -> String {
+        "TransactionFileRecorder".to_string()
+    }
+}
+
+#[async_trait]
+impl Processable for TransactionFileRecorder {
+    type Input = Transaction;
+    type Output = Transaction;
+    type RunType = AsyncRunType;
+
+    async fn process(
+        &mut self,
+        item: TransactionContext<Transaction>,
+    ) -> Result<Option<TransactionContext<Transaction>>, ProcessorError> {
+        let path = self
+            .dir
+            .join(batch_file_name(item.start_version, item.end_version));
+        // Written under a temporary name first, a crash never leaves a truncated batch file
+        let tmp_path = path.with_extension("tmp");
+        let bytes = encode_batch(item.data.clone(), self.chain_id);
+        let write = async {
+            tokio::fs::write(&tmp_path, bytes).await?;
+            tokio::fs::rename(&tmp_path, &path).await
+        };
+        write.await.map_err(|e| ProcessorError::ProcessError {
+            message: format!("Failed to record batch to {}: {}", path.display(), e),
+        })?;
+        Ok(Some(item))
+    }
+}
+
+/// TransactionFileReplayStep reads the recorded batches of a directory, one per poll, in place of
+/// the transaction stream. It ends once every file has been read.
+pub struct TransactionFileReplayStep
+where
+    Self: Sized + Send + 'static,
+{
+    // Remaining files, in reverse version order so the next one is popped from the end
+    files: Vec<PathBuf>,
+    starting_version: u64,
+}
+
+impl TransactionFileReplayStep {
+    pub fn new(dir: &Path, starting_version: u64) -> Result<Self> {
+        let mut files = list_batch_files(dir, starting_version)?;
+        tracing::info!(
+            file_count = files.len(),
+            starting_version,
+            "Replaying batches from {}",
+            dir.display()
+        );
+        files.reverse();
+        Ok(Self {
+            files,
+            starting_version,
+        })
+    }
+
+    async fn read_batch(&self, path: &Path) -> Result<TransactionContext<Transaction>> {
+        let bytes = tokio::fs::read(path).await?;
+        let (mut transactions, _) = decode_batch(&bytes)?;
+        // A file can start before the starting version, e.g. after a restart mid replay
+        transactions.retain(|txn| txn.version >= self.starting_version);
+        let (Some(first), Some(last)) = (transactions.first(), transactions.last()) else {
+            bail!("Batch has no transactions");
+        };
+        Ok(TransactionContext {
+            start_version: first.version,
+            end_version: last.version,
+            start_transaction_timestamp: first.timestamp.clone(),
+            end_transaction_timestamp: last.timestamp.clone(),
+            total_size_in_bytes: bytes.len() as u64,
+            data: transactions,
+        })
+    }
+}
+
+impl NamedStep for TransactionFileReplayStep {
+    fn name(&self) -> String {
+        "TransactionFileReplayStep".to_string()
+    }
+}
+
+#[async_trait]
+impl Processable for TransactionFileReplayStep {
+    type Input = ();
+    type Output = Transaction;
+    type RunType = PollableAsyncRunType;
+
+    async fn process(
+        &mut self,
+        _item: TransactionContext<()>,
+    ) -> Result<Option<TransactionContext<Transaction>>, ProcessorError> {
+        // Batches come from poll, there is no input
+        Ok(None)
+    }
+}
+
+#[async_trait]
+impl PollableAsyncStep for TransactionFileReplayStep
+where
+    Self: Sized + Send + Sync + 'static,
+{
+    fn poll_interval(&self) -> std::time::Duration {
+        std::time::Duration::ZERO
+    }
+
+    async fn poll(
+        &mut self,
+    ) -> Result<Option<Vec<TransactionContext<Transaction>>>, ProcessorError> {
+        let Some(path) = self.files.pop() else {
+            return Ok(None);
+        };
+        let batch = self
+            .read_batch(&path)
+            .await
+            .map_err(|e| ProcessorError::ProcessError {
+                message: format!("Failed to replay {}: {:#}", path.display(), e),
+            })?;
+        Ok(Some(vec![batch]))
+    }
+
+    async fn should_continue_polling(&mut self) -> bool {
+        !self.files.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn new_transaction(version: u64) -> Transaction {
+        Transaction {
+            version,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_batch_round_trip() {
+        let transactions = vec![new_transaction(10), new_transaction(11)];
+        let (decoded, chain_id) = decode_batch(&encode_batch(transactions.clone(), 2)).unwrap();
+        assert_eq!(decoded, transactions);
+        assert_eq!(chain_id, 2);
+        assert!(decode_batch(&TransactionsResponse::default().encode_to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_batch_file_name() {
+        let name = batch_file_name(5, 120);
+        assert_eq!(name, "00000000000000000005-00000000000000000120.pb");
+        assert_eq!(parse_batch_file_name(Path::new(&name)), Some((5, 120)));
+        // Lexical order is version order
+        assert!(batch_file_name(9, 9) < batch_file_name(10, 10));
+        assert_eq!(parse_batch_file_name(Path::new("notes.txt")), None);
+        assert_eq!(parse_batch_file_name(Path::new("5-x.pb")), None);
+    }
+
+    #[tokio::test]
+    async fn test_replay_recorded_batches() {
+        let dir = std::env::temp_dir().join(format!("transaction_files_{}", std::process::id()));
+        let mut recorder = TransactionFileRecorder::new(dir.clone(), 2).unwrap();
+        for versions in [0..5, 5..10, 10..15] {
+            let data = versions.map(new_transaction).collect::<Vec<_>>();
+            recorder
+                .process(TransactionContext {
+                    start_version: data[0].version,
+                    end_version: data[data.len() - 1].version,
+                    start_transaction_timestamp: None,
+                    end_transaction_timestamp: None,
+                    total_size_in_bytes: 0,
+                    data,
+                })
+                .await
+                .unwrap();
+        }
+        assert_eq!(get_recorded_chain_id(&dir).unwrap(), 2);
+
+        // The first batch ends before the starting version and the second starts before it
+        let mut replay = TransactionFileReplayStep::new(&dir, 7).unwrap();
+        let mut versions = vec![];
+        while replay.should_continue_polling().await {
+            for batch in replay.poll().await.unwrap().unwrap() {
+                versions.push((batch.start_version, batch.end_version));
+            }
+        }
+        assert_eq!(versions, vec![(7, 9), (10, 14)]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
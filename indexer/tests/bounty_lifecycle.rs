@@ -0,0 +1,345 @@
+//! Full bounty lifecycles indexed into a throwaway Postgres, mirroring
+//! contracts/bounty-app/tests/test_end_to_end.move. The tests start a Postgres container, run them
+//! with `cargo test --test bounty_lifecycle -- --ignored` where docker is available.
+
+mod common;
+
+use aptos_indexer_processor_sdk::aptos_protos::transaction::v1::Event as EventPB;
+use bigdecimal::BigDecimal;
+use common::{BountyFixture, BountyRow, BuildFixture, BuildRow, TestIndexer};
+use indexer::config::indexer_processor_config::PipelineConfig;
+
+const BOUNTY: &str = "0x00000000000000000000000000000000000000000000000000000000000000b1";
+const BUILD: &str = "0x00000000000000000000000000000000000000000000000000000000000000c1";
+const CANCELED_BUILD: &str = "0x00000000000000000000000000000000000000000000000000000000000000c2";
+const SECOND_BUILD: &str = "0x00000000000000000000000000000000000000000000000000000000000000c3";
+const USER1: &str = "0x0000000000000000000000000000000000000000000000000000000000000101";
+const USER2: &str = "0x0000000000000000000000000000000000000000000000000000000000000102";
+const USER3: &str = "0x0000000000000000000000000000000000000000000000000000000000000103";
+
+// Bounty of the Move test: 2 winners paid 50 each
+fn new_bounty() -> BountyFixture {
+    BountyFixture {
+        bounty_obj_addr: BOUNTY.to_string(),
+        creator: USER1.to_string(),
+        create_timestamp: 100,
+        last_update_timestamp: 100,
+        end_timestamp: 2147483647,
+        payment_per_winner: 50,
+        winner_count: 0,
+        winner_limit: 2,
+    }
+}
+
+fn new_build(build_obj_addr: &str, creator: &str) -> BuildFixture {
+    BuildFixture {
+        build_obj_addr: build_obj_addr.to_string(),
+        bounty_obj_addr: BOUNTY.to_string(),
+        creator: creator.to_string(),
+        payment_amount: 0,
+        create_timestamp: 200,
+        last_update_timestamp: 200,
+        proof_link: String::new(),
+        status: 1,
+    }
+}
+
+/// User1 creates a bounty, user2 builds it and gets accepted, then user1 ends the bounty and gets
+/// the payment of the second winner back. One transaction per step.
+fn end_to_end_transactions() -> Vec<(i64, Vec<EventPB>)> {
+    let bounty = new_bounty();
+    let build = new_build(BUILD, USER2);
+    let submitted_build = BuildFixture {
+        last_update_timestamp: 300,
+        proof_link: "build_proof_link".to_string(),
+        status: 2,
+        ..build.clone()
+    };
+    let accepted_build = BuildFixture {
+        last_update_timestamp: 400,
+        payment_amount: 50,
+        status: 4,
+        ..submitted_build.clone()
+    };
+    let bounty_with_winner = BountyFixture {
+        last_update_timestamp: 400,
+        winner_count: 1,
+        ..bounty.clone()
+    };
+    // Ending only moves the end timestamp
+    let ended_bounty = BountyFixture {
+        end_timestamp: 500,
+        ..bounty_with_winner.clone()
+    };
+    vec![
+        (100, vec![bounty.create_event()]),
+        (200, vec![build.create_event()]),
+        (300, vec![submitted_build.submit_for_review_event()]),
+        (400, vec![accepted_build.accept_event(&bounty_with_winner)]),
+        (500, vec![ended_bounty.end_event(50)]),
+    ]
+}
+
+async fn assert_end_to_end_indexed(indexer: &TestIndexer) {
+    assert_eq!(
+        indexer.get_bounty(BOUNTY).await,
+        BountyRow {
+            last_update_timestamp: 400,
+            end_timestamp: 500,
+            winner_count: 1,
+            total_payment: BigDecimal::from(100),
+            refunded_amount: BigDecimal::from(50),
+        }
+    );
+    assert_eq!(
+        indexer.get_build(BUILD).await,
+        BuildRow {
+            last_update_timestamp: 400,
+            build_status: 4,
+            proof_link: "build_proof_link".to_string(),
+            submit_timestamp: Some(300),
+            payment_amount: BigDecimal::from(50),
+        }
+    );
+
+    let creator = indexer.get_user_stat(USER1).await;
+    assert_eq!(creator.bounty_created, 1);
+    // 100 locked on creation, 50 sent back on end
+    assert_eq!(creator.apt_spent, BigDecimal::from(50));
+    assert_eq!(creator.total_points, 3);
+    assert_eq!(creator.create_timestamp, 100);
+    assert_eq!(creator.last_update_timestamp, 500);
+
+    let builder = indexer.get_user_stat(USER2).await;
+    assert_eq!(builder.build_created, 1);
+    assert_eq!(builder.build_submitted_for_review, 0);
+    assert_eq!(builder.build_completed, 1);
+    assert_eq!(builder.apt_received, BigDecimal::from(50));
+    assert_eq!(builder.total_points, 2);
+    assert_eq!(builder.create_timestamp, 200);
+}
+
+#[tokio::test]
+#[ignore = "starts a Postgres container, needs docker"]
+async fn test_end_to_end_one_batch_per_transaction() {
+    let mut indexer = TestIndexer::start(false).await;
+    for transaction in end_to_end_transactions() {
+        indexer.process_batch(vec![transaction]).await;
+    }
+    assert_end_to_end_indexed(&indexer).await;
+}
+
+#[tokio::test]
+#[ignore = "starts a Postgres container, needs docker"]
+async fn test_end_to_end_single_atomic_batch() {
+    // Every event type of the lifecycle in one batch, they must still apply in on-chain order
+    let mut indexer = TestIndexer::start(true).await;
+    indexer.process_batch(end_to_end_transactions()).await;
+    assert_end_to_end_indexed(&indexer).await;
+}
+
+#[tokio::test]
+#[ignore = "starts a Postgres container, needs docker"]
+async fn test_canceled_build() {
+    let mut indexer = TestIndexer::start(false).await;
+    let bounty = new_bounty();
+    let build = new_build(BUILD, USER2);
+    let canceled_build = new_build(CANCELED_BUILD, USER3);
+    indexer
+        .process_batch(vec![
+            (100, vec![bounty.create_event()]),
+            (
+                200,
+                vec![build.create_event(), canceled_build.create_event()],
+            ),
+        ])
+        .await;
+
+    // Only a build still in progress can be canceled
+    indexer
+        .process_batch(vec![
+            (
+                300,
+                vec![BuildFixture {
+                    last_update_timestamp: 300,
+                    proof_link: "build_proof_link".to_string(),
+                    status: 2,
+                    ..build.clone()
+                }
+                .submit_for_review_event()],
+            ),
+            (
+                400,
+                vec![BuildFixture {
+                    last_update_timestamp: 400,
+                    status: 3,
+                    ..canceled_build.clone()
+                }
+                .cancel_event()],
+            ),
+        ])
+        .await;
+
+    assert_eq!(indexer.get_build(CANCELED_BUILD).await.build_status, 3);
+    assert_eq!(indexer.get_build(BUILD).await.build_status, 2);
+    let canceled_builder = indexer.get_user_stat(USER3).await;
+    assert_eq!(canceled_builder.build_created, 1);
+    assert_eq!(canceled_builder.build_submitted_for_review, 0);
+    assert_eq!(canceled_builder.build_canceled, 1);
+    assert_eq!(canceled_builder.total_points, 1);
+    assert_eq!(
+        indexer
+            .get_user_stat(USER2)
+            .await
+            .build_submitted_for_review,
+        1
+    );
+    // The bounty is untouched until a build is accepted
+    assert_eq!(indexer.get_bounty(BOUNTY).await.winner_count, 0);
+}
+
+#[tokio::test]
+#[ignore = "starts a Postgres container, needs docker"]
+async fn test_reprocessed_batch_keeps_latest_state() {
+    let mut indexer = TestIndexer::start(false).await;
+    let bounty = new_bounty();
+    let build = new_build(BUILD, USER2);
+    indexer
+        .process_batch(vec![
+            (100, vec![bounty.create_event()]),
+            (200, vec![build.create_event()]),
+        ])
+        .await;
+
+    // Submitted and accepted in the same second
+    let submitted_build = BuildFixture {
+        last_update_timestamp: 300,
+        status: 2,
+        ..build.clone()
+    };
+    let accepted_build = BuildFixture {
+        payment_amount: 50,
+        status: 4,
+        ..submitted_build.clone()
+    };
+    let bounty_with_winner = BountyFixture {
+        last_update_timestamp: 300,
+        winner_count: 1,
+        ..bounty.clone()
+    };
+    let transactions = vec![
+        (300, vec![submitted_build.submit_for_review_event()]),
+        (300, vec![accepted_build.accept_event(&bounty_with_winner)]),
+    ];
+    let start_version = indexer.process_batch(transactions.clone()).await;
+    indexer.reprocess_batch(start_version, transactions).await;
+
+    // The replayed submit is older than the accept, it must not move the build back
+    let stored_build = indexer.get_build(BUILD).await;
+    assert_eq!(stored_build.build_status, 4);
+    assert_eq!(stored_build.payment_amount, BigDecimal::from(50));
+    assert_eq!(indexer.get_bounty(BOUNTY).await.winner_count, 1);
+}
+
+#[tokio::test]
+#[ignore = "starts a Postgres container, needs docker"]
+async fn test_long_and_non_ascii_proof_links() {
+    let mut indexer = TestIndexer::start(false).await;
+    let bounty = new_bounty();
+    let build = new_build(BUILD, USER2);
+    let second_build = new_build(SECOND_BUILD, USER3);
+    // The contract doesn't limit proof links, and parsed values are percent-encoded
+    let long_proof_link = format!("https://example.com/{}", "a/".repeat(2_000));
+    let non_ascii_proof_link = format!("https://example.com/{}", "証明".repeat(100));
+    let submitted = |build: &BuildFixture, proof_link: &str| BuildFixture {
+        last_update_timestamp: 300,
+        proof_link: proof_link.to_string(),
+        status: 2,
+        ..build.clone()
+    };
+    indexer
+        .process_batch(vec![
+            (100, vec![bounty.create_event()]),
+            (200, vec![build.create_event(), second_build.create_event()]),
+            (
+                300,
+                vec![
+                    submitted(&build, &long_proof_link).submit_for_review_event(),
+                    submitted(&second_build, &non_ascii_proof_link).submit_for_review_event(),
+                ],
+            ),
+        ])
+        .await;
+
+    assert_eq!(indexer.get_build(BUILD).await.proof_link, long_proof_link);
+    assert_eq!(
+        indexer.get_build(SECOND_BUILD).await.proof_link,
+        non_ascii_proof_link
+    );
+}
+
+/// Several events per user in one batch, written one row per insert. The changes of a user must
+/// be added once per batch, not once per chunk.
+async fn check_user_stats_with_chunk_size_one(commit_batch_atomically: bool) {
+    let mut indexer = TestIndexer::start(commit_batch_atomically).await;
+    let mut pipeline_config = PipelineConfig::default();
+    for table_name in ["bounties", "builds", "user_stats"] {
+        pipeline_config
+            .per_table_chunk_sizes
+            .insert(table_name.to_string(), 1);
+    }
+    indexer.set_storer_config(commit_batch_atomically, pipeline_config);
+
+    let bounty = new_bounty();
+    let builds = [
+        new_build(BUILD, USER2),
+        new_build(SECOND_BUILD, USER2),
+        new_build(CANCELED_BUILD, USER3),
+    ];
+    let mut transactions = vec![
+        (100, vec![bounty.create_event()]),
+        (
+            200,
+            builds.iter().map(|build| build.create_event()).collect(),
+        ),
+    ];
+    for build in &builds[..2] {
+        transactions.push((
+            300,
+            vec![BuildFixture {
+                last_update_timestamp: 300,
+                status: 2,
+                ..build.clone()
+            }
+            .submit_for_review_event()],
+        ));
+    }
+    indexer.process_batch(transactions).await;
+
+    let creator = indexer.get_user_stat(USER1).await;
+    assert_eq!(creator.bounty_created, 1);
+    assert_eq!(creator.apt_spent, BigDecimal::from(100));
+    assert_eq!(creator.total_points, 3);
+    let builder = indexer.get_user_stat(USER2).await;
+    assert_eq!(builder.build_created, 2);
+    assert_eq!(builder.build_submitted_for_review, 2);
+    assert_eq!(builder.total_points, 2);
+    assert_eq!(builder.create_timestamp, 200);
+    assert_eq!(builder.last_update_timestamp, 300);
+    let other_builder = indexer.get_user_stat(USER3).await;
+    assert_eq!(other_builder.build_created, 1);
+    assert_eq!(other_builder.build_submitted_for_review, 0);
+    assert_eq!(other_builder.total_points, 1);
+}
+
+#[tokio::test]
+#[ignore = "starts a Postgres container, needs docker"]
+async fn test_user_stats_with_chunk_size_one() {
+    check_user_stats_with_chunk_size_one(false).await;
+}
+
+#[tokio::test]
+#[ignore = "starts a Postgres container, needs docker"]
+async fn test_user_stats_with_chunk_size_one_atomic_batch() {
+    check_user_stats_with_chunk_size_one(true).await;
+}
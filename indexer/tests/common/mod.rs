@@ -0,0 +1,347 @@
+//! Harness for the end-to-end tests: starts a throwaway Postgres in docker, applies the embedded
+//! migrations and pushes hand-built transactions through EventsExtractor and EventsStorer.
+// Every test binary compiles the whole harness but not all of them use all of it
+#![allow(dead_code)]
+
+pub mod model;
+
+use aptos_indexer_processor_sdk::{
+    aptos_protos::{
+        transaction::v1::{transaction::TxnData, Event as EventPB, Transaction, UserTransaction},
+        util::timestamp::Timestamp,
+    },
+    traits::Processable,
+    types::transaction_context::TransactionContext,
+};
+use bigdecimal::BigDecimal;
+use diesel::{QueryDsl, Queryable};
+use diesel_async::RunQueryDsl;
+use indexer::{
+    config::indexer_processor_config::{AnalyticsConfig, PipelineConfig},
+    db_models::{
+        on_chain::{
+            AcceptBuildEventOnChain, BountyOnChain, BuildOnChain, CancelBuildEventOnChain,
+            CreateBountyEventOnChain, CreateBuildEventOnChain, EndBountyEventOnChain, MoveObject,
+            SubmitBuildForReviewEventOnChain,
+        },
+        user_stat::UserStat,
+    },
+    processors::events::{
+        events_extractor::{new_bounty_app_event, EventsExtractor},
+        events_storer::EventsStorer,
+    },
+    schema::{bounties, builds, user_stats},
+    utils::{
+        database_connection::{get_db_connection, new_db_pool},
+        database_migrations::run_pending_migrations,
+        database_utils::ArcDbPool,
+    },
+};
+use serde::Serialize;
+use testcontainers_modules::{
+    postgres::Postgres,
+    testcontainers::{runners::AsyncRunner, ContainerAsync, ImageExt},
+};
+
+pub const CONTRACT_ADDRESS: &str =
+    "0x81453e030e20e46297aea5bc6c22343825f2f7d9cbc9131979d779491586f187";
+pub const APT_FA_ADDR: &str = "0x000000000000000000000000000000000000000000000000000000000000000a";
+// The module defaults to Postgres 11, which is end of life
+const POSTGRES_TAG: &str = "16-alpine";
+
+/// Columns of a bounty that change over its lifecycle
+#[derive(Debug, PartialEq, Queryable)]
+pub struct BountyRow {
+    pub last_update_timestamp: i64,
+    pub end_timestamp: i64,
+    pub winner_count: i64,
+    pub total_payment: BigDecimal,
+    pub refunded_amount: BigDecimal,
+}
+
+/// Columns of a build that change over its lifecycle
+#[derive(Debug, PartialEq, Queryable)]
+pub struct BuildRow {
+    pub last_update_timestamp: i64,
+    pub build_status: i64,
+    pub proof_link: String,
+    pub submit_timestamp: Option<i64>,
+    pub payment_amount: BigDecimal,
+}
+
+fn new_events_storer(
+    pool: ArcDbPool,
+    commit_batch_atomically: bool,
+    pipeline_config: PipelineConfig,
+) -> EventsStorer {
+    EventsStorer::new(
+        pool,
+        CONTRACT_ADDRESS.to_string(),
+        "events_processor".to_string(),
+        commit_batch_atomically,
+        pipeline_config,
+        AnalyticsConfig::default(),
+    )
+}
+
+pub struct TestIndexer {
+    // Stopped and removed when dropped
+    _container: ContainerAsync<Postgres>,
+    pub pool: ArcDbPool,
+    extractor: EventsExtractor,
+    storer: EventsStorer,
+    next_version: u64,
+}
+
+impl TestIndexer {
+    pub async fn start(commit_batch_atomically: bool) -> Self {
+        let container = Postgres::default()
+            .with_tag(POSTGRES_TAG)
+            .start()
+            .await
+            .expect("Failed to start Postgres, is docker running?");
+        let database_url = format!(
+            "postgresql://postgres:postgres@{}:{}/postgres?sslmode=disable",
+            container.get_host().await.unwrap(),
+            container.get_host_port_ipv4(5432).await.unwrap()
+        );
+        run_pending_migrations(&database_url).await.unwrap();
+        let pool = new_db_pool(&database_url, 10).await.unwrap();
+
+        Self {
+            _container: container,
+            pool: pool.clone(),
+            extractor: EventsExtractor::new(CONTRACT_ADDRESS.to_string(), &[]),
+            storer: new_events_storer(pool, commit_batch_atomically, PipelineConfig::default()),
+            next_version: 1,
+        }
+    }
+
+    /// Storers of the next batches write with this config
+    pub fn set_storer_config(
+        &mut self,
+        commit_batch_atomically: bool,
+        pipeline_config: PipelineConfig,
+    ) {
+        self.storer =
+            new_events_storer(self.pool.clone(), commit_batch_atomically, pipeline_config);
+    }
+
+    /// Index one batch with a transaction per list of events, like the gRPC stream would send it.
+    /// Returns the version of its first transaction.
+    pub async fn process_batch(&mut self, transactions: Vec<(i64, Vec<EventPB>)>) -> u64 {
+        let start_version = self.next_version;
+        self.next_version += transactions.len() as u64;
+        self.reprocess_batch(start_version, transactions).await;
+        start_version
+    }
+
+    /// Index a batch again from the same versions, like after a restart that lost its checkpoint
+    pub async fn reprocess_batch(
+        &mut self,
+        start_version: u64,
+        transactions: Vec<(i64, Vec<EventPB>)>,
+    ) {
+        let data = transactions
+            .into_iter()
+            .enumerate()
+            .map(|(idx, (timestamp, events))| {
+                new_transaction(start_version + idx as u64, timestamp, events)
+            })
+            .collect::<Vec<_>>();
+        let batch = TransactionContext {
+            end_version: start_version + data.len() as u64 - 1,
+            data,
+            start_version,
+            start_transaction_timestamp: None,
+            end_transaction_timestamp: None,
+            total_size_in_bytes: 0,
+        };
+        let events = self.extractor.process(batch).await.unwrap().unwrap();
+        self.storer.process(events).await.unwrap();
+    }
+
+    pub async fn get_bounty(&self, bounty_obj_addr: &str) -> BountyRow {
+        bounties::table
+            .find(bounty_obj_addr)
+            .select((
+                bounties::last_update_timestamp,
+                bounties::end_timestamp,
+                bounties::winner_count,
+                bounties::total_payment,
+                bounties::refunded_amount,
+            ))
+            .first::<BountyRow>(&mut get_db_connection(&self.pool).await.unwrap())
+            .await
+            .unwrap()
+    }
+
+    pub async fn get_build(&self, build_obj_addr: &str) -> BuildRow {
+        builds::table
+            .find(build_obj_addr)
+            .select((
+                builds::last_update_timestamp,
+                builds::build_status,
+                builds::proof_link,
+                builds::submit_timestamp,
+                builds::payment_amount,
+            ))
+            .first::<BuildRow>(&mut get_db_connection(&self.pool).await.unwrap())
+            .await
+            .unwrap()
+    }
+
+    pub async fn get_user_stat(&self, user_addr: &str) -> UserStat {
+        user_stats::table
+            .find(user_addr)
+            .first::<UserStat>(&mut get_db_connection(&self.pool).await.unwrap())
+            .await
+            .unwrap()
+    }
+}
+
+pub fn new_transaction(version: u64, timestamp: i64, events: Vec<EventPB>) -> Transaction {
+    Transaction {
+        version,
+        timestamp: Some(Timestamp {
+            seconds: timestamp,
+            nanos: 0,
+        }),
+        txn_data: Some(TxnData::User(UserTransaction {
+            events,
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+}
+
+pub fn new_event(event_name: &str, data: &impl Serialize) -> EventPB {
+    new_bounty_app_event(CONTRACT_ADDRESS, event_name, data)
+}
+
+#[derive(Clone)]
+/// Fields of bounty_app::Bounty that the tests vary
+pub struct BountyFixture {
+    pub bounty_obj_addr: String,
+    pub creator: String,
+    pub create_timestamp: i64,
+    pub last_update_timestamp: i64,
+    pub end_timestamp: i64,
+    pub payment_per_winner: u64,
+    pub winner_count: u64,
+    pub winner_limit: u64,
+}
+
+impl BountyFixture {
+    fn to_on_chain(&self) -> BountyOnChain {
+        BountyOnChain {
+            creator: self.creator.clone(),
+            create_timestamp: self.create_timestamp as u64,
+            last_update_timestamp: self.last_update_timestamp as u64,
+            end_timestamp: self.end_timestamp as u64,
+            title: "title".to_string(),
+            description_link: "link".to_string(),
+            payment_metadata_object: MoveObject {
+                inner: APT_FA_ADDR.to_string(),
+            },
+            payment_per_winner: self.payment_per_winner,
+            stake_required: 0,
+            stake_lockup_in_seconds: 0,
+            winner_count: self.winner_count,
+            winner_limit: self.winner_limit,
+            contact_info: "contact @apt_to_the_moon on twitter".to_string(),
+        }
+    }
+
+    pub fn create_event(&self) -> EventPB {
+        new_event(
+            "CreateBountyEvent",
+            &CreateBountyEventOnChain {
+                bounty_obj_addr: self.bounty_obj_addr.clone(),
+                bounty: self.to_on_chain(),
+            },
+        )
+    }
+
+    pub fn end_event(&self, payment_sent_back_to_creator: u64) -> EventPB {
+        new_event(
+            "EndBountyEvent",
+            &EndBountyEventOnChain {
+                bounty_obj_addr: self.bounty_obj_addr.clone(),
+                bounty: self.to_on_chain(),
+                payment_sent_back_to_creator,
+            },
+        )
+    }
+}
+
+#[derive(Clone)]
+/// Fields of bounty_app::Build
+pub struct BuildFixture {
+    pub build_obj_addr: String,
+    pub bounty_obj_addr: String,
+    pub creator: String,
+    pub payment_amount: u64,
+    pub create_timestamp: i64,
+    pub last_update_timestamp: i64,
+    pub proof_link: String,
+    pub status: i64,
+}
+
+impl BuildFixture {
+    fn to_on_chain(&self) -> BuildOnChain {
+        BuildOnChain {
+            creator: self.creator.clone(),
+            payment_recipient: self.creator.clone(),
+            payment_amount: self.payment_amount,
+            create_timestamp: self.create_timestamp as u64,
+            last_update_timestamp: self.last_update_timestamp as u64,
+            proof_link: self.proof_link.clone(),
+            bounty_object: MoveObject {
+                inner: self.bounty_obj_addr.clone(),
+            },
+            status: self.status as u64,
+        }
+    }
+
+    pub fn create_event(&self) -> EventPB {
+        new_event(
+            "CreateBuildEvent",
+            &CreateBuildEventOnChain {
+                build_obj_addr: self.build_obj_addr.clone(),
+                build: self.to_on_chain(),
+            },
+        )
+    }
+
+    pub fn submit_for_review_event(&self) -> EventPB {
+        new_event(
+            "SubmitBuildForReviewEvent",
+            &SubmitBuildForReviewEventOnChain {
+                build_obj_addr: self.build_obj_addr.clone(),
+                build: self.to_on_chain(),
+            },
+        )
+    }
+
+    pub fn cancel_event(&self) -> EventPB {
+        new_event(
+            "CancelBuildEvent",
+            &CancelBuildEventOnChain {
+                build_obj_addr: self.build_obj_addr.clone(),
+                build: self.to_on_chain(),
+            },
+        )
+    }
+
+    pub fn accept_event(&self, bounty: &BountyFixture) -> EventPB {
+        new_event(
+            "AcceptBuildEvent",
+            &AcceptBuildEventOnChain {
+                build_obj_addr: self.build_obj_addr.clone(),
+                build: self.to_on_chain(),
+                bounty: bounty.to_on_chain(),
+            },
+        )
+    }
+}
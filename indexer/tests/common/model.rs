@@ -0,0 +1,319 @@
+//! Reference model of the bounty_app contract's state machine. It applies operations in order,
+//! skips the ones the contract would abort and returns the events of the others. The expected
+//! rows are derived from the final state, independently of the deltas the storers apply.
+//!
+//! Submitting a build again while it is in review is allowed by the contract but not modeled,
+//! only builds in progress are submitted.
+
+use aptos_indexer_processor_sdk::aptos_protos::transaction::v1::Event as EventPB;
+use bigdecimal::BigDecimal;
+use indexer::{
+    db_models::user_stat::UserStat,
+    processors::events::storers::{
+        BUILD_STATUS_CANCELED, BUILD_STATUS_COMPLETED, BUILD_STATUS_IN_PROGRESS,
+        BUILD_STATUS_READY_FOR_REVIEW,
+    },
+};
+use std::collections::BTreeMap;
+
+use super::{BountyFixture, BountyRow, BuildFixture, BuildRow};
+
+// Points per action, see the storers
+const POINT_PER_NEW_BOUNTY: i64 = 3;
+const POINT_PER_NEW_BUILD: i64 = 1;
+const POINT_PER_ACCEPT_BUILD: i64 = 1;
+
+const END_TIMESTAMP: i64 = 2147483647;
+
+/// An entry function call. Indexes pick among the existing users, bounties and builds modulo
+/// their count, so any operation refers to something.
+#[derive(Clone, Debug)]
+pub enum Operation {
+    CreateBounty {
+        creator: usize,
+        payment_per_winner: u64,
+        winner_limit: u64,
+    },
+    EndBounty {
+        bounty: usize,
+    },
+    CreateBuild {
+        bounty: usize,
+        creator: usize,
+    },
+    SubmitBuildForReview {
+        build: usize,
+    },
+    CancelBuild {
+        build: usize,
+    },
+    AcceptBuild {
+        build: usize,
+    },
+}
+
+struct ModelBounty {
+    fixture: BountyFixture,
+    ended: bool,
+    refunded_amount: u64,
+}
+
+struct ModelBuild {
+    fixture: BuildFixture,
+    bounty: usize,
+    submit_timestamp: Option<i64>,
+}
+
+pub struct Model {
+    // Keeps the objects and users of a model apart from the ones of other models in the same DB
+    namespace: u64,
+    users: Vec<String>,
+    bounties: Vec<ModelBounty>,
+    builds: Vec<ModelBuild>,
+    // First and last timestamp at which a user's stats changed
+    user_timestamps: BTreeMap<String, (i64, i64)>,
+}
+
+impl Model {
+    pub fn new(namespace: u64, user_count: usize) -> Self {
+        let mut model = Self {
+            namespace,
+            users: vec![],
+            bounties: vec![],
+            builds: vec![],
+            user_timestamps: BTreeMap::new(),
+        };
+        model.users = (0..user_count).map(|idx| model.new_addr(0, idx)).collect();
+        model
+    }
+
+    fn new_addr(&self, kind: u64, idx: usize) -> String {
+        format!("0x{:016x}{:016x}{:032x}", self.namespace, kind, idx)
+    }
+
+    fn touch_user(&mut self, user_addr: &str, timestamp: i64) {
+        let timestamps = self
+            .user_timestamps
+            .entry(user_addr.to_string())
+            .or_insert((timestamp, timestamp));
+        timestamps.1 = timestamp;
+    }
+
+    /// Apply an operation at the given time, later operations must not have earlier timestamps.
+    /// Returns the event the contract emits, None if the contract would abort.
+    pub fn apply(&mut self, operation: &Operation, timestamp: i64) -> Option<EventPB> {
+        match *operation {
+            Operation::CreateBounty {
+                creator,
+                payment_per_winner,
+                winner_limit,
+            } => {
+                let fixture = BountyFixture {
+                    bounty_obj_addr: self.new_addr(1, self.bounties.len()),
+                    creator: self.users[creator % self.users.len()].clone(),
+                    create_timestamp: timestamp,
+                    last_update_timestamp: timestamp,
+                    end_timestamp: END_TIMESTAMP,
+                    payment_per_winner,
+                    winner_count: 0,
+                    winner_limit,
+                };
+                self.touch_user(&fixture.creator, timestamp);
+                let event = fixture.create_event();
+                self.bounties.push(ModelBounty {
+                    fixture,
+                    ended: false,
+                    refunded_amount: 0,
+                });
+                Some(event)
+            }
+            Operation::EndBounty { bounty } => {
+                let bounty = pick(&mut self.bounties, bounty)?;
+                if bounty.ended {
+                    return None;
+                }
+                bounty.ended = true;
+                bounty.fixture.end_timestamp = timestamp;
+                bounty.refunded_amount = bounty.fixture.payment_per_winner
+                    * (bounty.fixture.winner_limit - bounty.fixture.winner_count);
+                let event = bounty.fixture.end_event(bounty.refunded_amount);
+                let creator = bounty.fixture.creator.clone();
+                self.touch_user(&creator, timestamp);
+                Some(event)
+            }
+            Operation::CreateBuild { bounty, creator } => {
+                let bounty_idx = bounty % self.bounties.len().max(1);
+                let bounty = self.bounties.get(bounty_idx)?;
+                let creator = self.users[creator % self.users.len()].clone();
+                let has_build = self
+                    .builds
+                    .iter()
+                    .any(|build| build.bounty == bounty_idx && build.fixture.creator == creator);
+                if bounty.ended
+                    || bounty.fixture.winner_count >= bounty.fixture.winner_limit
+                    || has_build
+                {
+                    return None;
+                }
+                let fixture = BuildFixture {
+                    build_obj_addr: self.new_addr(2, self.builds.len()),
+                    bounty_obj_addr: bounty.fixture.bounty_obj_addr.clone(),
+                    creator,
+                    payment_amount: 0,
+                    create_timestamp: timestamp,
+                    last_update_timestamp: timestamp,
+                    proof_link: String::new(),
+                    status: BUILD_STATUS_IN_PROGRESS,
+                };
+                self.touch_user(&fixture.creator, timestamp);
+                let event = fixture.create_event();
+                self.builds.push(ModelBuild {
+                    fixture,
+                    bounty: bounty_idx,
+                    submit_timestamp: None,
+                });
+                Some(event)
+            }
+            Operation::SubmitBuildForReview { build } => {
+                let build = pick(&mut self.builds, build)?;
+                if build.fixture.status != BUILD_STATUS_IN_PROGRESS {
+                    return None;
+                }
+                build.fixture.status = BUILD_STATUS_READY_FOR_REVIEW;
+                build.fixture.last_update_timestamp = timestamp;
+                build.fixture.proof_link = format!("https://github.com/o/r/pull/{}", timestamp);
+                build.submit_timestamp = Some(timestamp);
+                let event = build.fixture.submit_for_review_event();
+                let creator = build.fixture.creator.clone();
+                self.touch_user(&creator, timestamp);
+                Some(event)
+            }
+            Operation::CancelBuild { build } => {
+                let build = pick(&mut self.builds, build)?;
+                if build.fixture.status != BUILD_STATUS_IN_PROGRESS {
+                    return None;
+                }
+                build.fixture.status = BUILD_STATUS_CANCELED;
+                build.fixture.last_update_timestamp = timestamp;
+                let event = build.fixture.cancel_event();
+                let creator = build.fixture.creator.clone();
+                self.touch_user(&creator, timestamp);
+                Some(event)
+            }
+            Operation::AcceptBuild { build } => {
+                let build = pick(&mut self.builds, build)?;
+                let bounty = &mut self.bounties[build.bounty];
+                // Once ended or fully paid the bounty has no funds left for the payment
+                if build.fixture.status != BUILD_STATUS_READY_FOR_REVIEW
+                    || bounty.ended
+                    || bounty.fixture.winner_count >= bounty.fixture.winner_limit
+                {
+                    return None;
+                }
+                bounty.fixture.winner_count += 1;
+                bounty.fixture.last_update_timestamp = timestamp;
+                build.fixture.status = BUILD_STATUS_COMPLETED;
+                build.fixture.last_update_timestamp = timestamp;
+                build.fixture.payment_amount = bounty.fixture.payment_per_winner;
+                let event = build.fixture.accept_event(&bounty.fixture);
+                let creator = build.fixture.creator.clone();
+                self.touch_user(&creator, timestamp);
+                Some(event)
+            }
+        }
+    }
+
+    pub fn expected_bounties(&self) -> Vec<(String, BountyRow)> {
+        self.bounties
+            .iter()
+            .map(|bounty| {
+                let fixture = &bounty.fixture;
+                let row = BountyRow {
+                    last_update_timestamp: fixture.last_update_timestamp,
+                    end_timestamp: fixture.end_timestamp,
+                    winner_count: fixture.winner_count as i64,
+                    total_payment: BigDecimal::from(
+                        fixture.payment_per_winner * fixture.winner_limit,
+                    ),
+                    refunded_amount: BigDecimal::from(bounty.refunded_amount),
+                };
+                (fixture.bounty_obj_addr.clone(), row)
+            })
+            .collect()
+    }
+
+    pub fn expected_builds(&self) -> Vec<(String, BuildRow)> {
+        self.builds
+            .iter()
+            .map(|build| {
+                let fixture = &build.fixture;
+                let row = BuildRow {
+                    last_update_timestamp: fixture.last_update_timestamp,
+                    build_status: fixture.status,
+                    proof_link: fixture.proof_link.clone(),
+                    submit_timestamp: build.submit_timestamp,
+                    payment_amount: BigDecimal::from(fixture.payment_amount),
+                };
+                (fixture.build_obj_addr.clone(), row)
+            })
+            .collect()
+    }
+
+    /// Stats of every user who created a bounty or a build, all bounties are paid in APT
+    pub fn expected_user_stats(&self) -> Vec<UserStat> {
+        self.user_timestamps
+            .iter()
+            .map(|(user_addr, &(create_timestamp, last_update_timestamp))| {
+                let bounties = self
+                    .bounties
+                    .iter()
+                    .filter(|bounty| &bounty.fixture.creator == user_addr)
+                    .collect::<Vec<_>>();
+                let builds = self
+                    .builds
+                    .iter()
+                    .filter(|build| &build.fixture.creator == user_addr)
+                    .collect::<Vec<_>>();
+                let count_builds = |status: i64| {
+                    builds
+                        .iter()
+                        .filter(|build| build.fixture.status == status)
+                        .count() as i64
+                };
+                let apt_spent = bounties
+                    .iter()
+                    .map(|bounty| {
+                        bounty.fixture.payment_per_winner * bounty.fixture.winner_limit
+                            - bounty.refunded_amount
+                    })
+                    .sum::<u64>();
+                let apt_received = builds
+                    .iter()
+                    .map(|build| build.fixture.payment_amount)
+                    .sum::<u64>();
+                let build_completed = count_builds(BUILD_STATUS_COMPLETED);
+                let points = POINT_PER_NEW_BOUNTY * bounties.len() as i64
+                    + POINT_PER_NEW_BUILD * builds.len() as i64
+                    + POINT_PER_ACCEPT_BUILD * build_completed;
+                UserStat {
+                    last_update_timestamp,
+                    bounty_created: bounties.len() as i64,
+                    apt_spent: BigDecimal::from(apt_spent),
+                    build_created: builds.len() as i64,
+                    build_submitted_for_review: count_builds(BUILD_STATUS_READY_FOR_REVIEW),
+                    build_canceled: count_builds(BUILD_STATUS_CANCELED),
+                    build_completed,
+                    apt_received: BigDecimal::from(apt_received),
+                    season_1_points: points,
+                    total_points: points,
+                    ..UserStat::new_change(user_addr, create_timestamp)
+                }
+            })
+            .collect()
+    }
+}
+
+fn pick<T>(items: &mut [T], idx: usize) -> Option<&mut T> {
+    let len = items.len();
+    items.get_mut(idx % len.max(1))
+}
@@ -0,0 +1,199 @@
+//! Property tests of the storers against the reference model in tests/common/model.rs: random
+//! valid event sequences, grouped in transactions of one or more events, often of the same second,
+//! split into random batches and written with random chunk sizes, must leave the same bounties,
+//! builds and user stats as the model. Indexing some batches again must not change the bounties
+//! and builds. All cases share one Postgres container, run them with
+//! `cargo test --test stats_model -- --ignored` where docker is available.
+
+mod common;
+
+use common::{
+    model::{Model, Operation},
+    TestIndexer,
+};
+use indexer::config::indexer_processor_config::PipelineConfig;
+use proptest::{
+    prelude::*,
+    test_runner::{Config, TestCaseResult, TestRunner},
+};
+use std::cell::{Cell, RefCell};
+
+const USERS_PER_CASE: usize = 4;
+// Each case runs a few dozen batches against Postgres, the default of 256 cases takes too long
+const CASES: u32 = 32;
+
+#[derive(Debug)]
+struct Scenario {
+    operations: Vec<Operation>,
+    // Events per transaction, cycled until every event is in a transaction
+    transaction_sizes: Vec<usize>,
+    // Seconds from a transaction to the next one, cycled, 0 puts them in the same second
+    timestamp_steps: Vec<i64>,
+    // Transactions per batch, cycled until every transaction is processed
+    batch_sizes: Vec<usize>,
+    // Once every batch is indexed, each Some(n) indexes again the batch n batches before the last
+    // one, like a restart from an older checkpoint
+    replays: Vec<Option<usize>>,
+    bounties_chunk_size: usize,
+    builds_chunk_size: usize,
+    user_stats_chunk_size: usize,
+    max_concurrent_db_tasks: Option<usize>,
+    commit_batch_atomically: bool,
+}
+
+fn operation_strategy() -> impl Strategy<Value = Operation> {
+    // Weighted towards builds moving forward, so most bounties get winners before they end
+    prop_oneof![
+        2 => (any::<usize>(), 1..1_000u64, 1..4u64).prop_map(
+            |(creator, payment_per_winner, winner_limit)| Operation::CreateBounty {
+                creator,
+                payment_per_winner,
+                winner_limit,
+            }
+        ),
+        1 => any::<usize>().prop_map(|bounty| Operation::EndBounty { bounty }),
+        3 => (any::<usize>(), any::<usize>())
+            .prop_map(|(bounty, creator)| Operation::CreateBuild { bounty, creator }),
+        3 => any::<usize>().prop_map(|build| Operation::SubmitBuildForReview { build }),
+        1 => any::<usize>().prop_map(|build| Operation::CancelBuild { build }),
+        3 => any::<usize>().prop_map(|build| Operation::AcceptBuild { build }),
+    ]
+}
+
+fn scenario_strategy() -> impl Strategy<Value = Scenario> {
+    (
+        prop::collection::vec(operation_strategy(), 1..60),
+        prop::collection::vec(1..=3usize, 1..5),
+        prop::collection::vec(0..=2i64, 1..5),
+        prop::collection::vec(1..8usize, 1..10),
+        prop::collection::vec(prop::option::weighted(0.2, 0..3usize), 1..5),
+        (1..=5usize, 1..=5usize, 1..=5usize),
+        prop::option::of(1..4usize),
+        any::<bool>(),
+    )
+        .prop_map(
+            |(
+                operations,
+                transaction_sizes,
+                timestamp_steps,
+                batch_sizes,
+                replays,
+                (bounties_chunk_size, builds_chunk_size, user_stats_chunk_size),
+                max_concurrent_db_tasks,
+                commit_batch_atomically,
+            )| Scenario {
+                operations,
+                transaction_sizes,
+                timestamp_steps,
+                batch_sizes,
+                replays,
+                bounties_chunk_size,
+                builds_chunk_size,
+                user_stats_chunk_size,
+                max_concurrent_db_tasks,
+                commit_batch_atomically,
+            },
+        )
+}
+
+async fn check_scenario(
+    indexer: &mut TestIndexer,
+    namespace: u64,
+    scenario: Scenario,
+) -> TestCaseResult {
+    let mut pipeline_config = PipelineConfig {
+        max_concurrent_db_tasks: scenario.max_concurrent_db_tasks,
+        ..PipelineConfig::default()
+    };
+    for (table_name, chunk_size) in [
+        ("bounties", scenario.bounties_chunk_size),
+        ("builds", scenario.builds_chunk_size),
+        ("user_stats", scenario.user_stats_chunk_size),
+    ] {
+        pipeline_config
+            .per_table_chunk_sizes
+            .insert(table_name.to_string(), chunk_size);
+    }
+    indexer.set_storer_config(scenario.commit_batch_atomically, pipeline_config);
+
+    // Events of the valid operations in the order the chain would have them
+    let mut model = Model::new(namespace, USERS_PER_CASE);
+    let mut transaction_sizes = scenario.transaction_sizes.iter().cycle();
+    let mut timestamp_steps = scenario.timestamp_steps.iter().cycle();
+    let mut transactions = vec![];
+    let mut transaction_size = *transaction_sizes.next().unwrap();
+    let mut timestamp = 1_000;
+    let mut events = vec![];
+    for operation in scenario.operations.iter() {
+        events.extend(model.apply(operation, timestamp));
+        if events.len() == transaction_size {
+            transactions.push((timestamp, std::mem::take(&mut events)));
+            transaction_size = *transaction_sizes.next().unwrap();
+            timestamp += timestamp_steps.next().unwrap();
+        }
+    }
+    if !events.is_empty() {
+        transactions.push((timestamp, events));
+    }
+
+    let mut transactions = transactions.into_iter();
+    let mut indexed_batches = vec![];
+    for &batch_size in scenario.batch_sizes.iter().cycle() {
+        let batch = transactions.by_ref().take(batch_size).collect::<Vec<_>>();
+        if batch.is_empty() {
+            break;
+        }
+        let start_version = indexer.process_batch(batch.clone()).await;
+        indexed_batches.push((start_version, batch));
+    }
+    check_rows(indexer, &model).await?;
+    for expected in model.expected_user_stats() {
+        prop_assert_eq!(indexer.get_user_stat(&expected.user_addr).await, expected);
+    }
+
+    // User stats are deltas, a replayed batch counts its events again. Only the rows are
+    // idempotent, so only they are checked again.
+    for replay in scenario.replays.iter() {
+        if let Some((start_version, batch)) =
+            replay.and_then(|back| indexed_batches.iter().rev().nth(back))
+        {
+            indexer.reprocess_batch(*start_version, batch.clone()).await;
+        }
+    }
+    check_rows(indexer, &model).await
+}
+
+async fn check_rows(indexer: &TestIndexer, model: &Model) -> TestCaseResult {
+    for (bounty_obj_addr, expected) in model.expected_bounties() {
+        prop_assert_eq!(indexer.get_bounty(&bounty_obj_addr).await, expected);
+    }
+    for (build_obj_addr, expected) in model.expected_builds() {
+        prop_assert_eq!(indexer.get_build(&build_obj_addr).await, expected);
+    }
+    Ok(())
+}
+
+#[test]
+#[ignore = "starts a Postgres container, needs docker"]
+fn test_storers_match_model() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let indexer = RefCell::new(runtime.block_on(TestIndexer::start(false)));
+    // Every case, shrunk ones included, writes to objects and users of its own
+    let namespace = Cell::new(0);
+
+    let mut runner = TestRunner::new(Config {
+        cases: CASES,
+        ..Config::default()
+    });
+    let result = runner.run(&scenario_strategy(), |scenario| {
+        namespace.set(namespace.get() + 1);
+        runtime.block_on(check_scenario(
+            &mut indexer.borrow_mut(),
+            namespace.get(),
+            scenario,
+        ))
+    });
+    if let Err(e) = result {
+        panic!("{}", e);
+    }
+}
@@ -17,6 +17,25 @@ import { Build } from "@/lib/type/build";
 import { getBuild, getBuildProps } from "@/db/getBuild";
 import { getUserStat, getUserStatProps } from "@/db/getUserStat";
 import { APT_FA_ADDR, APT_UNIT, getSurfClient } from "@/lib/aptos";
+import {
+  LeaderboardRankDelta,
+  LeaderboardSnapshot,
+} from "@/lib/type/leaderboard_snapshot";
+import {
+  getUserRankHistory,
+  getUserRankHistoryProps,
+} from "@/db/getUserRankHistory";
+import { getRankDeltas, getRankDeltasProps } from "@/db/getRankDeltas";
+import { PointsLedgerEntry } from "@/lib/type/points_ledger_entry";
+import {
+  getUserPointsHistory,
+  getUserPointsHistoryProps,
+} from "@/db/getUserPointsHistory";
+import { searchBounties, searchBountiesProps } from "@/db/searchBounties";
+import {
+  BountySearchFacets,
+  BountySearchResult,
+} from "@/lib/type/bounty_search";
 
 export const getBountiesOnServer = async ({
   page,
@@ -85,6 +104,48 @@ export const getUserStatOnServer = async ({
   return getUserStat({ userAddr });
 };
 
+export const getUserRankHistoryOnServer = async ({
+  userAddr,
+  season,
+}: getUserRankHistoryProps): Promise<{
+  snapshots: LeaderboardSnapshot[];
+}> => {
+  return getUserRankHistory({ userAddr, season });
+};
+
+export const getRankDeltasOnServer = async ({
+  season,
+  page,
+  limit,
+}: getRankDeltasProps): Promise<{
+  rankDeltas: LeaderboardRankDelta[];
+}> => {
+  return getRankDeltas({ season, page, limit });
+};
+
+export const getUserPointsHistoryOnServer = async ({
+  userAddr,
+  page,
+  limit,
+}: getUserPointsHistoryProps): Promise<{
+  entries: PointsLedgerEntry[];
+}> => {
+  return getUserPointsHistory({ userAddr, page, limit });
+};
+
+export const searchBountiesOnServer = async ({
+  query,
+  page,
+  limit,
+  filter,
+}: searchBountiesProps): Promise<{
+  results: BountySearchResult[];
+  total: number;
+  facets: BountySearchFacets;
+}> => {
+  return searchBounties({ query, page, limit, filter });
+};
+
 // export const getAPTPriceFromCMCOnServer = async (): Promise<number> => {
 //   const parameters = {
 //     method: "GET",
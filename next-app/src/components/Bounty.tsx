@@ -1,7 +1,6 @@
 "use client";
 
 import { useQuery } from "@tanstack/react-query";
-import { convertAmountFromOnChainToHumanReadable } from "@aptos-labs/ts-sdk";
 import { truncateAddress, useWallet } from "@aptos-labs/wallet-adapter-react";
 
 import {
@@ -12,7 +11,7 @@ import {
   CardFooter,
 } from "@/components/ui/card";
 import { LabelValueGrid } from "@/components/LabelValueGrid";
-import { APT_UNIT, getSurfClient, KNOWN_PAYMENT, NETWORK } from "@/lib/aptos";
+import { getSurfClient, KNOWN_PAYMENT, NETWORK } from "@/lib/aptos";
 import { getBountyOnServer } from "@/app/actions";
 import { isMaxUnixTimestamp } from "@/lib/time";
 import { CreateBuild } from "@/components/CreateBuild";
@@ -173,11 +172,13 @@ export const Bounty = ({ bountyObjAddr }: BountyProps) => {
                   label: "Stake required",
                   value: (
                     <p>
-                      {convertAmountFromOnChainToHumanReadable(
-                        data.bounty.stake_required,
-                        APT_UNIT
-                      )}{" "}
-                      APT
+                      {data.bounty.stake_required_display ??
+                        data.bounty.stake_required}{" "}
+                      {
+                        KNOWN_PAYMENT.get(
+                          data.bounty.payment_metadata_obj_addr
+                        )!.ticker
+                      }
                     </p>
                   ),
                 },
@@ -193,12 +194,8 @@ export const Bounty = ({ bountyObjAddr }: BountyProps) => {
                   label: "Payment per winner",
                   value: (
                     <p>
-                      {convertAmountFromOnChainToHumanReadable(
-                        data.bounty.payment_per_winner,
-                        KNOWN_PAYMENT.get(
-                          data.bounty.payment_metadata_obj_addr
-                        )!.unit
-                      )}{" "}
+                      {data.bounty.payment_per_winner_display ??
+                        data.bounty.payment_per_winner}{" "}
                       {
                         KNOWN_PAYMENT.get(
                           data.bounty.payment_metadata_obj_addr
@@ -211,12 +208,8 @@ export const Bounty = ({ bountyObjAddr }: BountyProps) => {
                   label: "Total payment",
                   value: (
                     <p>
-                      {convertAmountFromOnChainToHumanReadable(
-                        data.bounty.total_payment,
-                        KNOWN_PAYMENT.get(
-                          data.bounty.payment_metadata_obj_addr
-                        )!.unit
-                      )}{" "}
+                      {data.bounty.total_payment_display ??
+                        data.bounty.total_payment}{" "}
                       {
                         KNOWN_PAYMENT.get(
                           data.bounty.payment_metadata_obj_addr
@@ -1,11 +1,10 @@
 "use client";
 
 import { useQuery } from "@tanstack/react-query";
-import { convertAmountFromOnChainToHumanReadable } from "@aptos-labs/ts-sdk";
 
 import { Card, CardHeader, CardTitle, CardContent } from "@/components/ui/card";
 import { LabelValueGrid } from "@/components/LabelValueGrid";
-import { APT_UNIT, NETWORK } from "@/lib/aptos";
+import { NETWORK } from "@/lib/aptos";
 import { getUserStatOnServer } from "@/app/actions";
 import { getAnsNameOrTruncatedAddr } from "@/lib/clientOnlyUtils";
 
@@ -94,19 +93,11 @@ export const UserStat = ({ userAddr }: UserStatProps) => {
               },
               {
                 label: "APT spent",
-                value: (
-                  <p>
-                    {convertAmountFromOnChainToHumanReadable(
-                      data.userStat.apt_spent,
-                      APT_UNIT
-                    )}{" "}
-                    APT
-                  </p>
-                ),
+                value: <p>{data.userStat.apt_spent_display} APT</p>,
               },
               {
                 label: "Stablecoin spent",
-                value: <p>{data.userStat.stable_spent} USD</p>,
+                value: <p>{data.userStat.stable_spent_display} USD</p>,
               },
               {
                 label: "Build created",
@@ -126,19 +117,11 @@ export const UserStat = ({ userAddr }: UserStatProps) => {
               },
               {
                 label: "APT received",
-                value: (
-                  <p>
-                    {convertAmountFromOnChainToHumanReadable(
-                      data.userStat.apt_received,
-                      APT_UNIT
-                    )}{" "}
-                    APT
-                  </p>
-                ),
+                value: <p>{data.userStat.apt_received_display} APT</p>,
               },
               {
                 label: "Stablecoin received",
-                value: <p>{data.userStat.stable_received} USD</p>,
+                value: <p>{data.userStat.stable_received_display} USD</p>,
               },
               // {
               //   label: "Season 1 points",
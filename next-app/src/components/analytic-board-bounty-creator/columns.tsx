@@ -1,10 +1,9 @@
 "use client";
 
 import { ColumnDef } from "@tanstack/react-table";
-import { convertAmountFromOnChainToHumanReadable } from "@aptos-labs/ts-sdk";
 
 import { DataTableColumnHeader } from "@/components/ui/data-table-column-header";
-import { APT_UNIT } from "@/lib/aptos";
+import { compareAmounts } from "@/lib/utils";
 import { UserStat } from "@/lib/type/user_stat";
 import { AddressTableCell } from "@/components/AddressTableCell";
 
@@ -23,14 +22,13 @@ export const columns: ColumnDef<UserStat>[] = [
       <DataTableColumnHeader column={column} title="APT spent" />
     ),
     cell: ({ row }) => (
-      <div className="w-[100px]">
-        {convertAmountFromOnChainToHumanReadable(
-          row.original.apt_spent,
-          APT_UNIT
-        )}{" "}
-        APT
-      </div>
+      <div className="w-[100px]">{row.original.apt_spent_display} APT</div>
     ),
+    sortingFn: (a, b, columnId) =>
+      compareAmounts(
+        a.getValue<string>(columnId),
+        b.getValue<string>(columnId)
+      ),
     enableSorting: true,
   },
   {
@@ -39,14 +37,13 @@ export const columns: ColumnDef<UserStat>[] = [
       <DataTableColumnHeader column={column} title="Stablecoin spent" />
     ),
     cell: ({ row }) => (
-      <div className="w-[100px]">
-        {convertAmountFromOnChainToHumanReadable(
-          row.original.stable_spent,
-          APT_UNIT // TODO: use stablecoin unit after we have stablecoin on Aptos
-        )}{" "}
-        USD
-      </div>
+      <div className="w-[100px]">{row.original.stable_spent_display} USD</div>
     ),
+    sortingFn: (a, b, columnId) =>
+      compareAmounts(
+        a.getValue<string>(columnId),
+        b.getValue<string>(columnId)
+      ),
     enableSorting: true,
   },
   {
@@ -1,10 +1,9 @@
 "use client";
 
 import { ColumnDef } from "@tanstack/react-table";
-import { convertAmountFromOnChainToHumanReadable } from "@aptos-labs/ts-sdk";
 
 import { DataTableColumnHeader } from "@/components/ui/data-table-column-header";
-import { APT_UNIT } from "@/lib/aptos";
+import { compareAmounts } from "@/lib/utils";
 import { UserStat } from "@/lib/type/user_stat";
 import { AddressTableCell } from "@/components/AddressTableCell";
 
@@ -23,14 +22,13 @@ export const columns: ColumnDef<UserStat>[] = [
       <DataTableColumnHeader column={column} title="APT received" />
     ),
     cell: ({ row }) => (
-      <div className="w-[100px]">
-        {convertAmountFromOnChainToHumanReadable(
-          row.original.apt_received,
-          APT_UNIT
-        )}{" "}
-        APT
-      </div>
+      <div className="w-[100px]">{row.original.apt_received_display} APT</div>
     ),
+    sortingFn: (a, b, columnId) =>
+      compareAmounts(
+        a.getValue<string>(columnId),
+        b.getValue<string>(columnId)
+      ),
     enableSorting: true,
   },
   {
@@ -40,13 +38,14 @@ export const columns: ColumnDef<UserStat>[] = [
     ),
     cell: ({ row }) => (
       <div className="w-[100px]">
-        {convertAmountFromOnChainToHumanReadable(
-          row.original.stable_received,
-          APT_UNIT // TODO: use stablecoin unit after we have stablecoin on Aptos
-        )}{" "}
-        USD
+        {row.original.stable_received_display} USD
       </div>
     ),
+    sortingFn: (a, b, columnId) =>
+      compareAmounts(
+        a.getValue<string>(columnId),
+        b.getValue<string>(columnId)
+      ),
     enableSorting: true,
   },
   {
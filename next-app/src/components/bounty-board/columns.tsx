@@ -1,10 +1,10 @@
 "use client";
 
 import { ColumnDef } from "@tanstack/react-table";
-import { convertAmountFromOnChainToHumanReadable } from "@aptos-labs/ts-sdk";
 
 import { Bounty, convertBountyStatusToHumanReadable } from "@/lib/type/bounty";
-import { APT_UNIT } from "@/lib/aptos";
+import { KNOWN_PAYMENT } from "@/lib/aptos";
+import { compareAmounts } from "@/lib/utils";
 import { DataTableColumnHeader } from "@/components/ui/data-table-column-header";
 
 export const columns: ColumnDef<Bounty>[] = [
@@ -44,13 +44,16 @@ export const columns: ColumnDef<Bounty>[] = [
     ),
     cell: ({ row }) => (
       <div className="w-[160px]">
-        {convertAmountFromOnChainToHumanReadable(
-          row.getValue("total_payment"),
-          APT_UNIT
-        )}{" "}
-        APT
+        {row.original.total_payment_display ??
+          row.original.total_payment}{" "}
+        {KNOWN_PAYMENT.get(row.original.payment_metadata_obj_addr)?.ticker}
       </div>
     ),
+    sortingFn: (a, b, columnId) =>
+      compareAmounts(
+        a.getValue<string>(columnId),
+        b.getValue<string>(columnId)
+      ),
     enableSorting: true,
   },
   {
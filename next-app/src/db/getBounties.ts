@@ -1,5 +1,9 @@
 import { getPostgresClient } from "@/lib/db";
-import { Bounty, convertDbBountyRowToBounty } from "@/lib/type/bounty";
+import {
+  Bounty,
+  PAYMENT_DECIMALS_SQL,
+  convertDbBountyRowToBounty,
+} from "@/lib/type/bounty";
 
 export type getBountiesProps = {
   page: number;
@@ -20,7 +24,7 @@ export const getBounties = async ({
   total: number;
 }> => {
   const bounties = await getPostgresClient()(
-    `SELECT * FROM bounties ${
+    `SELECT bounties.*, ${PAYMENT_DECIMALS_SQL} FROM bounties ${
       filter && `WHERE ${filter}`
     } ORDER BY ${sortedBy} ${order} LIMIT ${limit} OFFSET ${(page - 1) * limit}`
   ).then((rows) => {
@@ -1,5 +1,9 @@
 import { getPostgresClient } from "@/lib/db";
-import { Bounty, convertDbBountyRowToBounty } from "@/lib/type/bounty";
+import {
+  Bounty,
+  PAYMENT_DECIMALS_SQL,
+  convertDbBountyRowToBounty,
+} from "@/lib/type/bounty";
 
 export type getBountyProps = {
   bountyObjAddr: `0x${string}`;
@@ -11,7 +15,7 @@ export const getBounty = async ({
   bounty: Bounty;
 }> => {
   const bounty = await getPostgresClient()(
-    `SELECT * FROM bounties WHERE bounty_obj_addr = '${bountyObjAddr}'`
+    `SELECT bounties.*, ${PAYMENT_DECIMALS_SQL} FROM bounties WHERE bounty_obj_addr = '${bountyObjAddr}'`
   ).then((rows) => {
     if (rows.length === 0) {
       throw new Error("Bounty not found");
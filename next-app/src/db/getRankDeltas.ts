@@ -0,0 +1,47 @@
+import { getPostgresClient } from "@/lib/db";
+import {
+  convertDbLeaderboardRankDeltaRowToLeaderboardRankDelta,
+  LeaderboardRankDelta,
+  LeaderboardSeason,
+} from "@/lib/type/leaderboard_snapshot";
+
+export type getRankDeltasProps = {
+  season: LeaderboardSeason;
+  page: number;
+  limit: number;
+};
+
+// Compare the latest snapshot of the season with the one before it
+export const getRankDeltas = async ({
+  season,
+  page,
+  limit,
+}: getRankDeltasProps): Promise<{
+  rankDeltas: LeaderboardRankDelta[];
+}> => {
+  const rankDeltas = await getPostgresClient()(
+    `WITH latest_versions AS (
+        SELECT DISTINCT snapshot_version FROM leaderboard_snapshots
+        WHERE season = $1 ORDER BY snapshot_version DESC LIMIT 2
+    )
+    SELECT
+        current.user_addr,
+        current.rank,
+        current.points,
+        previous.rank AS previous_rank,
+        previous.rank - current.rank AS rank_delta
+    FROM leaderboard_snapshots current
+    LEFT JOIN leaderboard_snapshots previous
+        ON previous.season = current.season
+        AND previous.user_addr = current.user_addr
+        AND previous.snapshot_version = (SELECT MIN(snapshot_version) FROM latest_versions)
+        AND previous.snapshot_version < current.snapshot_version
+    WHERE current.season = $1
+        AND current.snapshot_version = (SELECT MAX(snapshot_version) FROM latest_versions)
+    ORDER BY current.rank ASC LIMIT $2 OFFSET $3`,
+    [season, limit, (page - 1) * limit]
+  ).then((rows) => {
+    return rows.map(convertDbLeaderboardRankDeltaRowToLeaderboardRankDelta);
+  });
+  return { rankDeltas };
+};
@@ -0,0 +1,28 @@
+import { getPostgresClient } from "@/lib/db";
+import {
+  convertDbPointsLedgerEntryRowToPointsLedgerEntry,
+  PointsLedgerEntry,
+} from "@/lib/type/points_ledger_entry";
+
+export type getUserPointsHistoryProps = {
+  userAddr: `0x${string}`;
+  page: number;
+  limit: number;
+};
+
+// Newest first, each entry is one point award or penalty
+export const getUserPointsHistory = async ({
+  userAddr,
+  page,
+  limit,
+}: getUserPointsHistoryProps): Promise<{
+  entries: PointsLedgerEntry[];
+}> => {
+  const entries = await getPostgresClient()(
+    `SELECT * FROM points_ledger WHERE user_addr = $1 ORDER BY txn_version DESC, event_type ASC LIMIT $2 OFFSET $3`,
+    [userAddr, limit, (page - 1) * limit]
+  ).then((rows) => {
+    return rows.map(convertDbPointsLedgerEntryRowToPointsLedgerEntry);
+  });
+  return { entries };
+};
@@ -0,0 +1,26 @@
+import { getPostgresClient } from "@/lib/db";
+import {
+  convertDbLeaderboardSnapshotRowToLeaderboardSnapshot,
+  LeaderboardSeason,
+  LeaderboardSnapshot,
+} from "@/lib/type/leaderboard_snapshot";
+
+export type getUserRankHistoryProps = {
+  userAddr: `0x${string}`;
+  season: LeaderboardSeason;
+};
+
+export const getUserRankHistory = async ({
+  userAddr,
+  season,
+}: getUserRankHistoryProps): Promise<{
+  snapshots: LeaderboardSnapshot[];
+}> => {
+  const snapshots = await getPostgresClient()(
+    `SELECT * FROM leaderboard_snapshots WHERE user_addr = $1 AND season = $2 ORDER BY snapshot_version ASC`,
+    [userAddr, season]
+  ).then((rows) => {
+    return rows.map(convertDbLeaderboardSnapshotRowToLeaderboardSnapshot);
+  });
+  return { snapshots };
+};
@@ -20,16 +20,20 @@ export const getUserStat = async ({
         create_timestamp: 0,
         last_update_timestamp: 0,
         bounty_created: 0,
-        apt_spent: 0,
-        stable_spent: 0,
+        apt_spent: "0",
+        stable_spent: "0",
         build_created: 0,
         build_submitted_for_review: 0,
         build_canceled: 0,
         build_completed: 0,
-        apt_received: 0,
-        stable_received: 0,
+        apt_received: "0",
+        stable_received: "0",
         season_1_points: 0,
         total_points: 0,
+        apt_spent_display: "0",
+        stable_spent_display: "0",
+        apt_received_display: "0",
+        stable_received_display: "0",
       };
     }
     return convertDbUserStatRowToUserStat(rows[0]);
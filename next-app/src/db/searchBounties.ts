@@ -0,0 +1,144 @@
+import { getPostgresClient } from "@/lib/db";
+import { PAYMENT_DECIMALS_SQL } from "@/lib/type/bounty";
+import {
+  BountySearchFacets,
+  BountySearchFilter,
+  BountySearchResult,
+  convertDbBountySearchRowToBountySearchResult,
+  HIGHLIGHT_START,
+  HIGHLIGHT_STOP,
+} from "@/lib/type/bounty_search";
+
+export type searchBountiesProps = {
+  query: string;
+  page: number;
+  limit: number;
+  filter?: BountySearchFilter;
+};
+
+// Must match convertBountyStatusToHumanReadable
+const STATUS_SQL = `CASE WHEN bounties.end_timestamp < EXTRACT(EPOCH FROM NOW()) OR bounties.winner_count = bounties.winner_limit THEN 'Closed' ELSE 'Open' END`;
+
+// Matches are marked with characters parseHighlight splits on, not with HTML tags
+const HIGHLIGHT_OPTIONS = `StartSel=${HIGHLIGHT_START}, StopSel=${HIGHLIGHT_STOP}`;
+
+// Search text is user input so it's always passed as a parameter
+export const searchBounties = async ({
+  query,
+  page,
+  limit,
+  filter,
+}: searchBountiesProps): Promise<{
+  results: BountySearchResult[];
+  total: number;
+  facets: BountySearchFacets;
+}> => {
+  const params: any[] = [query];
+  const conditions = [
+    `bounty_search_vector(bounty_search_documents.title, bounty_search_documents.description_text) @@ websearch_to_tsquery('english', $1)`,
+  ];
+  const addCondition = (condition: (param: string) => string, value: any) => {
+    params.push(value);
+    conditions.push(condition(`$${params.length}`));
+  };
+  if (filter?.paymentMetadataObjAddr !== undefined) {
+    addCondition(
+      (p) => `bounties.payment_metadata_obj_addr = ${p}`,
+      filter.paymentMetadataObjAddr
+    );
+  }
+  if (filter?.minPaymentPerWinner !== undefined) {
+    addCondition(
+      (p) => `bounties.payment_per_winner >= ${p}`,
+      filter.minPaymentPerWinner
+    );
+  }
+  if (filter?.maxPaymentPerWinner !== undefined) {
+    addCondition(
+      (p) => `bounties.payment_per_winner <= ${p}`,
+      filter.maxPaymentPerWinner
+    );
+  }
+  if (filter?.status !== undefined) {
+    addCondition((p) => `${STATUS_SQL} = ${p}`, filter.status);
+  }
+  if (filter?.hasStake !== undefined) {
+    addCondition(
+      (p) => `(bounties.stake_required > 0) = ${p}`,
+      filter.hasStake
+    );
+  }
+  const fromAndWhere = `FROM bounty_search_documents
+    JOIN bounties ON bounties.bounty_obj_addr = bounty_search_documents.bounty_obj_addr
+    WHERE ${conditions.join(" AND ")}`;
+
+  const results = await getPostgresClient()(
+    `SELECT
+        bounties.*,
+        ${PAYMENT_DECIMALS_SQL},
+        ts_rank_cd(bounty_search_vector(bounty_search_documents.title, bounty_search_documents.description_text), websearch_to_tsquery('english', $1)) AS rank,
+        ts_headline('english', bounty_search_documents.title, websearch_to_tsquery('english', $1), '${HIGHLIGHT_OPTIONS}, HighlightAll=true') AS title_highlight,
+        ts_headline('english', bounty_search_documents.description_text, websearch_to_tsquery('english', $1), '${HIGHLIGHT_OPTIONS}, MaxFragments=2') AS description_highlight
+    ${fromAndWhere}
+    ORDER BY rank DESC, bounties.create_timestamp DESC
+    LIMIT ${limit} OFFSET ${(page - 1) * limit}`,
+    params
+  ).then((rows) => {
+    return rows.map(convertDbBountySearchRowToBountySearchResult);
+  });
+
+  // Facets are counted over all matches, not only the current page
+  const facetRows = await getPostgresClient()(
+    `SELECT
+        'payment_token' AS facet, bounties.payment_metadata_obj_addr AS value, COUNT(*) AS count
+    ${fromAndWhere} GROUP BY bounties.payment_metadata_obj_addr
+    UNION ALL
+    SELECT 'status', ${STATUS_SQL}, COUNT(*)
+    ${fromAndWhere} GROUP BY ${STATUS_SQL}
+    UNION ALL
+    SELECT 'has_stake', (bounties.stake_required > 0)::TEXT, COUNT(*)
+    ${fromAndWhere} GROUP BY bounties.stake_required > 0
+    UNION ALL
+    SELECT 'min_payment_per_winner', MIN(bounties.payment_per_winner)::TEXT, COUNT(*)
+    ${fromAndWhere}
+    UNION ALL
+    SELECT 'max_payment_per_winner', MAX(bounties.payment_per_winner)::TEXT, COUNT(*)
+    ${fromAndWhere}`,
+    params
+  );
+
+  const facets: BountySearchFacets = {
+    payment_tokens: [],
+    statuses: [],
+    has_stake: [],
+    min_payment_per_winner: null,
+    max_payment_per_winner: null,
+  };
+  let total = 0;
+  facetRows.forEach((row) => {
+    const count = parseInt(row.count);
+    switch (row.facet) {
+      case "payment_token":
+        facets.payment_tokens.push({
+          payment_metadata_obj_addr: row.value,
+          count,
+        });
+        total += count;
+        break;
+      case "status":
+        facets.statuses.push({ status: row.value, count });
+        break;
+      case "has_stake":
+        facets.has_stake.push({ has_stake: row.value === "true", count });
+        break;
+      case "min_payment_per_winner":
+        facets.min_payment_per_winner = row.value;
+        break;
+      case "max_payment_per_winner":
+        facets.max_payment_per_winner = row.value;
+        break;
+    }
+  });
+
+  return { results, total, facets };
+};
@@ -21,6 +21,8 @@ export const getSurfClient = () => SURF_CLIENT;
 export const APT_FA_ADDR = "0xa";
 // 1 APT = 10 ^ 8 oAPT
 export const APT_UNIT = 8;
+// USDC and USDT on Aptos have 6 decimals
+export const STABLE_UNIT = 6;
 
 export type FA = {
   ticker: string;
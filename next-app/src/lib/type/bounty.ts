@@ -1,3 +1,5 @@
+import { formatAmount } from "@/lib/utils";
+
 export type Bounty = {
   bounty_obj_addr: `0x${string}`;
   creator_addr: `0x${string}`;
@@ -7,19 +9,37 @@ export type Bounty = {
   title: string;
   description_link: string;
   payment_metadata_obj_addr: `0x${string}`;
-  payment_per_winner: number;
-  stake_required: number;
+  payment_per_winner: string;
+  stake_required: string;
   stake_lockup_in_seconds: number;
   winner_count: number;
   winner_limit: number;
-  total_payment: number;
+  total_payment: string;
   contact_info: string;
   last_update_event_idx: number;
+  contact_handle_type: ContactHandleType;
+  contact_handle: string | null;
+  // amounts are in the token's smallest unit, null when the token is not in payment_tokens
+  payment_decimals: number | null;
+  // amounts in whole tokens, null when payment_decimals is
+  payment_per_winner_display: string | null;
+  stake_required_display: string | null;
+  total_payment_display: string | null;
 };
 
+export type ContactHandleType = "discord" | "x" | "telegram" | "email" | "other";
+
+// Select it next to bounties.* so rows can be passed to convertDbBountyRowToBounty
+export const PAYMENT_DECIMALS_SQL = `(SELECT payment_tokens.decimals FROM payment_tokens
+    WHERE payment_tokens.payment_metadata_obj_addr = bounties.payment_metadata_obj_addr) AS payment_decimals`;
+
 export const convertDbBountyRowToBounty = (
   row: Record<string, any>
 ): Bounty => {
+  const paymentDecimals =
+    row.payment_decimals == null ? null : parseInt(row.payment_decimals);
+  const toDisplay = (amount: string) =>
+    paymentDecimals === null ? null : formatAmount(amount, paymentDecimals);
   return {
     bounty_obj_addr: row.bounty_obj_addr,
     creator_addr: row.creator_addr,
@@ -29,14 +49,20 @@ export const convertDbBountyRowToBounty = (
     title: row.title,
     description_link: row.description_link,
     payment_metadata_obj_addr: row.payment_metadata_obj_addr,
-    payment_per_winner: parseInt(row.payment_per_winner),
-    stake_required: parseInt(row.stake_required),
+    payment_per_winner: row.payment_per_winner,
+    stake_required: row.stake_required,
     stake_lockup_in_seconds: parseInt(row.stake_lockup_in_seconds),
     winner_count: parseInt(row.winner_count),
     winner_limit: parseInt(row.winner_limit),
-    total_payment: parseInt(row.total_payment),
+    total_payment: row.total_payment,
     contact_info: row.contact_info,
     last_update_event_idx: parseInt(row.last_update_event_idx),
+    contact_handle_type: row.contact_handle_type,
+    contact_handle: row.contact_handle,
+    payment_decimals: paymentDecimals,
+    payment_per_winner_display: toDisplay(row.payment_per_winner),
+    stake_required_display: toDisplay(row.stake_required),
+    total_payment_display: toDisplay(row.total_payment),
   };
 };
 
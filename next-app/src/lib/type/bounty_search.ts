@@ -0,0 +1,73 @@
+import { Bounty, convertDbBountyRowToBounty } from "@/lib/type/bounty";
+
+export type BountyStatus = "Open" | "Closed";
+
+export type BountySearchFilter = {
+  paymentMetadataObjAddr?: `0x${string}`;
+  // inclusive, in on-chain unit of the payment token
+  minPaymentPerWinner?: string;
+  maxPaymentPerWinner?: string;
+  status?: BountyStatus;
+  hasStake?: boolean;
+};
+
+// Part of a highlight, render the text as text and style it when it matched the query. Titles and
+// descriptions are user input, never turn them into HTML
+export type HighlightSegment = {
+  text: string;
+  match: boolean;
+};
+
+export type BountySearchResult = {
+  bounty: Bounty;
+  rank: number;
+  title_highlight: HighlightSegment[];
+  description_highlight: HighlightSegment[];
+};
+
+export type BountySearchFacets = {
+  payment_tokens: { payment_metadata_obj_addr: `0x${string}`; count: number }[];
+  statuses: { status: BountyStatus; count: number }[];
+  has_stake: { has_stake: boolean; count: number }[];
+  // null when nothing matches
+  min_payment_per_winner: string | null;
+  max_payment_per_winner: string | null;
+};
+
+// ts_headline wraps matches in these, private use characters that don't appear in normal text
+export const HIGHLIGHT_START = "\uE000";
+export const HIGHLIGHT_STOP = "\uE001";
+
+// Split a ts_headline result on its markers, markers typed by users only split the text further
+export const parseHighlight = (highlight: string): HighlightSegment[] => {
+  const segments: HighlightSegment[] = [];
+  const push = (text: string, match: boolean) => {
+    text = text.replaceAll(HIGHLIGHT_START, "").replaceAll(HIGHLIGHT_STOP, "");
+    if (text.length > 0) {
+      segments.push({ text, match });
+    }
+  };
+  const [before, ...parts] = highlight.split(HIGHLIGHT_START);
+  push(before, false);
+  parts.forEach((part) => {
+    const stop = part.indexOf(HIGHLIGHT_STOP);
+    if (stop === -1) {
+      push(part, false);
+      return;
+    }
+    push(part.slice(0, stop), true);
+    push(part.slice(stop + HIGHLIGHT_STOP.length), false);
+  });
+  return segments;
+};
+
+export const convertDbBountySearchRowToBountySearchResult = (
+  row: Record<string, any>
+): BountySearchResult => {
+  return {
+    bounty: convertDbBountyRowToBounty(row),
+    rank: parseFloat(row.rank),
+    title_highlight: parseHighlight(row.title_highlight),
+    description_highlight: parseHighlight(row.description_highlight),
+  };
+};
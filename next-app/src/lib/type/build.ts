@@ -1,19 +1,35 @@
-import { convertAmountFromOnChainToHumanReadable } from "@aptos-labs/ts-sdk";
 import { APT_UNIT } from "@/lib/aptos";
+import { formatAmount } from "@/lib/utils";
 
 export type Build = {
   build_obj_addr: `0x${string}`;
   bounty_obj_addr: `0x${string}`;
   creator_addr: `0x${string}`;
   payment_recipient_addr: `0x${string}`;
-  payment_amount: number;
+  payment_amount: string;
   create_timestamp: number;
   last_update_timestamp: number;
   proof_link: string;
   build_status: number;
   last_update_event_idx: number;
+  proof_link_kind: ProofLinkKind;
+  proof_link_host: string | null;
+  // GitHub owner, or X user for tweets
+  proof_link_owner: string | null;
+  proof_link_repo: string | null;
+  proof_link_pr_number: number | null;
+  // same for proofs reused across builds
+  proof_link_normalized: string | null;
 };
 
+export type ProofLinkKind =
+  | "github_pr"
+  | "github_commit"
+  | "github_repo"
+  | "github_gist"
+  | "tweet"
+  | "other";
+
 export const convertBuildStatusToHumanReadable = (build: Build) => {
   switch (build.build_status) {
     case 1:
@@ -23,7 +39,7 @@ export const convertBuildStatusToHumanReadable = (build: Build) => {
     case 3:
       return "Cancelled";
     case 4:
-      return `Completed and earned ${convertAmountFromOnChainToHumanReadable(
+      return `Completed and earned ${formatAmount(
         build.payment_amount,
         APT_UNIT
       )} APT`;
@@ -38,11 +54,20 @@ export const convertDbBuildRowToBuild = (row: Record<string, any>): Build => {
     bounty_obj_addr: row.bounty_obj_addr,
     creator_addr: row.creator_addr,
     payment_recipient_addr: row.payment_recipient_addr,
-    payment_amount: parseInt(row.payment_amount),
+    payment_amount: row.payment_amount,
     create_timestamp: parseInt(row.create_timestamp),
     last_update_timestamp: parseInt(row.last_update_timestamp),
     proof_link: row.proof_link,
     build_status: parseInt(row.build_status),
     last_update_event_idx: parseInt(row.last_update_event_idx),
+    proof_link_kind: row.proof_link_kind,
+    proof_link_host: row.proof_link_host,
+    proof_link_owner: row.proof_link_owner,
+    proof_link_repo: row.proof_link_repo,
+    proof_link_pr_number:
+      row.proof_link_pr_number === null
+        ? null
+        : parseInt(row.proof_link_pr_number),
+    proof_link_normalized: row.proof_link_normalized,
   };
 };
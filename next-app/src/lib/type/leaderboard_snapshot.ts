@@ -0,0 +1,46 @@
+export type LeaderboardSeason = "season_1" | "all_time";
+
+export type LeaderboardSnapshot = {
+  season: LeaderboardSeason;
+  snapshot_version: number;
+  snapshot_timestamp: number;
+  user_addr: `0x${string}`;
+  rank: number;
+  points: number;
+};
+
+export type LeaderboardRankDelta = {
+  user_addr: `0x${string}`;
+  rank: number;
+  points: number;
+  // null when the user was not on the previous snapshot
+  previous_rank: number | null;
+  // positive when the user moved up
+  rank_delta: number | null;
+};
+
+export const convertDbLeaderboardSnapshotRowToLeaderboardSnapshot = (
+  row: Record<string, any>
+): LeaderboardSnapshot => {
+  return {
+    season: row.season,
+    snapshot_version: parseInt(row.snapshot_version),
+    snapshot_timestamp: parseInt(row.snapshot_timestamp),
+    user_addr: row.user_addr,
+    rank: parseInt(row.rank),
+    points: parseInt(row.points),
+  };
+};
+
+export const convertDbLeaderboardRankDeltaRowToLeaderboardRankDelta = (
+  row: Record<string, any>
+): LeaderboardRankDelta => {
+  return {
+    user_addr: row.user_addr,
+    rank: parseInt(row.rank),
+    points: parseInt(row.points),
+    previous_rank:
+      row.previous_rank === null ? null : parseInt(row.previous_rank),
+    rank_delta: row.rank_delta === null ? null : parseInt(row.rank_delta),
+  };
+};
@@ -0,0 +1,26 @@
+export type PointsLedgerEntry = {
+  txn_version: number;
+  // on-chain event that awarded the points, e.g. CreateBuildEvent
+  event_type: string;
+  // bounty or build the event is about
+  object_addr: `0x${string}`;
+  user_addr: `0x${string}`;
+  season: string;
+  // negative for penalties
+  points_delta: number;
+  timestamp: number;
+};
+
+export const convertDbPointsLedgerEntryRowToPointsLedgerEntry = (
+  row: Record<string, any>
+): PointsLedgerEntry => {
+  return {
+    txn_version: parseInt(row.txn_version),
+    event_type: row.event_type,
+    object_addr: row.object_addr,
+    user_addr: row.user_addr,
+    season: row.season,
+    points_delta: parseInt(row.points_delta),
+    timestamp: parseInt(row.timestamp),
+  };
+};
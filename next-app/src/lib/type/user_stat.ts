@@ -1,18 +1,26 @@
+import { APT_UNIT, STABLE_UNIT } from "@/lib/aptos";
+import { formatAmount } from "@/lib/utils";
+
 export type UserStat = {
   user_addr: `0x${string}`;
   create_timestamp: number;
   last_update_timestamp: number;
   bounty_created: number;
-  apt_spent: number;
-  stable_spent: number;
+  apt_spent: string;
+  stable_spent: string;
   build_created: number;
   build_submitted_for_review: number;
   build_canceled: number;
   build_completed: number;
-  apt_received: number;
-  stable_received: number;
+  apt_received: string;
+  stable_received: string;
   season_1_points: number;
   total_points: number;
+  // amounts above in whole tokens
+  apt_spent_display: string;
+  stable_spent_display: string;
+  apt_received_display: string;
+  stable_received_display: string;
 };
 
 export const convertDbUserStatRowToUserStat = (
@@ -23,15 +31,19 @@ export const convertDbUserStatRowToUserStat = (
     create_timestamp: parseInt(row.create_timestamp),
     last_update_timestamp: parseInt(row.last_update_timestamp),
     bounty_created: parseInt(row.bounty_created),
-    apt_spent: parseInt(row.apt_spent),
-    stable_spent: parseInt(row.stable_spent),
+    apt_spent: row.apt_spent,
+    stable_spent: row.stable_spent,
     build_created: parseInt(row.build_created),
     build_submitted_for_review: parseInt(row.build_submitted_for_review),
     build_canceled: parseInt(row.build_canceled),
     build_completed: parseInt(row.build_completed),
-    apt_received: parseInt(row.apt_received),
-    stable_received: parseInt(row.stable_received),
+    apt_received: row.apt_received,
+    stable_received: row.stable_received,
     season_1_points: parseInt(row.season_1_points),
     total_points: parseInt(row.total_points),
+    apt_spent_display: formatAmount(row.apt_spent, APT_UNIT),
+    stable_spent_display: formatAmount(row.stable_spent, STABLE_UNIT),
+    apt_received_display: formatAmount(row.apt_received, APT_UNIT),
+    stable_received_display: formatAmount(row.stable_received, STABLE_UNIT),
   };
 };
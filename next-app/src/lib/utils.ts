@@ -5,6 +5,28 @@ export function cn(...inputs: ClassValue[]) {
   return twMerge(clsx(inputs));
 }
 
+// Amounts are NUMERIC in the database and come as strings in the token's smallest unit. Keep them
+// as strings, they can be larger than Number.MAX_SAFE_INTEGER.
+const normalizeAmount = (amount: string) =>
+  amount.split(".")[0].replace(/^0+(?=\d)/, "");
+
+// Amount in whole tokens, e.g. formatAmount("150000000", 8) is "1.5"
+export const formatAmount = (amount: string, decimals: number): string => {
+  const digits = normalizeAmount(amount).padStart(decimals + 1, "0");
+  const whole = digits.slice(0, digits.length - decimals);
+  const fraction = digits.slice(digits.length - decimals).replace(/0+$/, "");
+  return fraction.length > 0 ? `${whole}.${fraction}` : whole;
+};
+
+// Sorts amounts without converting them to numbers
+export const compareAmounts = (a: string, b: string): number => {
+  const [x, y] = [normalizeAmount(a), normalizeAmount(b)];
+  if (x.length !== y.length) {
+    return x.length - y.length;
+  }
+  return x < y ? -1 : x > y ? 1 : 0;
+};
+
 // export const convertPaymentMapToHumanReadable = (payment: any) => {
 //   const paymentMap = new Map<`0x${string}`, number>(
 //     Object.entries(payment) as [`0x${string}`, number][]